@@ -1,67 +1,194 @@
 use crate::{
     event::ChartEvent,
+    format::{DateFormat, NumberFormat},
+    indicator::{IndicatorEntry, INDICATOR_REGISTRY, MAX_INDICATOR_PARAMS},
     reactive::StreamExt,
-    stock::Stock,
+    stock::{NewsHeadline, Stock},
+    theme::ColorScheme,
     widgets::{SelectMenuState, TextFieldState},
 };
 use chrono::{DateTime, Datelike, Duration, Utc};
 use derivative::Derivative;
-use derive_more::{Display, From, Into};
-use derive_new::new;
+use derive_more::Display;
+use im::{HashMap, HashSet, Vector};
 use math::round;
 use once_cell::sync::Lazy;
 use reactive_rs::{Broadcast, Stream};
 use regex::Regex;
-use shrinkwraprs::Shrinkwrap;
+use serde::Deserialize;
 use std::{
-    cell::RefCell, fmt, marker::PhantomData, num::ParseIntError, ops::Range, rc::Rc, str::FromStr,
+    cell::RefCell, cmp, collections::VecDeque, convert::TryFrom, fmt, iter, num::ParseIntError,
+    ops::Range, rc::Rc, str::FromStr,
 };
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 use thiserror::Error;
-use tui::layout::Rect;
-use typenum::{Unsigned, U2, U20, U50};
-use yahoo_finance::Interval;
+use tui::{layout::Rect, symbols::Marker};
 
 #[derive(Clone, Debug)]
 pub struct App<'r> {
+    pub relative_strength_stock: Option<Stock>,
+    /// The stock at the split time frame, while the split view is shown.
+    pub split_stock: Option<Stock>,
     pub stock: Stock,
     pub ui_state: UiState<'r>,
 }
 
-type DateRange = Range<DateTime<Utc>>;
+pub type DateRange = Range<DateTime<Utc>>;
 
 #[derive(Clone, Derivative)]
 #[derivative(Debug)]
 pub struct UiState<'r> {
+    /// Whether the prices are adjusted for dividends, or `None` for the default of the time frame.
+    pub adjusted_close: Option<bool>,
+    pub chart_anchor: ChartAnchor,
+    pub chart_style: ChartStyle,
+    pub chart_tool_state: ChartToolState,
+    #[derivative(Debug = "ignore")]
+    pub chart_viewports: Broadcast<'r, (), ChartViewport>,
+    pub color_scheme: ColorScheme,
+    pub compact_header: bool,
+    /// Step the price of the crosshair is rounded to, or the price clicked if `None`.
+    pub crosshair_step: Option<f64>,
+    pub crossover_periods: Option<CrossoverPeriods>,
+    pub date_format: DateFormat,
     pub date_range: Option<DateRange>,
     pub debug_draw: bool,
+    /// Whether dashed lines mark the earnings dates, past and upcoming.
+    pub earnings: bool,
+    /// Whether a ribbon of exponential moving averages is drawn over the prices.
+    pub ema_ribbon: bool,
+    /// Hypothetical entry price of a position in the stock, drawn as a line with the unrealized
+    /// profit or loss to the latest close in the header.
+    pub entry_price: Option<f64>,
+    pub entry_price_field_state: Rc<RefCell<TextFieldState>>,
+    /// Symbols in the favorites bar, selected with the function keys in order.
+    pub favorites: Vec<String>,
+    pub footer_message: Option<FooterMessage>,
     pub frame_rate_counter: Rc<RefCell<FrameRateCounter>>,
+    pub fullscreen_chart: bool,
+    /// Minimum gap between the open of a bar and the previous close, in percent, to mark on the
+    /// chart.
+    pub gap_threshold: Option<f64>,
+    /// Number of divisions of the price chart by gridlines each way, or no gridlines if `None`.
+    pub gridlines: Option<u16>,
+    /// Series of the price chart hidden from the legend, still listed there to be shown again.
+    pub hidden_series: HashSet<ChartSeries>,
+    /// Indicator hidden with the toggle key, restored on the next press.
+    pub hidden_indicator: Option<Indicator>,
     pub indicator: Option<Indicator>,
     pub indicator_menu_state: Rc<RefCell<SelectMenuState<Indicator>>>,
+    /// Decimal places of the indicator values, or based on their magnitude like prices if `None`.
+    pub indicator_precision: Option<usize>,
+    /// Whether the price axis is flipped so that falling prices go up, for reading short setups.
+    pub inverted_axis: bool,
+    pub indicator_params_field_state: Rc<RefCell<TextFieldState>>,
+    pub line_style: LineStyle,
+    /// Whether the area between the price and the main line of the indicator is shaded by which
+    /// is above.
+    pub ma_shading: bool,
+    /// Most labels on the x-axis, fewer if they don't fit.
+    pub max_x_labels: Option<usize>,
+    /// Whether a strip beneath the charts shows all the bars, with a box around the date range.
+    pub minimap: bool,
+    #[derivative(Debug = "ignore")]
+    pub minimap_viewports: Broadcast<'r, (), Option<ChartViewport>>,
+    pub news_headlines: Vec<NewsHeadline>,
+    pub news_panel_state: ScrollPanelState,
+    pub number_format: NumberFormat,
+    pub pane_split: PaneSplit,
+    /// Labels the price axis with the percent change from the first visible close.
+    pub percent_axis: bool,
+    /// Date of the bar the percent axis is relative to, instead of the first visible bar.
+    pub percent_baseline: Option<DateTime<Utc>>,
+    /// Whether a dashed line marks the close of the bar before the latest one.
+    pub previous_close: bool,
+    pub price_flash: Option<PriceFlash>,
+    /// Whether a panel shows a histogram of the returns over the date range.
+    pub return_histogram: bool,
+    /// Whether the app name and the symbol being loaded are shown until its first fetch completes.
+    pub splash: bool,
+    /// Time frame of the chart shown beside the main one in the split view.
+    pub split_time_frame: TimeFrame,
+    pub split_view: bool,
+    /// Age of the latest bar while the market is open beyond which the data is flagged as stale.
+    pub stale_after: Option<Duration>,
+    pub stats_panel: bool,
     pub stock_symbol_field_state: Rc<RefCell<TextFieldState>>,
+    /// Whether the body shows only the statistics and a sparkline, until expanded to the chart.
+    pub summary_view: bool,
     pub time_frame: TimeFrame,
     pub time_frame_menu_state: Rc<RefCell<SelectMenuState<TimeFrame>>>,
     #[derivative(Debug = "ignore")]
     pub ui_target_areas: Broadcast<'r, (), (UiTarget, Option<Rect>)>,
+    pub up_down_ribbon: bool,
+    /// Number of bars of returns the volatility cone is projected from, or no cone if `None`.
+    pub volatility_cone: Option<u16>,
+    /// Period of the exponential moving average of the volume, drawn over the volume bars.
+    pub volume_ema_period: Option<u16>,
+    pub volume_pane: bool,
 }
 
 impl<'r> Default for UiState<'r> {
     fn default() -> Self {
         Self {
+            adjusted_close: None,
+            chart_anchor: ChartAnchor::default(),
+            chart_style: ChartStyle::default(),
+            chart_tool_state: ChartToolState::default(),
+            chart_viewports: Broadcast::new(),
+            color_scheme: ColorScheme::default(),
+            compact_header: false,
+            crosshair_step: None,
+            crossover_periods: None,
+            date_format: DateFormat::default(),
             date_range: TimeFrame::default().now_date_range(),
             debug_draw: false,
+            earnings: false,
+            ema_ribbon: false,
+            entry_price: None,
+            entry_price_field_state: Rc::new(RefCell::new(TextFieldState::default())),
+            favorites: vec![],
+            footer_message: None,
             frame_rate_counter: Rc::new(RefCell::new(FrameRateCounter::new(
                 Duration::milliseconds(1_000),
             ))),
+            fullscreen_chart: false,
+            gap_threshold: None,
+            gridlines: None,
+            hidden_indicator: None,
+            hidden_series: HashSet::new(),
             indicator: None,
             indicator_menu_state: Rc::new(RefCell::new({
-                let mut menu_state = SelectMenuState::new(Indicator::iter());
+                let mut menu_state = SelectMenuState::new(Indicator::defaults());
                 menu_state.allow_empty_selection = true;
                 menu_state.select(None).unwrap();
                 menu_state
             })),
+            indicator_precision: None,
+            inverted_axis: false,
+            indicator_params_field_state: Rc::new(RefCell::new(TextFieldState::default())),
+            line_style: LineStyle::default(),
+            ma_shading: false,
+            max_x_labels: None,
+            minimap: false,
+            minimap_viewports: Broadcast::new(),
+            news_headlines: vec![],
+            news_panel_state: ScrollPanelState::default(),
+            number_format: NumberFormat::default(),
+            pane_split: PaneSplit::default(),
+            percent_axis: false,
+            percent_baseline: None,
+            previous_close: false,
+            price_flash: None,
+            return_histogram: false,
+            splash: false,
+            split_time_frame: TimeFrame::OneYear,
+            split_view: false,
+            stale_after: None,
+            stats_panel: false,
             stock_symbol_field_state: Rc::new(RefCell::new(TextFieldState::default())),
+            summary_view: false,
             time_frame: TimeFrame::default(),
             time_frame_menu_state: Rc::new(RefCell::new({
                 let mut menu_state = SelectMenuState::new(TimeFrame::iter());
@@ -69,10 +196,24 @@ impl<'r> Default for UiState<'r> {
                 menu_state
             })),
             ui_target_areas: Broadcast::new(),
+            up_down_ribbon: false,
+            volatility_cone: None,
+            volume_ema_period: None,
+            volume_pane: false,
         }
     }
 }
 
+impl<'r> UiState<'r> {
+    pub fn is_adjusted_close(&self) -> bool {
+        self.adjusted_close
+            .unwrap_or_else(|| self.time_frame.default_adjusted_close())
+    }
+}
+
+/// Number of steps of a smooth pan in the duration of the time frame.
+const PAN_STEPS: i32 = 10;
+
 pub fn to_date_ranges<'a, S, U, R, C>(
     chart_events: S,
     stock_symbols: U,
@@ -124,24 +265,30 @@ where
                 }
 
                 match ev {
-                    ChartEvent::PanBackward if time_frame != &TimeFrame::YearToDate => {
+                    ChartEvent::PanBackward | ChartEvent::StepBackward
+                        if time_frame != &TimeFrame::YearToDate =>
+                    {
                         let date_range = time_frame.duration().map(|duration| {
+                            let step = pan_step(*ev, duration);
                             acc_date_range
                                 .as_ref()
                                 .map(|acc_date_range| {
-                                    let end_date = acc_date_range.start;
+                                    let end_date = acc_date_range.end - step;
                                     (end_date - duration)..end_date
                                 })
                                 .unwrap()
                         });
                         (date_range, stock_symbol.clone(), *time_frame)
                     }
-                    ChartEvent::PanForward if time_frame != &TimeFrame::YearToDate => {
+                    ChartEvent::PanForward | ChartEvent::StepForward
+                        if time_frame != &TimeFrame::YearToDate =>
+                    {
                         let date_range = time_frame.duration().map(|duration| {
+                            let step = pan_step(*ev, duration);
                             acc_date_range
                                 .as_ref()
                                 .map(|acc_date_range| {
-                                    let start_date = acc_date_range.end;
+                                    let start_date = acc_date_range.start + step;
                                     start_date..(start_date + duration)
                                 })
                                 .map(|date_range| {
@@ -156,6 +303,19 @@ where
                         });
                         (date_range, stock_symbol.clone(), *time_frame)
                     }
+                    ChartEvent::GoTo(date) if time_frame != &TimeFrame::YearToDate => {
+                        let date_range = time_frame.duration().map(|duration| {
+                            let start_date = *date - duration / 2;
+                            let date_range = start_date..(start_date + duration);
+                            let max_date_range = time_frame.now_date_range().unwrap();
+                            if date_range.end > max_date_range.end {
+                                max_date_range
+                            } else {
+                                date_range
+                            }
+                        });
+                        (date_range, stock_symbol.clone(), *time_frame)
+                    }
                     ChartEvent::Reset => reset(),
                     _ => noop(),
                 }
@@ -165,10 +325,98 @@ where
         .distinct_until_changed()
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// Returns how far the chart event pans the date range, a whole duration of the time frame or a
+/// step of it.
+fn pan_step(ev: ChartEvent, duration: Duration) -> Duration {
+    match ev {
+        ChartEvent::StepBackward | ChartEvent::StepForward => duration / PAN_STEPS,
+        _ => duration,
+    }
+}
+
+/// Scales the period of the indicator by the ratio of the period scales of the time frames, from
+/// the time frame the indicator was chosen at to the current one.
+///
+/// The period is kept as chosen if either time frame has no period scale.
+pub fn to_scaled_indicators<'a, S, R, C>(
+    indicators: S,
+    time_frames: R,
+    init_indicator: Option<Indicator>,
+    init_time_frame: TimeFrame,
+    period_scales: HashMap<TimeFrame, f64>,
+) -> impl Stream<'a, Item = Option<Indicator>, Context = C>
+where
+    S: Stream<'a, Item = Option<Indicator>, Context = C>,
+    R: Stream<'a, Item = TimeFrame>,
+    C: 'a + Clone,
+{
+    indicators
+        .combine_latest(time_frames, |(indicator, time_frame)| {
+            (*indicator, *time_frame)
+        })
+        .fold(
+            (init_indicator, init_time_frame, init_time_frame),
+            |&(acc_indicator, acc_indicator_time_frame, _), &(indicator, time_frame)| {
+                if indicator != acc_indicator {
+                    (indicator, time_frame, time_frame)
+                } else {
+                    (acc_indicator, acc_indicator_time_frame, time_frame)
+                }
+            },
+        )
+        .map(move |&(indicator, indicator_time_frame, time_frame)| {
+            let scale = period_scales
+                .get(&indicator_time_frame)
+                .and_then(|from_scale| {
+                    period_scales
+                        .get(&time_frame)
+                        .map(|to_scale| to_scale / from_scale)
+                });
+            match scale {
+                Some(scale) => indicator.map(|indicator| indicator.with_period_scaled(scale)),
+                None => indicator,
+            }
+        })
+        .distinct_until_changed()
+}
+
+/// Whether the prices are adjusted for dividends, toggled from the default of the time frame, which
+/// is used while `None`. A new time frame, or resetting the view, restores the default.
+pub fn to_adjusted_closes<'a, S, R, T, C>(
+    toggles: S,
+    resets: R,
+    time_frames: T,
+) -> impl Stream<'a, Item = Option<bool>, Context = C>
+where
+    S: Stream<'a, Item = (), Context = C>,
+    R: Stream<'a, Item = ()>,
+    T: Stream<'a, Item = TimeFrame> + Clone,
+    C: 'a + Clone,
+{
+    toggles
+        .with_latest_from(time_frames.clone(), |(_, time_frame)| Some(*time_frame))
+        .merge(time_frames.map(|_| None))
+        .merge(resets.map(|_| None))
+        .fold(
+            None,
+            |acc_adjusted_close: &Option<bool>, toggled_time_frame| {
+                toggled_time_frame.map(|time_frame: TimeFrame| {
+                    !acc_adjusted_close.unwrap_or_else(|| time_frame.default_adjusted_close())
+                })
+            },
+        )
+        .distinct_until_changed()
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, Eq, Hash, PartialEq)]
 pub enum UiTarget {
+    Chart,
+    EntryPriceField,
+    FavoritesBar,
     IndicatorBox,
     IndicatorMenu,
+    IndicatorParamsField,
+    NewsPanel,
     StockNameButton,
     StockSymbolButton,
     StockSymbolField,
@@ -176,24 +424,715 @@ pub enum UiTarget {
     TimeFrameMenu,
 }
 
+/// Maximum number of favorites, one for each function key.
+pub const MAX_FAVORITES: usize = 12;
+
+/// Label of the favorite in the favorites bar, with the function key selecting it.
+pub fn favorite_label(index: usize, symbol: &str) -> String {
+    format!("F{} {}", index + 1, symbol)
+}
+
+/// Areas of the favorites in the favorites bar, from the left, leaving out those that don't fit.
+pub fn favorite_slot_areas(favorites: &[String], area: Rect) -> Vec<Rect> {
+    favorites
+        .iter()
+        .enumerate()
+        .scan(area.left(), |x, (i, symbol)| {
+            let width = favorite_label(i, symbol).chars().count() as u16;
+            if *x + width > area.right() {
+                return None;
+            }
+            let slot_area = Rect::new(*x, area.top(), width, cmp::min(area.height, 1));
+            *x += width + 2;
+            Some(slot_area)
+        })
+        .collect()
+}
+
+/// Returns the shortest time frame covering the history since the earliest bar, if the time frame
+/// is more than twice as long as the history, and by more than a month, leaving its chart mostly
+/// empty.
+pub fn fitting_time_frame(
+    time_frame: TimeFrame,
+    earliest: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Option<TimeFrame> {
+    let history = now - earliest;
+    let duration = time_frame.duration()?;
+    if duration <= history * 2 || duration - history <= Duration::days(30) {
+        return None;
+    }
+
+    TimeFrame::iter()
+        .filter(|fitting| fitting.duration().map_or(false, |d| d >= history))
+        .min_by_key(|fitting| fitting.duration())
+}
+
+/// Returns the favorites with the symbol moved `step` places along the bar, stopping at either end.
+/// Favorites without the symbol are returned as they are.
+pub fn move_favorite(favorites: &[String], symbol: &str, step: isize) -> Vec<String> {
+    let mut favorites = favorites.to_vec();
+    if let Some(i) = favorites.iter().position(|favorite| favorite == symbol) {
+        let j = (i as isize + step).max(0).min(favorites.len() as isize - 1) as usize;
+        let favorite = favorites.remove(i);
+        favorites.insert(j, favorite);
+    }
+    favorites
+}
+
+/// Overlay that can be opened on launch.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Overlay {
+    Indicator,
+    News,
+    Symbol,
+    TimeFrame,
+}
+
+impl Overlay {
+    pub fn ui_target(self) -> UiTarget {
+        match self {
+            Self::Indicator => UiTarget::IndicatorMenu,
+            Self::News => UiTarget::NewsPanel,
+            Self::Symbol => UiTarget::StockSymbolField,
+            Self::TimeFrame => UiTarget::TimeFrameMenu,
+        }
+    }
+}
+
+impl FromStr for Overlay {
+    type Err = ParseOverlayError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "indicator" => Ok(Self::Indicator),
+            "news" => Ok(Self::News),
+            "symbol" => Ok(Self::Symbol),
+            "time-frame" => Ok(Self::TimeFrame),
+            "" => Err(ParseOverlayError::Empty),
+            _ => Err(ParseOverlayError::Invalid),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseOverlayError {
+    #[error("cannot parse overlay from empty string")]
+    Empty,
+    #[error("invalid overlay literal")]
+    Invalid,
+}
+
+/// Maps the timestamps of the bars to their positions on the x-axis, one apart, so that days
+/// without trading such as weekends and holidays leave no gaps in the chart.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TradingDayAxis {
+    timestamps: Rc<Vec<f64>>,
+}
+
+impl TradingDayAxis {
+    const SECONDS_PER_DAY: f64 = 86400f64;
+
+    /// The timestamps must be sorted and distinct.
+    pub fn new(timestamps: Vec<f64>) -> Self {
+        Self {
+            timestamps: Rc::new(timestamps),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timestamps.is_empty()
+    }
+
+    /// Converts a timestamp to a position on the axis. Timestamps between bars are interpolated,
+    /// and those beyond the bars are extrapolated from the nearest two.
+    pub fn to_x(&self, timestamp: f64) -> f64 {
+        let timestamps = &self.timestamps;
+        if timestamps.len() < 2 {
+            let first = timestamps.first().copied().unwrap_or(0f64);
+            return (timestamp - first) / Self::SECONDS_PER_DAY;
+        }
+
+        let i = match timestamps
+            .binary_search_by(|t| t.partial_cmp(&timestamp).unwrap_or(cmp::Ordering::Equal))
+        {
+            Ok(i) => return i as f64,
+            Err(i) => cmp::min(cmp::max(i, 1), timestamps.len() - 1),
+        };
+        let (start, end) = (timestamps[i - 1], timestamps[i]);
+
+        (i - 1) as f64 + (timestamp - start) / (end - start)
+    }
+
+    /// Converts a position on the axis back to a timestamp.
+    pub fn to_timestamp(&self, x: f64) -> f64 {
+        let timestamps = &self.timestamps;
+        if timestamps.len() < 2 {
+            let first = timestamps.first().copied().unwrap_or(0f64);
+            return first + x * Self::SECONDS_PER_DAY;
+        }
+
+        let i = (x.floor() + 1f64)
+            .max(1f64)
+            .min((timestamps.len() - 1) as f64) as usize;
+        let (start, end) = (timestamps[i - 1], timestamps[i]);
+
+        start + (x - (i - 1) as f64) * (end - start)
+    }
+}
+
+/// The plotting area of a chart, along with the bounds of the data plotted in it.
+///
+/// The data space has timestamps on the x-axis, while the bounds are in positions on the trading
+/// day axis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartViewport {
+    pub area: Rect,
+    pub x_axis: TradingDayAxis,
+    pub x_bounds: [f64; 2],
+    pub y_bounds: [f64; 2],
+}
+
+impl ChartViewport {
+    /// Converts a point on the screen to a point in the data space.
+    pub fn point_to_value(&self, (x, y): (u16, u16)) -> Option<(f64, f64)> {
+        let area = self.area;
+
+        if area.left() > x || area.right() <= x || area.top() > y || area.bottom() <= y {
+            return None;
+        }
+
+        let x_ratio = if area.width > 1 {
+            (x - area.left()) as f64 / (area.width - 1) as f64
+        } else {
+            0f64
+        };
+        let y_ratio = if area.height > 1 {
+            (area.bottom() - 1 - y) as f64 / (area.height - 1) as f64
+        } else {
+            0f64
+        };
+
+        let [x_min, x_max] = self.x_bounds;
+        let [y_min, y_max] = self.y_bounds;
+        let value = (
+            self.x_axis.to_timestamp(x_min + x_ratio * (x_max - x_min)),
+            y_min + y_ratio * (y_max - y_min),
+        );
+
+        if value.0.is_finite() && value.1.is_finite() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// Converts a point in the data space to a point on the screen.
+    pub fn value_to_point(&self, (vx, vy): (f64, f64)) -> Option<(u16, u16)> {
+        let area = self.area;
+
+        let [x_min, x_max] = self.x_bounds;
+        let [y_min, y_max] = self.y_bounds;
+        let x_ratio = (self.x_axis.to_x(vx) - x_min) / (x_max - x_min);
+        let y_ratio = (vy - y_min) / (y_max - y_min);
+
+        if !(0f64..=1f64).contains(&x_ratio)
+            || !(0f64..=1f64).contains(&y_ratio)
+            || area.width == 0
+            || area.height == 0
+        {
+            return None;
+        }
+
+        let x = area.left() + round::half_to_even(x_ratio * (area.width - 1) as f64, 0) as u16;
+        let y =
+            area.bottom() - 1 - round::half_to_even(y_ratio * (area.height - 1) as f64, 0) as u16;
+
+        Some((x, y))
+    }
+}
+
+/// What the right edge of the chart is pinned to: the last bar, packing the bars tightly, or now,
+/// leaving room for the forming bar to fill as the session progresses.
+#[derive(Clone, Copy, Debug, Derivative, Eq, PartialEq)]
+#[derivative(Default)]
+pub enum ChartAnchor {
+    #[derivative(Default)]
+    LastBar,
+    Now,
+}
+
+impl FromStr for ChartAnchor {
+    type Err = ParseChartAnchorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "last-bar" => Ok(Self::LastBar),
+            "now" => Ok(Self::Now),
+            "" => Err(ParseChartAnchorError::Empty),
+            _ => Err(ParseChartAnchorError::Invalid),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseChartAnchorError {
+    #[error("cannot parse chart anchor from empty string")]
+    Empty,
+    #[error("invalid chart anchor literal")]
+    Invalid,
+}
+
+/// Marker used to draw the chart lines. Braille has the highest resolution, but does not render
+/// well in some terminal fonts.
+#[derive(Clone, Copy, Debug, Derivative, Eq, PartialEq)]
+#[derivative(Default)]
+pub enum ChartStyle {
+    Block,
+    #[derivative(Default)]
+    Braille,
+    Dot,
+}
+
+impl ChartStyle {
+    pub fn marker(self) -> Marker {
+        match self {
+            Self::Block => Marker::Block,
+            Self::Braille => Marker::Braille,
+            Self::Dot => Marker::Dot,
+        }
+    }
+}
+
+impl FromStr for ChartStyle {
+    type Err = ParseChartStyleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "block" => Ok(Self::Block),
+            "braille" => Ok(Self::Braille),
+            "dot" => Ok(Self::Dot),
+            "" => Err(ParseChartStyleError::Empty),
+            _ => Err(ParseChartStyleError::Invalid),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseChartStyleError {
+    #[error("cannot parse chart style from empty string")]
+    Empty,
+    #[error("invalid chart style literal")]
+    Invalid,
+}
+
+/// How the price line connects the bars: straight lines, a smooth curve, or only a marker at each
+/// bar.
+#[derive(Clone, Copy, Debug, Derivative, Eq, PartialEq)]
+#[derivative(Default)]
+pub enum LineStyle {
+    #[derivative(Default)]
+    Line,
+    Markers,
+    Smooth,
+}
+
+impl FromStr for LineStyle {
+    type Err = ParseLineStyleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "line" => Ok(Self::Line),
+            "markers" => Ok(Self::Markers),
+            "smooth" => Ok(Self::Smooth),
+            "" => Err(ParseLineStyleError::Empty),
+            _ => Err(ParseLineStyleError::Invalid),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseLineStyleError {
+    #[error("cannot parse line style from empty string")]
+    Empty,
+    #[error("invalid line style literal")]
+    Invalid,
+}
+
+/// Periods of the fast and slow simple moving averages whose crossings are marked on the chart.
+#[derive(Clone, Copy, Debug, Display, Eq, PartialEq)]
+#[display(fmt = "SMA({}/{})", fast, slow)]
+pub struct CrossoverPeriods {
+    pub fast: u16,
+    pub slow: u16,
+}
+
+impl FromStr for CrossoverPeriods {
+    type Err = ParseCrossoverPeriodsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseCrossoverPeriodsError::Empty);
+        }
+
+        let mut periods = s.split(',').map(|n| n.trim().parse::<u16>());
+        match (periods.next(), periods.next(), periods.next()) {
+            (Some(Ok(fast)), Some(Ok(slow)), None) if 0 < fast && fast < slow => {
+                Ok(Self { fast, slow })
+            }
+            _ => Err(ParseCrossoverPeriodsError::Invalid),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseCrossoverPeriodsError {
+    #[error("cannot parse crossover periods from empty string")]
+    Empty,
+    #[error("invalid crossover periods, expected <fast>,<slow> with fast < slow")]
+    Invalid,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ChartTool {
+    FibonacciRetracement,
+    /// Reports the change between two bars in the footer.
+    Measure,
+    Trendline,
+}
+
+impl ChartTool {
+    pub fn anchor_count(self) -> usize {
+        match self {
+            Self::FibonacciRetracement | Self::Measure | Self::Trendline => 2,
+        }
+    }
+}
+
+/// Series of the price chart listed in the legend when shown, numbered in this order.
+#[derive(Clone, Copy, Debug, EnumIter, Eq, Hash, PartialEq)]
+pub enum ChartSeries {
+    Price,
+    Indicator,
+    Crossovers,
+    EmaRibbon,
+}
+
+impl ChartSeries {
+    /// Returns the series numbered by the digit, toggled with Alt and the digit.
+    pub fn from_hotkey(c: char) -> Option<Self> {
+        let n = c.to_digit(10)? as usize;
+        Self::iter().nth(n.checked_sub(1)?)
+    }
+
+    pub fn number(self) -> usize {
+        Self::iter().position(|series| series == self).unwrap() + 1
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ScrollPanelState {
+    pub active: bool,
+    /// Index of the first item shown.
+    pub scroll: usize,
+}
+
+/// Anchors placed on the chart by each tool, in the chart's data space.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChartToolState {
+    /// The tool currently placing anchors, if any.
+    pub active: Option<ChartTool>,
+    pub anchors: HashMap<ChartTool, Vec<(f64, f64)>>,
+    /// The point last clicked while no tool was placing anchors.
+    pub crosshair: Option<(f64, f64)>,
+}
+
+impl ChartToolState {
+    /// Starts placing anchors for the tool, or clears its anchors if it has any.
+    pub fn toggle(&self, chart_tool: ChartTool) -> Self {
+        if self.anchors.contains_key(&chart_tool) {
+            return Self {
+                active: self.active.filter(|&active| active != chart_tool),
+                anchors: self.anchors.without(&chart_tool),
+                ..self.clone()
+            };
+        }
+
+        // Incomplete anchors of the previously active tool are discarded.
+        let anchors = match self.active {
+            Some(active) => self.anchors.without(&active),
+            None => self.anchors.clone(),
+        };
+
+        Self {
+            active: Some(chart_tool),
+            anchors: anchors.update(chart_tool, vec![]),
+            ..self.clone()
+        }
+    }
+
+    /// Places an anchor for the active tool, or moves the crosshair if no tool is active.
+    pub fn add_anchor(&self, point: (f64, f64)) -> Self {
+        let chart_tool = match self.active {
+            Some(chart_tool) => chart_tool,
+            None => {
+                return Self {
+                    crosshair: Some(point),
+                    ..self.clone()
+                }
+            }
+        };
+
+        let mut anchors = self.anchors.get(&chart_tool).cloned().unwrap_or_default();
+        anchors.push(point);
+
+        Self {
+            active: if anchors.len() < chart_tool.anchor_count() {
+                Some(chart_tool)
+            } else {
+                None
+            },
+            anchors: self.anchors.update(chart_tool, anchors),
+            ..self.clone()
+        }
+    }
+
+    pub fn clear_crosshair(&self) -> Self {
+        Self {
+            crosshair: None,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the anchors of the tool, if all of them have been placed.
+    pub fn complete_anchors(&self, chart_tool: ChartTool) -> Option<&[(f64, f64)]> {
+        self.anchors
+            .get(&chart_tool)
+            .filter(|anchors| anchors.len() == chart_tool.anchor_count())
+            .map(|anchors| anchors.as_slice())
+    }
+}
+
+/// Bounded undo/redo history of a value.
+#[derive(Clone, Debug)]
+pub struct History<T: Clone> {
+    capacity: usize,
+    future: Vector<T>,
+    past: Vector<T>,
+    present: T,
+}
+
+impl<T: Clone + PartialEq> History<T> {
+    pub fn new(present: T, capacity: usize) -> Self {
+        Self {
+            capacity,
+            future: Vector::new(),
+            past: Vector::new(),
+            present,
+        }
+    }
+
+    pub fn present(&self) -> &T {
+        &self.present
+    }
+
+    /// Makes the value the present one, discarding the redo history. The oldest value is dropped
+    /// if the capacity is exceeded.
+    pub fn push(&self, value: T) -> Self {
+        if value == self.present {
+            return self.clone();
+        }
+
+        let mut past = self.past.clone();
+        past.push_back(self.present.clone());
+        while past.len() > self.capacity {
+            past.pop_front();
+        }
+
+        Self {
+            future: Vector::new(),
+            past,
+            present: value,
+            ..self.clone()
+        }
+    }
+
+    pub fn undo(&self) -> Self {
+        let mut past = self.past.clone();
+        match past.pop_back() {
+            Some(value) => {
+                let mut future = self.future.clone();
+                future.push_front(self.present.clone());
+                Self {
+                    future,
+                    past,
+                    present: value,
+                    ..self.clone()
+                }
+            }
+            None => self.clone(),
+        }
+    }
+
+    pub fn redo(&self) -> Self {
+        let mut future = self.future.clone();
+        match future.pop_front() {
+            Some(value) => {
+                let mut past = self.past.clone();
+                past.push_back(self.present.clone());
+                Self {
+                    future,
+                    past,
+                    present: value,
+                    ..self.clone()
+                }
+            }
+            None => self.clone(),
+        }
+    }
+}
+
+/// Percentage of the chart height given to the price chart, with the rest going to the sub-pane.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PaneSplit(u16);
+
+impl Default for PaneSplit {
+    fn default() -> Self {
+        Self(70)
+    }
+}
+
+impl PaneSplit {
+    pub const MAX_PERCENT: u16 = 80;
+    pub const MIN_PERCENT: u16 = 20;
+    const STEP_PERCENT: u16 = 10;
+
+    /// Returns the split for the percentage, clamped to the allowed range.
+    pub fn new(price_percent: u16) -> Self {
+        Self(price_percent.max(Self::MIN_PERCENT).min(Self::MAX_PERCENT))
+    }
+
+    pub fn price_percent(self) -> u16 {
+        self.0
+    }
+
+    pub fn sub_pane_percent(self) -> u16 {
+        100 - self.0
+    }
+
+    pub fn grow(self) -> Self {
+        Self::new(self.0 + Self::STEP_PERCENT)
+    }
+
+    pub fn shrink(self) -> Self {
+        Self::new(self.0.saturating_sub(Self::STEP_PERCENT))
+    }
+}
+
+/// Message shown in the footer for a number of ticks.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FooterMessage {
+    pub text: String,
+    remaining_ticks: u8,
+}
+
+impl FooterMessage {
+    const TICKS: u8 = 30;
+
+    pub fn new(text: String) -> Self {
+        Self {
+            text,
+            remaining_ticks: Self::TICKS,
+        }
+    }
+
+    /// Returns the message for the next tick, or `None` once it has expired.
+    pub fn tick(self) -> Option<Self> {
+        if self.remaining_ticks > 1 {
+            Some(Self {
+                remaining_ticks: self.remaining_ticks - 1,
+                ..self
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Highlight of the latest price after it changes, fading out over a number of ticks.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PriceFlash {
+    pub direction: PriceDirection,
+    pub remaining_ticks: u8,
+}
+
+impl PriceFlash {
+    const TICKS: u8 = 10;
+
+    pub fn new(direction: PriceDirection) -> Self {
+        Self {
+            direction,
+            remaining_ticks: Self::TICKS,
+        }
+    }
+
+    /// Returns the flash for the next tick, or `None` once it has faded out.
+    pub fn tick(self) -> Option<Self> {
+        if self.remaining_ticks > 1 {
+            Some(Self {
+                remaining_ticks: self.remaining_ticks - 1,
+                ..self
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn is_fading(self) -> bool {
+        self.remaining_ticks <= Self::TICKS / 2
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PriceDirection {
+    Down,
+    Up,
+}
+
 #[derive(Debug)]
 pub struct FrameRateCounter {
-    frame_time: u16,
     frames: u16,
     last_interval: DateTime<Utc>,
+    /// Frame times of the latest intervals, oldest first, for the smoothed frame time.
+    recent_frame_times: VecDeque<u16>,
+    smoothing_window: usize,
     update_interval: Duration,
 }
 
 impl FrameRateCounter {
     pub fn new(update_interval: Duration) -> Self {
         Self {
-            frame_time: 0,
             frames: 0,
             last_interval: Utc::now(),
+            recent_frame_times: VecDeque::new(),
+            smoothing_window: 1,
             update_interval,
         }
     }
 
+    /// Returns the counter with the smoothed frame time averaged over the given number of update
+    /// intervals, at least one.
+    pub fn smoothed(self, smoothing_window: usize) -> Self {
+        Self {
+            smoothing_window: cmp::max(smoothing_window, 1),
+            ..self
+        }
+    }
+
     /// Increments the counter. Returns the frame time if the update interval has elapsed.
     pub fn incr(&mut self) -> Option<Duration> {
         self.frames += 1;
@@ -204,7 +1143,7 @@ impl FrameRateCounter {
             let frame_time =
                 (now - self.last_interval).num_milliseconds() as f64 / self.frames as f64;
             let frame_time = round::floor(frame_time, 0) as u16;
-            self.frame_time = frame_time;
+            self.record(frame_time);
 
             self.frames = 0;
 
@@ -216,72 +1155,195 @@ impl FrameRateCounter {
         None
     }
 
-    pub fn frame_time(&self) -> Option<Duration> {
-        match self.frame_time {
+    /// Returns the average frame time of the latest intervals within the smoothing window.
+    pub fn smoothed_frame_time(&self) -> Option<Duration> {
+        if self.recent_frame_times.is_empty() {
+            return None;
+        }
+
+        let sum: u32 = self.recent_frame_times.iter().map(|&t| t as u32).sum();
+        match sum / self.recent_frame_times.len() as u32 {
             0 => None,
             frame_time => Some(Duration::milliseconds(frame_time as i64)),
         }
     }
+
+    fn record(&mut self, frame_time: u16) {
+        if self.recent_frame_times.len() == self.smoothing_window {
+            self.recent_frame_times.pop_front();
+        }
+        self.recent_frame_times.push_back(frame_time);
+    }
 }
 
-#[derive(Clone, Copy, Debug, EnumIter, Eq, PartialEq)]
-pub enum Indicator {
-    BollingerBands(Period<U20>, StdDevMultiplier<U2>),
-    ExponentialMovingAverage(Period<U50>),
-    // MovingAverageConvergenceDivergence,
-    // RelativeStrengthIndex,
-    SimpleMovingAverage(Period<U50>),
+/// An indicator of the registry with its parameters, e.g. `SMA(50)`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Indicator {
+    kind: IndicatorKind,
+    /// The parameters in the order they are written, followed by zeros.
+    params: [u16; MAX_INDICATOR_PARAMS],
 }
 
-#[derive(
-    Clone, Copy, Debug, Display, Eq, From, Into, new, Ord, PartialEq, PartialOrd, Shrinkwrap,
-)]
-#[display(fmt = "{}", _0)]
-pub struct Period<D: Unsigned>(#[shrinkwrap(main_field)] u16, PhantomData<*const D>);
+impl Indicator {
+    /// Returns the indicator of the kind with its default parameters.
+    pub fn new(kind: IndicatorKind) -> Self {
+        let mut params = [0; MAX_INDICATOR_PARAMS];
+        for (param, entry_param) in params.iter_mut().zip(kind.entry().params) {
+            *param = entry_param.default;
+        }
 
-impl<D> Default for Period<D>
-where
-    D: Unsigned,
-{
-    fn default() -> Self {
-        Self::new(D::to_u16())
+        Self { kind, params }
+    }
+
+    /// Returns the indicators of the registry with their default parameters, in its order.
+    pub fn defaults() -> impl Iterator<Item = Self> {
+        INDICATOR_REGISTRY
+            .iter()
+            .map(|entry| Self::new(IndicatorKind(entry.name)))
+    }
+
+    pub fn kind(self) -> IndicatorKind {
+        self.kind
+    }
+
+    /// The parameters, in the order they are written.
+    pub fn params(&self) -> &[u16] {
+        &self.params[..self.kind.entry().params.len()]
+    }
+
+    /// Names of the parameters, in the order they are written.
+    pub fn param_names(self) -> Vec<&'static str> {
+        self.kind
+            .entry()
+            .params
+            .iter()
+            .map(|param| param.name)
+            .collect()
+    }
+
+    /// Returns the indicator with its periods set to the period.
+    pub fn with_period(self, period: u16) -> Self {
+        self.map_periods(|_| period)
+    }
+
+    /// Returns the indicator with its periods multiplied by the scale, rounded and at least 1.
+    pub fn with_period_scaled(self, scale: f64) -> Self {
+        self.map_periods(|n| {
+            (f64::from(n) * scale)
+                .round()
+                .max(1f64)
+                .min(f64::from(u16::MAX)) as u16
+        })
+    }
+
+    /// Returns the indicator of the same kind with the comma separated parameters, e.g. `20, 2`.
+    pub fn with_params(self, params: &str) -> Result<Self, ParseIndicatorError> {
+        let names = self.param_names();
+        let params: Vec<_> = params.split(',').map(str::trim).collect();
+        if params.len() != names.len() {
+            return Err(ParseIndicatorError::ParamCount {
+                expected: names.len(),
+                found: params.len(),
+            });
+        }
+
+        let mut indicator = self;
+        for (i, (name, value)) in names.iter().zip(params).enumerate() {
+            indicator.params[i] = parse_param(name, value)?;
+        }
+
+        Ok(indicator)
+    }
+
+    fn map_periods<F>(self, f: F) -> Self
+    where
+        F: Fn(u16) -> u16,
+    {
+        let mut indicator = self;
+        for (param, entry_param) in indicator.params.iter_mut().zip(self.kind.entry().params) {
+            if entry_param.is_period {
+                *param = f(*param);
+            }
+        }
+
+        indicator
     }
 }
 
-impl<D> FromStr for Period<D>
-where
-    D: Unsigned,
-{
-    type Err = <u16 as FromStr>::Err;
+/// Returns the indicator `step` places from the indicator among the indicators, with none before the
+/// first, wrapping around at either end. Indicators are matched by kind, whatever their parameters.
+pub fn cycle_indicator(
+    indicator: Option<Indicator>,
+    indicators: &[Indicator],
+    step: isize,
+) -> Option<Indicator> {
+    let cycle: Vec<_> = iter::once(None)
+        .chain(indicators.iter().copied().map(Some))
+        .collect();
+    let i = cycle
+        .iter()
+        .position(|cycle_indicator| {
+            cycle_indicator.map(Indicator::kind) == indicator.map(Indicator::kind)
+        })
+        .unwrap_or(0);
+    let n = cycle.len() as isize;
+
+    cycle[(i as isize + step).rem_euclid(n) as usize]
+}
 
-    fn from_str(src: &str) -> Result<Self, Self::Err> {
-        Ok(Self::new(u16::from_str(src)?))
+fn parse_param(name: &str, value: &str) -> Result<u16, ParseIndicatorError> {
+    let param: u16 = value.parse().map_err(|err| ParseIndicatorError::ParseInt {
+        name: name.to_owned(),
+        source: err,
+        value: value.to_owned(),
+    })?;
+    // a zero period or multiplier would leave nothing to plot, checked on the parsed value as e.g.
+    // `+0` parses too
+    if param == 0 {
+        return Err(ParseIndicatorError::NotPositive {
+            name: name.to_owned(),
+        });
     }
+
+    Ok(param)
 }
 
-#[derive(
-    Clone, Copy, Debug, Display, Eq, From, Into, new, Ord, PartialEq, PartialOrd, Shrinkwrap,
-)]
+/// Indicator without its parameters, by the name of its entry in the registry. The config file
+/// names it either way, e.g. `sma` or `simple_moving_average`.
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, Hash, PartialEq)]
 #[display(fmt = "{}", _0)]
-pub struct StdDevMultiplier<D: Unsigned>(#[shrinkwrap(main_field)] u8, PhantomData<*const D>);
+#[serde(try_from = "String")]
+pub struct IndicatorKind(&'static str);
 
-impl<D> Default for StdDevMultiplier<D>
-where
-    D: Unsigned,
-{
-    fn default() -> Self {
-        Self::new(D::to_u8())
+impl IndicatorKind {
+    pub fn entry(self) -> &'static IndicatorEntry {
+        INDICATOR_REGISTRY
+            .iter()
+            .find(|entry| entry.name == self.0)
+            .expect("indicator kinds are only made from the registry")
     }
 }
 
-impl<D> FromStr for StdDevMultiplier<D>
-where
-    D: Unsigned,
-{
-    type Err = <u8 as FromStr>::Err;
+impl FromStr for IndicatorKind {
+    type Err = ParseIndicatorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "" {
+            return Err(ParseIndicatorError::Empty);
+        }
+        INDICATOR_REGISTRY
+            .iter()
+            .find(|entry| entry.name.eq_ignore_ascii_case(s) || entry.long_name == s)
+            .map(|entry| Self(entry.name))
+            .ok_or(ParseIndicatorError::Invalid)
+    }
+}
+
+impl TryFrom<String> for IndicatorKind {
+    type Error = ParseIndicatorError;
 
-    fn from_str(src: &str) -> Result<Self, Self::Err> {
-        Ok(Self::new(u8::from_str(src)?))
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
     }
 }
 
@@ -289,49 +1351,20 @@ impl FromStr for Indicator {
     type Err = ParseIndicatorError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        const BB_PATTERN: &str = r"BB\s*\(\s*(?P<n>\d+)\s*,\s*(?P<k>\d+)\s*\)";
-        const EMA_PATTERN: &str = r"EMA\s*\(\s*(?P<n>\d+)\s*\)";
-        const SMA_PATTERN: &str = r"SMA\s*\(\s*(?P<n>\d+)\s*\)";
-
-        static BB_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(BB_PATTERN).unwrap());
-        static EMA_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(EMA_PATTERN).unwrap());
-        static SMA_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(SMA_PATTERN).unwrap());
-
-        if let Some(caps) = BB_REGEX.captures(s) {
-            let n = &caps["n"];
-            let n = n.parse().map_err(|err| ParseIndicatorError::ParseInt {
-                name: "n".to_owned(),
-                source: err,
-                value: n.to_owned(),
-            })?;
-            let k = &caps["k"];
-            let k = k.parse().map_err(|err| ParseIndicatorError::ParseInt {
-                name: "k".to_owned(),
-                source: err,
-                value: k.to_owned(),
-            })?;
-            Ok(Indicator::BollingerBands(n, k))
-        } else if let Some(caps) = EMA_REGEX.captures(s) {
-            let n = &caps["n"];
-            let n = n.parse().map_err(|err| ParseIndicatorError::ParseInt {
-                name: "n".to_owned(),
-                source: err,
-                value: n.to_owned(),
-            })?;
-            Ok(Indicator::ExponentialMovingAverage(n))
-        } else if let Some(caps) = SMA_REGEX.captures(s) {
-            let n = &caps["n"];
-            let n = n.parse().map_err(|err| ParseIndicatorError::ParseInt {
-                name: "n".to_owned(),
-                source: err,
-                value: n.to_owned(),
-            })?;
-            Ok(Indicator::SimpleMovingAverage(n))
-        } else if s == "" {
-            Err(ParseIndicatorError::Empty)
-        } else {
-            Err(ParseIndicatorError::Invalid)
+        const PATTERN: &str = r"(?P<name>[A-Z]+)\s*\(\s*(?P<params>[^)]*?)\s*\)";
+
+        static REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(PATTERN).unwrap());
+
+        if s == "" {
+            return Err(ParseIndicatorError::Empty);
         }
+        let caps = REGEX.captures(s).ok_or(ParseIndicatorError::Invalid)?;
+        let entry = INDICATOR_REGISTRY
+            .iter()
+            .find(|entry| entry.name == &caps["name"])
+            .ok_or(ParseIndicatorError::Invalid)?;
+
+        Self::new(IndicatorKind(entry.name)).with_params(&caps["params"])
     }
 }
 
@@ -341,6 +1374,10 @@ pub enum ParseIndicatorError {
     Empty,
     #[error("invalid indicator literal")]
     Invalid,
+    #[error("indicator parameter {} must be positive", .name)]
+    NotPositive { name: String },
+    #[error("expected {} indicator parameters, found {}", .expected, .found)]
+    ParamCount { expected: usize, found: usize },
     #[error("invalid indicator parameter {}: {}", .name, .value)]
     ParseInt {
         name: String,
@@ -351,20 +1388,17 @@ pub enum ParseIndicatorError {
 
 impl fmt::Display for Indicator {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::BollingerBands(n, k) => write!(f, "BB({}, {})", n, k),
-            Self::ExponentialMovingAverage(n) => write!(f, "EMA({})", n),
-            // Self::MovingAverageConvergenceDivergence => write!(f, "MACD"),
-            // Self::RelativeStrengthIndex => write!(f, "RSI"),
-            Self::SimpleMovingAverage(n) => write!(f, "SMA({})", n),
-        }
+        let params: Vec<_> = self.params().iter().map(u16::to_string).collect();
+        write!(f, "{}({})", self.kind, params.join(", "))
     }
 }
 
-#[derive(Clone, Copy, Debug, Derivative, EnumIter, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Derivative, Deserialize, EnumIter, Eq, Hash, PartialEq)]
 #[derivative(Default)]
+#[serde(try_from = "String")]
 pub enum TimeFrame {
     FiveDays,
+    OneWeek,
     #[derivative(Default)]
     OneMonth,
     ThreeMonths,
@@ -378,9 +1412,20 @@ pub enum TimeFrame {
 }
 
 impl TimeFrame {
+    /// The key selecting the time frame without opening the menu, the digits in order followed by
+    /// `-` for the last one.
+    pub fn hotkey(self) -> char {
+        const HOTKEYS: [char; 11] = ['1', '2', '3', '4', '5', '6', '7', '8', '9', '0', '-'];
+
+        HOTKEYS[TimeFrame::iter()
+            .position(|time_frame| time_frame == self)
+            .unwrap()]
+    }
+
     pub fn duration(self) -> Option<Duration> {
         match self {
             Self::FiveDays => Some(Duration::days(5)),
+            Self::OneWeek => Some(Duration::days(7)),
             Self::OneMonth => Some(Duration::days(30)),
             Self::ThreeMonths => Some(Duration::days(30 * 3)),
             Self::SixMonths => Some(Duration::days(30 * 6)),
@@ -392,19 +1437,15 @@ impl TimeFrame {
         }
     }
 
-    pub fn interval(self) -> Interval {
-        match self {
-            Self::FiveDays => Interval::_5d,
-            Self::OneMonth => Interval::_1mo,
-            Self::ThreeMonths => Interval::_3mo,
-            Self::SixMonths => Interval::_6mo,
-            Self::YearToDate => Interval::_ytd,
-            Self::OneYear => Interval::_1y,
-            Self::TwoYears => Interval::_2y,
-            Self::FiveYears => Interval::_5y,
-            Self::TenYears => Interval::_10y,
-            Self::Max => Interval::_max,
-        }
+    /// The interval between the bars fetched for the time frame, which are daily for all of them.
+    pub fn bar_duration(self) -> Duration {
+        Duration::days(1)
+    }
+
+    /// Whether the prices are adjusted for dividends by default, which is only for ranges over a year.
+    pub fn default_adjusted_close(self) -> bool {
+        self.duration()
+            .map_or(self == Self::Max, |duration| duration > Duration::days(365))
     }
 
     pub fn now_date_range(self) -> Option<DateRange> {
@@ -425,6 +1466,7 @@ impl FromStr for TimeFrame {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "5D" | "5d" => Ok(Self::FiveDays),
+            "1W" | "1w" | "1wk" => Ok(Self::OneWeek),
             "1M" | "1mo" => Ok(Self::OneMonth),
             "3M" | "3mo" => Ok(Self::ThreeMonths),
             "6M" | "6mo" => Ok(Self::SixMonths),
@@ -440,6 +1482,14 @@ impl FromStr for TimeFrame {
     }
 }
 
+impl TryFrom<String> for TimeFrame {
+    type Error = ParseTimeFrameError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ParseTimeFrameError {
     #[error("cannot parse time frame from empty string")]
@@ -452,6 +1502,7 @@ impl fmt::Display for TimeFrame {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::FiveDays => write!(f, "5D"),
+            Self::OneWeek => write!(f, "1W"),
             Self::OneMonth => write!(f, "1M"),
             Self::ThreeMonths => write!(f, "3M"),
             Self::SixMonths => write!(f, "6M"),
@@ -464,3 +1515,361 @@ impl fmt::Display for TimeFrame {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Recorder;
+    use im::hashmap;
+
+    struct DateRangeInputs {
+        chart_events: Broadcast<'static, (), ChartEvent>,
+        stock_symbols: Broadcast<'static, (), String>,
+        time_frames: Broadcast<'static, (), TimeFrame>,
+        date_ranges: Recorder<Option<DateRange>>,
+    }
+
+    fn date_range_inputs(time_frame: TimeFrame) -> DateRangeInputs {
+        let chart_events = Broadcast::new();
+        let stock_symbols = Broadcast::new();
+        let time_frames = Broadcast::new();
+        let date_ranges = Recorder::subscribe(to_date_ranges(
+            chart_events.clone(),
+            stock_symbols.clone(),
+            "AAPL".to_owned(),
+            time_frames.clone(),
+            time_frame,
+        ));
+        stock_symbols.send("AAPL".to_owned());
+        time_frames.send(time_frame);
+
+        DateRangeInputs {
+            chart_events,
+            stock_symbols,
+            time_frames,
+            date_ranges,
+        }
+    }
+
+    fn shift(date_range: &DateRange, duration: Duration) -> DateRange {
+        (date_range.start + duration)..(date_range.end + duration)
+    }
+
+    #[test]
+    fn pans_by_the_duration_of_the_time_frame() {
+        let inputs = date_range_inputs(TimeFrame::OneMonth);
+        let now_date_range = TimeFrame::OneMonth.now_date_range().unwrap();
+
+        inputs.chart_events.send(ChartEvent::PanBackward);
+        inputs.chart_events.send(ChartEvent::PanBackward);
+        inputs.chart_events.send(ChartEvent::PanForward);
+
+        assert_eq!(
+            inputs.date_ranges.take(),
+            vec![
+                Some(shift(&now_date_range, -Duration::days(30))),
+                Some(shift(&now_date_range, -Duration::days(60))),
+                Some(shift(&now_date_range, -Duration::days(30))),
+            ]
+        );
+    }
+
+    #[test]
+    fn steps_by_a_fraction_of_the_duration() {
+        let inputs = date_range_inputs(TimeFrame::OneMonth);
+        let now_date_range = TimeFrame::OneMonth.now_date_range().unwrap();
+
+        inputs.chart_events.send(ChartEvent::StepBackward);
+        inputs.chart_events.send(ChartEvent::StepBackward);
+        inputs.chart_events.send(ChartEvent::StepForward);
+        inputs.chart_events.send(ChartEvent::StepForward);
+        inputs.chart_events.send(ChartEvent::StepForward);
+
+        assert_eq!(
+            inputs.date_ranges.take(),
+            vec![
+                Some(shift(&now_date_range, -Duration::days(3))),
+                Some(shift(&now_date_range, -Duration::days(6))),
+                Some(shift(&now_date_range, -Duration::days(3))),
+                Some(now_date_range),
+            ]
+        );
+    }
+
+    #[test]
+    fn does_not_pan_forward_past_now() {
+        let inputs = date_range_inputs(TimeFrame::OneMonth);
+        let now_date_range = TimeFrame::OneMonth.now_date_range().unwrap();
+
+        inputs.chart_events.send(ChartEvent::PanBackward);
+        inputs.date_ranges.take();
+        inputs.chart_events.send(ChartEvent::PanForward);
+        inputs.chart_events.send(ChartEvent::PanForward);
+
+        assert_eq!(inputs.date_ranges.take(), vec![Some(now_date_range)]);
+    }
+
+    #[test]
+    fn resets_to_now() {
+        let inputs = date_range_inputs(TimeFrame::OneWeek);
+
+        inputs.chart_events.send(ChartEvent::PanBackward);
+        inputs.date_ranges.take();
+        inputs.chart_events.send(ChartEvent::Reset);
+
+        assert_eq!(
+            inputs.date_ranges.take(),
+            vec![TimeFrame::OneWeek.now_date_range()]
+        );
+    }
+
+    #[test]
+    fn resets_when_the_symbol_or_time_frame_changes() {
+        let inputs = date_range_inputs(TimeFrame::OneMonth);
+
+        inputs.chart_events.send(ChartEvent::PanBackward);
+        inputs.date_ranges.take();
+        inputs.stock_symbols.send("MSFT".to_owned());
+
+        assert_eq!(
+            inputs.date_ranges.take(),
+            vec![TimeFrame::OneMonth.now_date_range()]
+        );
+
+        inputs.chart_events.send(ChartEvent::PanBackward);
+        inputs.date_ranges.take();
+        inputs.time_frames.send(TimeFrame::ThreeMonths);
+
+        assert_eq!(
+            inputs.date_ranges.take(),
+            vec![TimeFrame::ThreeMonths.now_date_range()]
+        );
+    }
+
+    #[test]
+    fn does_not_pan_year_to_date() {
+        let inputs = date_range_inputs(TimeFrame::YearToDate);
+
+        inputs.chart_events.send(ChartEvent::PanBackward);
+        inputs.chart_events.send(ChartEvent::PanForward);
+
+        assert_eq!(
+            inputs.date_ranges.take(),
+            vec![TimeFrame::YearToDate.now_date_range()]
+        );
+    }
+
+    #[test]
+    fn toggles_adjusted_closes_from_the_default_of_the_time_frame() {
+        let toggles: Broadcast<'static, (), ()> = Broadcast::new();
+        let resets: Broadcast<'static, (), ()> = Broadcast::new();
+        let time_frames: Broadcast<'static, (), TimeFrame> = Broadcast::new();
+        let adjusted_closes = Recorder::subscribe(to_adjusted_closes(
+            toggles.clone(),
+            resets.clone(),
+            time_frames.clone(),
+        ));
+
+        time_frames.send(TimeFrame::OneMonth);
+        toggles.send(());
+        toggles.send(());
+        time_frames.send(TimeFrame::FiveYears);
+        toggles.send(());
+        resets.send(());
+
+        assert_eq!(
+            adjusted_closes.take(),
+            vec![None, Some(true), Some(false), None, Some(false), None]
+        );
+    }
+
+    #[test]
+    fn rejects_zero_indicator_params() {
+        let sma: Indicator = "SMA(20)".parse().unwrap();
+        let bb: Indicator = "BB(20,2)".parse().unwrap();
+
+        assert_eq!(sma.with_params("50").unwrap().to_string(), "SMA(50)");
+        assert!(sma.with_params("0").is_err());
+        assert!(sma.with_params("+0").is_err());
+        assert!(bb.with_params("+0, 2").is_err());
+        assert!(bb.with_params("20, 00").is_err());
+        assert!("SMA(0)".parse::<Indicator>().is_err());
+    }
+
+    #[test]
+    fn gives_every_time_frame_a_hotkey() {
+        assert_eq!(TimeFrame::FiveDays.hotkey(), '1');
+        assert_eq!(TimeFrame::TenYears.hotkey(), '0');
+        assert_eq!(TimeFrame::Max.hotkey(), '-');
+    }
+
+    #[test]
+    fn parses_the_indicators_in_the_registry() {
+        for indicator in Indicator::defaults() {
+            assert_eq!(
+                indicator.to_string().parse::<Indicator>().unwrap(),
+                indicator
+            );
+        }
+        assert_eq!(
+            "sma".parse::<IndicatorKind>().unwrap(),
+            "simple_moving_average".parse::<IndicatorKind>().unwrap()
+        );
+        assert!(matches!(
+            "BB(20)".parse::<Indicator>(),
+            Err(ParseIndicatorError::ParamCount {
+                expected: 2,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            "RSI(14)".parse::<Indicator>(),
+            Err(ParseIndicatorError::Invalid)
+        ));
+    }
+
+    #[test]
+    fn goes_to_the_date_centered_but_not_past_now() {
+        let inputs = date_range_inputs(TimeFrame::OneMonth);
+        let now_date_range = TimeFrame::OneMonth.now_date_range().unwrap();
+        let date = now_date_range.start - Duration::days(100);
+
+        inputs.chart_events.send(ChartEvent::GoTo(date));
+        inputs
+            .chart_events
+            .send(ChartEvent::GoTo(now_date_range.end));
+
+        assert_eq!(
+            inputs.date_ranges.take(),
+            vec![
+                Some((date - Duration::days(15))..(date + Duration::days(15))),
+                Some(now_date_range),
+            ]
+        );
+    }
+
+    #[test]
+    fn smooths_the_frame_time_over_the_window() {
+        let mut frame_rate_counter =
+            FrameRateCounter::new(Duration::milliseconds(1_000)).smoothed(3);
+
+        assert_eq!(frame_rate_counter.smoothed_frame_time(), None);
+        for &frame_time in &[10, 20, 60, 40] {
+            frame_rate_counter.record(frame_time);
+        }
+
+        assert_eq!(
+            frame_rate_counter.smoothed_frame_time(),
+            Some(Duration::milliseconds(40))
+        );
+    }
+
+    #[test]
+    fn moves_the_favorite_along_the_bar() {
+        let favorites: Vec<_> = vec!["AAPL", "MSFT", "TSLA"]
+            .into_iter()
+            .map(str::to_owned)
+            .collect();
+
+        assert_eq!(
+            move_favorite(&favorites, "TSLA", -1),
+            vec!["AAPL", "TSLA", "MSFT"]
+        );
+        assert_eq!(move_favorite(&favorites, "AAPL", -1), favorites);
+        assert_eq!(
+            move_favorite(&favorites, "AAPL", 5),
+            vec!["MSFT", "TSLA", "AAPL"]
+        );
+        assert_eq!(move_favorite(&favorites, "GOOG", 1), favorites);
+    }
+
+    #[test]
+    fn fits_the_time_frame_to_a_short_history() {
+        let now = Utc::now();
+
+        assert_eq!(
+            fitting_time_frame(TimeFrame::TenYears, now - Duration::days(20), now),
+            Some(TimeFrame::OneMonth)
+        );
+        assert_eq!(
+            fitting_time_frame(TimeFrame::FiveYears, now - Duration::days(400), now),
+            Some(TimeFrame::TwoYears)
+        );
+        // a long enough history, a small shortfall and no fixed duration are left alone
+        assert_eq!(
+            fitting_time_frame(TimeFrame::OneYear, now - Duration::days(200), now),
+            None
+        );
+        assert_eq!(
+            fitting_time_frame(TimeFrame::OneMonth, now - Duration::days(3), now),
+            None
+        );
+        assert_eq!(
+            fitting_time_frame(TimeFrame::Max, now - Duration::days(20), now),
+            None
+        );
+    }
+
+    #[test]
+    fn numbers_the_chart_series_from_one() {
+        assert_eq!(ChartSeries::from_hotkey('1'), Some(ChartSeries::Price));
+        assert_eq!(ChartSeries::from_hotkey('4'), Some(ChartSeries::EmaRibbon));
+        assert_eq!(ChartSeries::from_hotkey('0'), None);
+        assert_eq!(ChartSeries::from_hotkey('5'), None);
+        assert_eq!(ChartSeries::from_hotkey('a'), None);
+        for series in ChartSeries::iter() {
+            let hotkey = std::char::from_digit(series.number() as u32, 10).unwrap();
+            assert_eq!(ChartSeries::from_hotkey(hotkey), Some(series));
+        }
+    }
+
+    #[test]
+    fn cycles_through_the_indicators_and_none() {
+        let indicators: Vec<_> = Indicator::defaults().collect();
+        let next = |indicator| cycle_indicator(indicator, &indicators, 1);
+        let previous = |indicator| cycle_indicator(indicator, &indicators, -1);
+
+        assert_eq!(next(None), Some(indicators[0]));
+        assert_eq!(next(sma(20)), None);
+        assert_eq!(previous(None), indicators.last().copied());
+        // matched by kind, so custom parameters don't lose the place
+        assert_eq!(previous(sma(20)), Some(indicators[indicators.len() - 2]));
+    }
+
+    fn sma(period: u16) -> Option<Indicator> {
+        Some(format!("SMA({})", period).parse().unwrap())
+    }
+
+    #[test]
+    fn scales_the_indicator_period_with_the_time_frame() {
+        let indicators: Broadcast<'static, (), Option<Indicator>> = Broadcast::new();
+        let time_frames: Broadcast<'static, (), TimeFrame> = Broadcast::new();
+        let period_scales = hashmap! {
+            TimeFrame::OneMonth => 1f64,
+            TimeFrame::OneYear => 4f64,
+        };
+        let scaled_indicators = Recorder::subscribe(to_scaled_indicators(
+            indicators.clone(),
+            time_frames.clone(),
+            sma(20),
+            TimeFrame::OneMonth,
+            period_scales,
+        ));
+        time_frames.send(TimeFrame::OneMonth);
+        indicators.send(sma(20));
+
+        time_frames.send(TimeFrame::OneYear);
+        // unscaled time frames keep the period as chosen
+        time_frames.send(TimeFrame::FiveDays);
+        indicators.send(sma(10));
+        time_frames.send(TimeFrame::OneYear);
+        time_frames.send(TimeFrame::OneMonth);
+        indicators.send(sma(30));
+        time_frames.send(TimeFrame::OneYear);
+
+        assert_eq!(
+            scaled_indicators.take(),
+            vec![sma(20), sma(80), sma(20), sma(10), sma(30), sma(120)]
+        );
+    }
+}
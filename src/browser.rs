@@ -0,0 +1,31 @@
+/// Opens the URL in the default web browser.
+///
+/// The opener runs on a thread of its own, as it may not return until the browser does. Its
+/// failure is only logged.
+#[cfg(feature = "open")]
+pub fn open(url: &str) -> anyhow::Result<()> {
+    use log::warn;
+    use std::thread;
+
+    let url = url.to_owned();
+    thread::Builder::new()
+        .name("browser".to_owned())
+        .spawn(move || match ::open::that(&url) {
+            Ok(exit_status) if exit_status.success() => {}
+            Ok(exit_status) => warn!(
+                "cannot open {}: the opener exited with {}",
+                url, exit_status
+            ),
+            Err(err) => warn!("cannot open {}: {}", url, err),
+        })?;
+
+    Ok(())
+}
+
+/// Opens the URL in the default web browser.
+#[cfg(not(feature = "open"))]
+pub fn open(_url: &str) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!(
+        "stocker was built without the open feature"
+    ))
+}
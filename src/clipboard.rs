@@ -0,0 +1,19 @@
+/// Copies the text to the system clipboard.
+#[cfg(feature = "clipboard")]
+pub fn copy(text: &str) -> anyhow::Result<()> {
+    use ::clipboard::{ClipboardContext, ClipboardProvider};
+
+    let mut clipboard_context: ClipboardContext =
+        ClipboardProvider::new().map_err(|err| anyhow::anyhow!("{}", err))?;
+    clipboard_context
+        .set_contents(text.to_owned())
+        .map_err(|err| anyhow::anyhow!("{}", err))
+}
+
+/// Copies the text to the system clipboard.
+#[cfg(not(feature = "clipboard"))]
+pub fn copy(_text: &str) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!(
+        "stocker was built without the clipboard feature"
+    ))
+}
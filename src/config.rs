@@ -0,0 +1,311 @@
+use crate::{
+    app::{self, Indicator, IndicatorKind, PaneSplit, TimeFrame},
+    format,
+    theme::ThemeColor,
+};
+use anyhow::Context;
+use serde::Deserialize;
+use std::{collections::HashMap, fs, path::Path};
+use strum::IntoEnumIterator;
+
+/// Settings read from a TOML config file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub colors: ColorsConfig,
+    /// Symbols in the favorites bar, e.g. `favorites = ["AAPL", "BTC-USD"]`.
+    pub favorites: Vec<String>,
+    pub indicators: IndicatorsConfig,
+    pub layout: LayoutConfig,
+    pub price_precision: PricePrecisionConfig,
+    /// Whether the order of the favorites, as rearranged in the favorites bar, is written back to
+    /// the config file on quitting.
+    pub save_favorites_order: bool,
+    pub symbols: SymbolsConfig,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let config = fs::read_to_string(path)
+            .with_context(|| format!("cannot read config file {}", path.display()))?;
+        let config: Self = toml::from_str(&config)
+            .with_context(|| format!("cannot parse config file {}", path.display()))?;
+
+        let periods = [
+            config.indicators.default_bb_period,
+            config.indicators.default_ema_period,
+            config.indicators.default_sma_period,
+        ];
+        anyhow::ensure!(
+            !periods.contains(&Some(0)),
+            "invalid config file {}: indicator periods must be greater than 0",
+            path.display()
+        );
+        anyhow::ensure!(
+            config
+                .indicators
+                .period_scales
+                .values()
+                .all(|scale| scale.is_finite() && *scale > 0f64),
+            "invalid config file {}: indicator period scales must be greater than 0",
+            path.display()
+        );
+        anyhow::ensure!(
+            config
+                .indicators
+                .precision
+                .map_or(true, |precision| precision <= format::MAX_PRICE_PRECISION),
+            "invalid config file {}: indicator precision must be at most {}",
+            path.display(),
+            format::MAX_PRICE_PRECISION
+        );
+
+        anyhow::ensure!(
+            config.favorites.len() <= app::MAX_FAVORITES,
+            "invalid config file {}: there can be at most {} favorites",
+            path.display(),
+            app::MAX_FAVORITES
+        );
+
+        anyhow::ensure!(
+            config
+                .price_precision
+                .0
+                .values()
+                .all(|&precision| precision <= format::MAX_PRICE_PRECISION),
+            "invalid config file {}: price precision must be at most {}",
+            path.display(),
+            format::MAX_PRICE_PRECISION
+        );
+
+        if let Some(price_pane_percent) = config.layout.price_pane_percent {
+            anyhow::ensure!(
+                (PaneSplit::MIN_PERCENT..=PaneSplit::MAX_PERCENT).contains(&price_pane_percent),
+                "invalid config file {}: price pane percent must be between {} and {}",
+                path.display(),
+                PaneSplit::MIN_PERCENT,
+                PaneSplit::MAX_PERCENT
+            );
+        }
+
+        Ok(config)
+    }
+
+    /// Writes the favorites to the config file, leaving the rest of it as written.
+    pub fn save_favorites(path: &Path, favorites: &[String]) -> anyhow::Result<()> {
+        let config = fs::read_to_string(path)
+            .with_context(|| format!("cannot read config file {}", path.display()))?;
+        fs::write(path, with_favorites(&config, favorites))
+            .with_context(|| format!("cannot write config file {}", path.display()))
+    }
+}
+
+/// Returns the config with the top-level favorites replaced by the symbols, or added at the top if
+/// there are none, keeping the other lines.
+fn with_favorites(config: &str, favorites: &[String]) -> String {
+    let favorites_line = format!(
+        "favorites = [{}]",
+        favorites
+            .iter()
+            .map(|favorite| toml::Value::from(favorite.as_str()).to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let lines: Vec<_> = config.lines().collect();
+    // top-level keys come before the first table
+    let top_level_end = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .unwrap_or_else(|| lines.len());
+    let favorites_start = lines[..top_level_end].iter().position(|line| {
+        let line = line.trim_start();
+        line.starts_with("favorites") && line["favorites".len()..].trim_start().starts_with('=')
+    });
+
+    let mut config_lines = vec![];
+    match favorites_start {
+        Some(favorites_start) => {
+            // the array may span lines, up to its closing bracket
+            let mut depth = 0;
+            let mut favorites_end = favorites_start;
+            for (i, line) in lines.iter().enumerate().skip(favorites_start) {
+                let code = line.split('#').next().unwrap_or("");
+                depth += code.matches('[').count() as isize - code.matches(']').count() as isize;
+                favorites_end = i;
+                if depth <= 0 {
+                    break;
+                }
+            }
+            config_lines.extend(lines[..favorites_start].iter().map(|&line| line.to_owned()));
+            config_lines.push(favorites_line);
+            config_lines.extend(
+                lines[favorites_end + 1..]
+                    .iter()
+                    .map(|&line| line.to_owned()),
+            );
+        }
+        None => {
+            config_lines.push(favorites_line);
+            config_lines.extend(lines.iter().map(|&line| line.to_owned()));
+        }
+    }
+
+    let mut config_text = config_lines.join("\n");
+    if config.ends_with('\n') || favorites_start.is_none() {
+        config_text.push('\n');
+    }
+    config_text
+}
+
+/// The `[colors]` section, e.g.
+///
+/// ```toml
+/// [colors]
+/// down = "#e69f00"
+/// gridlines = "#303030"
+/// latest_bar = "yellow"
+/// ma_shading_below = "#3a0000"
+/// up = "#56b4e9"
+///
+/// [colors.indicators]
+/// sma = "blue"
+/// ema = "#ff8800"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorsConfig {
+    pub down: Option<ThemeColor>,
+    pub gridlines: Option<ThemeColor>,
+    pub indicators: HashMap<IndicatorKind, ThemeColor>,
+    pub latest_bar: Option<ThemeColor>,
+    pub ma_shading_above: Option<ThemeColor>,
+    pub ma_shading_below: Option<ThemeColor>,
+    pub up: Option<ThemeColor>,
+}
+
+/// The `[indicators]` section, e.g.
+///
+/// ```toml
+/// [indicators]
+/// default_sma_period = 200
+/// precision = 4
+///
+/// [indicators.period_scales]
+/// 1M = 1
+/// 1Y = 4
+/// 10Y = 20
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndicatorsConfig {
+    pub default_bb_period: Option<u16>,
+    pub default_ema_period: Option<u16>,
+    pub default_sma_period: Option<u16>,
+    /// Scales of the indicator period by time frame, where switching time frames multiplies the
+    /// period by the ratio of their scales, e.g. from SMA(20) at 1M to SMA(80) at 1Y.
+    pub period_scales: HashMap<TimeFrame, f64>,
+    /// Decimal places of the indicator values in the tooltip, based on the price if unset.
+    pub precision: Option<usize>,
+}
+
+impl IndicatorsConfig {
+    /// Returns the indicators offered in the indicator menu, with the default periods overridden.
+    pub fn default_indicators(&self) -> Vec<Indicator> {
+        let default_periods = [
+            ("BB", self.default_bb_period),
+            ("EMA", self.default_ema_period),
+            ("SMA", self.default_sma_period),
+        ];
+        Indicator::defaults()
+            .map(|indicator| {
+                default_periods
+                    .iter()
+                    .find(|(name, _)| *name == indicator.kind().entry().name)
+                    .and_then(|&(_, period)| period)
+                    .map_or(indicator, |period| indicator.with_period(period))
+            })
+            .collect()
+    }
+}
+
+/// The `[layout]` section, e.g.
+///
+/// ```toml
+/// [layout]
+/// price_pane_percent = 50
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutConfig {
+    /// Percentage of the chart height given to the price chart when a sub-pane is shown.
+    pub price_pane_percent: Option<u16>,
+}
+
+impl LayoutConfig {
+    pub fn pane_split(&self) -> PaneSplit {
+        self.price_pane_percent
+            .map_or_else(PaneSplit::default, PaneSplit::new)
+    }
+}
+
+/// The `[symbols]` section, mapping the symbols typed to the symbols of the data source, e.g.
+///
+/// ```toml
+/// [symbols]
+/// BMW = "BMW.DE"
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct SymbolsConfig(HashMap<String, String>);
+
+impl SymbolsConfig {
+    /// Returns the symbol mapped from the symbol, ignoring case, or the symbol itself if unmapped.
+    pub fn resolve(&self, symbol: &str) -> String {
+        self.0
+            .iter()
+            .find(|(bare_symbol, _)| bare_symbol.eq_ignore_ascii_case(symbol))
+            .map_or_else(|| symbol.to_owned(), |(_, resolved)| resolved.clone())
+    }
+}
+
+/// The `[price_precision]` section, mapping symbols to the decimal places of their prices instead
+/// of basing them on the magnitude, e.g.
+///
+/// ```toml
+/// [price_precision]
+/// "EURUSD=X" = 4
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct PricePrecisionConfig(HashMap<String, usize>);
+
+impl PricePrecisionConfig {
+    /// Returns the decimal places set for the symbol, ignoring case.
+    pub fn get(&self, symbol: &str) -> Option<usize> {
+        self.0
+            .iter()
+            .find(|(precision_symbol, _)| precision_symbol.eq_ignore_ascii_case(symbol))
+            .map(|(_, &precision)| precision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_the_favorites_keeping_the_other_lines() {
+        let favorites = vec!["MSFT".to_owned(), "AAPL".to_owned()];
+        let config = "# my favorites\nfavorites = [\n  \"AAPL\",\n  \"MSFT\", # [work]\n]\n\n[symbols]\nBMW = \"BMW.DE\"\n";
+
+        assert_eq!(
+            with_favorites(config, &favorites),
+            "# my favorites\nfavorites = [\"MSFT\", \"AAPL\"]\n\n[symbols]\nBMW = \"BMW.DE\"\n"
+        );
+        assert_eq!(
+            with_favorites("[symbols]\nBMW = \"BMW.DE\"", &favorites),
+            "favorites = [\"MSFT\", \"AAPL\"]\n[symbols]\nBMW = \"BMW.DE\"\n"
+        );
+    }
+}
@@ -0,0 +1,118 @@
+pub use self::csv::*;
+pub use alpha_vantage::*;
+pub use profiling::*;
+pub use rate_limiting::*;
+pub use yahoo::*;
+
+use crate::{
+    app::DateRange,
+    stock::{CorporateEvent, NewsHeadline, StockProfile, StockQuote},
+};
+use chrono::{DateTime, Utc};
+use futures::{executor, Future};
+use std::{str::FromStr, time::Duration};
+use thiserror::Error;
+use yahoo_finance::Bar;
+
+mod alpha_vantage;
+mod csv;
+mod profiling;
+mod rate_limiting;
+mod yahoo;
+
+pub trait DataSource {
+    fn fetch_profile(&self, symbol: &str) -> anyhow::Result<StockProfile>;
+
+    /// Fetches the bars within the date range, or all available bars if there is no date range.
+    fn fetch_bars(&self, symbol: &str, date_range: Option<&DateRange>) -> anyhow::Result<Vec<Bar>>;
+
+    /// Fetches the known dates of earnings reports, past and upcoming, oldest first. Data sources
+    /// without earnings dates return none.
+    fn fetch_earnings_dates(&self, _symbol: &str) -> anyhow::Result<Vec<DateTime<Utc>>> {
+        Ok(vec![])
+    }
+
+    /// Fetches all dividends and splits, oldest first. Data sources without events return none.
+    fn fetch_events(&self, _symbol: &str) -> anyhow::Result<Vec<CorporateEvent>> {
+        Ok(vec![])
+    }
+
+    /// Fetches the latest news headlines, newest first. Data sources without news return none.
+    fn fetch_news(&self, _symbol: &str) -> anyhow::Result<Vec<NewsHeadline>> {
+        Ok(vec![])
+    }
+
+    /// Fetches the latest quote. Data sources without quotes return none.
+    fn fetch_quote(&self, _symbol: &str) -> anyhow::Result<Option<StockQuote>> {
+        Ok(None)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DataSourceKind {
+    AlphaVantage,
+    Csv,
+    Yahoo,
+}
+
+impl Default for DataSourceKind {
+    fn default() -> Self {
+        Self::Yahoo
+    }
+}
+
+impl FromStr for DataSourceKind {
+    type Err = ParseDataSourceKindError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "alphavantage" => Ok(Self::AlphaVantage),
+            "csv" => Ok(Self::Csv),
+            "yahoo" => Ok(Self::Yahoo),
+            "" => Err(ParseDataSourceKindError::Empty),
+            _ => Err(ParseDataSourceKindError::Invalid),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseDataSourceKindError {
+    #[error("cannot parse data source from empty string")]
+    Empty,
+    #[error("invalid data source literal")]
+    Invalid,
+}
+
+#[derive(Debug, Error)]
+#[error("timed out after {0:?}")]
+pub struct FetchTimeoutError(pub Duration);
+
+/// The data source refused the request for too many requests, as with a 429 response.
+#[derive(Debug, Error)]
+#[error("rate limited, slow down")]
+pub struct RateLimitError;
+
+/// Turns errors reporting a 429 response only in their message, as those of `yahoo_finance`, into a
+/// `RateLimitError`.
+fn detect_rate_limit(err: anyhow::Error) -> anyhow::Error {
+    let is_rate_limit = err.chain().any(|cause| {
+        let message = cause.to_string();
+        message.contains("429") || message.contains("Too Many Requests")
+    });
+    if is_rate_limit {
+        RateLimitError.into()
+    } else {
+        err
+    }
+}
+
+/// Blocks on a request to the data source, failing if it has not completed within the timeout.
+fn block_on_with_timeout<F, T, E>(timeout: Duration, request: F) -> anyhow::Result<T>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Into<anyhow::Error>,
+{
+    executor::block_on(async_std::future::timeout(timeout, request))
+        .map_err(|_| FetchTimeoutError(timeout))?
+        .map_err(Into::into)
+}
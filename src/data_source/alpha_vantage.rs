@@ -0,0 +1,129 @@
+use crate::{
+    app::DateRange,
+    data_source::{block_on_with_timeout, DataSource, RateLimitError},
+    market::Exchange,
+    stock::StockProfile,
+};
+use anyhow::{bail, Context};
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use reqwest::Url;
+use serde_json::Value;
+use std::time;
+use yahoo_finance::Bar;
+
+const BASE_URL: &str = "https://www.alphavantage.co/query";
+
+/// The number of calendar days roughly covered by the compact output size (100 trading days).
+const COMPACT_OUTPUT_DAYS: i64 = 140;
+
+#[derive(Debug)]
+pub struct AlphaVantageDataSource {
+    api_key: String,
+    /// Time after which a request is abandoned.
+    timeout: time::Duration,
+}
+
+impl AlphaVantageDataSource {
+    pub fn new(api_key: String, timeout: time::Duration) -> Self {
+        Self { api_key, timeout }
+    }
+
+    fn query(&self, params: &[(&str, &str)]) -> anyhow::Result<Value> {
+        let url = Url::parse_with_params(
+            BASE_URL,
+            params
+                .iter()
+                .copied()
+                .chain(vec![("apikey", self.api_key.as_str())]),
+        )?;
+
+        let body = block_on_with_timeout(self.timeout, async {
+            reqwest::get(url).await?.text().await
+        })?;
+        let value: Value = serde_json::from_str(&body)?;
+
+        if let Some(message) = value.get("Error Message").and_then(Value::as_str) {
+            bail!("Alpha Vantage error: {}", message);
+        }
+        // the note is only sent on exceeding the call frequency
+        if let Some(note) = value.get("Note").and_then(Value::as_str) {
+            return Err(
+                anyhow::Error::new(RateLimitError).context(format!("Alpha Vantage note: {}", note))
+            );
+        }
+
+        Ok(value)
+    }
+}
+
+impl DataSource for AlphaVantageDataSource {
+    fn fetch_profile(&self, symbol: &str) -> anyhow::Result<StockProfile> {
+        let overview = self.query(&[("function", "OVERVIEW"), ("symbol", symbol)])?;
+
+        // Funds have no company overview, so fall back to the symbol.
+        let name = overview
+            .get("Name")
+            .and_then(Value::as_str)
+            .unwrap_or(symbol)
+            .to_owned();
+        let exchange = overview
+            .get("Exchange")
+            .and_then(Value::as_str)
+            .and_then(Exchange::from_code);
+
+        Ok(StockProfile { exchange, name })
+    }
+
+    fn fetch_bars(&self, symbol: &str, date_range: Option<&DateRange>) -> anyhow::Result<Vec<Bar>> {
+        let output_size = match date_range {
+            Some(date_range)
+                if date_range.start >= Utc::now() - Duration::days(COMPACT_OUTPUT_DAYS) =>
+            {
+                "compact"
+            }
+            _ => "full",
+        };
+
+        let time_series = self.query(&[
+            ("function", "TIME_SERIES_DAILY"),
+            ("symbol", symbol),
+            ("outputsize", output_size),
+        ])?;
+        let time_series = time_series
+            .get("Time Series (Daily)")
+            .and_then(Value::as_object)
+            .with_context(|| "missing daily time series")?;
+
+        let parse_field = |values: &Value, key: &str| -> anyhow::Result<f64> {
+            Ok(values
+                .get(key)
+                .and_then(Value::as_str)
+                .with_context(|| format!("missing field: {}", key))?
+                .parse()?)
+        };
+
+        let mut bars = vec![];
+        for (date, values) in time_series {
+            let datetime = Utc
+                .from_utc_date(&NaiveDate::parse_from_str(date, "%Y-%m-%d")?)
+                .and_hms(0, 0, 0);
+            if let Some(date_range) = date_range {
+                if !date_range.contains(&datetime) {
+                    continue;
+                }
+            }
+
+            bars.push(Bar {
+                timestamp: datetime.timestamp_millis(),
+                open: parse_field(values, "1. open")?,
+                high: parse_field(values, "2. high")?,
+                low: parse_field(values, "3. low")?,
+                close: parse_field(values, "4. close")?,
+                volume: Some(parse_field(values, "5. volume")? as u64),
+            });
+        }
+        bars.sort_by_key(|bar| bar.timestamp);
+
+        Ok(bars)
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{app::DateRange, data_source::DataSource, stock::StockProfile};
+use anyhow::Context;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use log::warn;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use yahoo_finance::{Bar, Timestamped};
+
+pub const DEFAULT_CSV_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Loads bars from local OHLCV CSV files.
+///
+/// The path is either a single CSV file, which is used regardless of the symbol, or a directory
+/// containing one `<SYMBOL>.csv` file per symbol. The columns are mapped by their header names
+/// (`Date`, `Open`, `High`, `Low`, `Close`, and optionally `Volume`), case-insensitively, so files
+/// exported from Yahoo Finance can be used as is.
+#[derive(Debug)]
+pub struct CsvDataSource {
+    date_format: String,
+    path: PathBuf,
+}
+
+impl CsvDataSource {
+    pub fn new(path: PathBuf, date_format: String) -> Self {
+        Self { date_format, path }
+    }
+
+    fn file_path(&self, symbol: &str) -> PathBuf {
+        if self.path.is_dir() {
+            self.path.join(format!("{}.csv", symbol))
+        } else {
+            self.path.clone()
+        }
+    }
+
+    fn parse_date(&self, s: &str) -> Option<DateTime<Utc>> {
+        NaiveDateTime::parse_from_str(s, &self.date_format)
+            .or_else(|_| {
+                NaiveDate::parse_from_str(s, &self.date_format).map(|date| date.and_hms(0, 0, 0))
+            })
+            .ok()
+            .map(|datetime| DateTime::from_utc(datetime, Utc))
+    }
+
+    fn read_bars(&self, file_path: &Path) -> anyhow::Result<Vec<Bar>> {
+        let contents = fs::read_to_string(file_path)
+            .with_context(|| format!("failed to read CSV file: {}", file_path.display()))?;
+        let mut lines = contents.lines().enumerate();
+
+        let (_, header) = lines.next().context("missing CSV header")?;
+        let columns: Vec<String> = header
+            .split(',')
+            .map(|column| column.trim().to_lowercase())
+            .collect();
+        let column_index = |names: &[&str]| {
+            columns
+                .iter()
+                .position(|column| names.contains(&column.as_str()))
+        };
+        let date_index = column_index(&["date", "datetime", "timestamp"])
+            .context("missing date column in CSV header")?;
+        let open_index = column_index(&["open"]).context("missing open column in CSV header")?;
+        let high_index = column_index(&["high"]).context("missing high column in CSV header")?;
+        let low_index = column_index(&["low"]).context("missing low column in CSV header")?;
+        let close_index = column_index(&["close"]).context("missing close column in CSV header")?;
+        let volume_index = column_index(&["volume"]);
+
+        let parse_bar = |fields: &[&str]| {
+            let field = |index: usize| fields.get(index).copied().unwrap_or("");
+            let price = |index: usize| field(index).parse::<f64>().ok();
+
+            Some(Bar {
+                timestamp: self.parse_date(field(date_index))?.timestamp_millis(),
+                open: price(open_index)?,
+                high: price(high_index)?,
+                low: price(low_index)?,
+                close: price(close_index)?,
+                volume: volume_index
+                    .and_then(|index| field(index).parse::<f64>().ok())
+                    .map(|volume| volume as u64),
+            })
+        };
+
+        let mut bars = vec![];
+        for (line_index, line) in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+
+            match parse_bar(&fields) {
+                Some(bar) => bars.push(bar),
+                None => warn!(
+                    "skipping invalid CSV row {} in {}: {}",
+                    line_index + 1,
+                    file_path.display(),
+                    line
+                ),
+            }
+        }
+        bars.sort_by_key(|bar| bar.timestamp);
+
+        Ok(bars)
+    }
+}
+
+impl DataSource for CsvDataSource {
+    fn fetch_profile(&self, symbol: &str) -> anyhow::Result<StockProfile> {
+        let name = self
+            .file_path(symbol)
+            .file_stem()
+            .and_then(|file_stem| file_stem.to_str())
+            .unwrap_or(symbol)
+            .to_owned();
+
+        Ok(StockProfile {
+            exchange: None,
+            name,
+        })
+    }
+
+    fn fetch_bars(&self, symbol: &str, date_range: Option<&DateRange>) -> anyhow::Result<Vec<Bar>> {
+        let bars = self.read_bars(&self.file_path(symbol))?;
+
+        Ok(bars
+            .into_iter()
+            .filter(|bar| {
+                date_range.map_or(true, |date_range| date_range.contains(&bar.datetime()))
+            })
+            .collect())
+    }
+}
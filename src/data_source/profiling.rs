@@ -0,0 +1,74 @@
+use crate::{
+    app::DateRange,
+    data_source::DataSource,
+    stock::{CorporateEvent, NewsHeadline, StockProfile, StockQuote},
+};
+use chrono::{DateTime, Utc};
+use log::info;
+use std::{rc::Rc, time::Instant};
+use yahoo_finance::Bar;
+
+/// Logs the time taken by every fetch of the wrapped data source, for `--profile`.
+pub struct ProfilingDataSource {
+    data_source: Rc<dyn DataSource>,
+}
+
+impl ProfilingDataSource {
+    pub fn new(data_source: Rc<dyn DataSource>) -> Self {
+        Self { data_source }
+    }
+}
+
+impl DataSource for ProfilingDataSource {
+    fn fetch_profile(&self, symbol: &str) -> anyhow::Result<StockProfile> {
+        profile(format!("profile of {}", symbol), || {
+            self.data_source.fetch_profile(symbol)
+        })
+    }
+
+    fn fetch_bars(&self, symbol: &str, date_range: Option<&DateRange>) -> anyhow::Result<Vec<Bar>> {
+        profile(format!("bars of {}", symbol), || {
+            self.data_source.fetch_bars(symbol, date_range)
+        })
+    }
+
+    fn fetch_earnings_dates(&self, symbol: &str) -> anyhow::Result<Vec<DateTime<Utc>>> {
+        profile(format!("earnings dates of {}", symbol), || {
+            self.data_source.fetch_earnings_dates(symbol)
+        })
+    }
+
+    fn fetch_events(&self, symbol: &str) -> anyhow::Result<Vec<CorporateEvent>> {
+        profile(format!("events of {}", symbol), || {
+            self.data_source.fetch_events(symbol)
+        })
+    }
+
+    fn fetch_news(&self, symbol: &str) -> anyhow::Result<Vec<NewsHeadline>> {
+        profile(format!("news of {}", symbol), || {
+            self.data_source.fetch_news(symbol)
+        })
+    }
+
+    fn fetch_quote(&self, symbol: &str) -> anyhow::Result<Option<StockQuote>> {
+        profile(format!("quote of {}", symbol), || {
+            self.data_source.fetch_quote(symbol)
+        })
+    }
+}
+
+fn profile<T, F>(what: String, fetch: F) -> anyhow::Result<T>
+where
+    F: FnOnce() -> anyhow::Result<T>,
+{
+    let start = Instant::now();
+    let result = fetch();
+    info!(
+        "fetching {} took {} ms{}",
+        what,
+        start.elapsed().as_millis(),
+        if result.is_err() { " and failed" } else { "" }
+    );
+
+    result
+}
@@ -0,0 +1,144 @@
+use crate::{
+    app::DateRange,
+    data_source::{DataSource, RateLimitError},
+    stock::{CorporateEvent, NewsHeadline, StockProfile, StockQuote},
+};
+use chrono::{DateTime, Utc};
+use log::warn;
+use std::{
+    cell::Cell,
+    cmp,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+use yahoo_finance::Bar;
+
+/// The most the backoff is doubled to, on rate limits in a row.
+const MAX_BACKOFF_DOUBLINGS: u32 = 4;
+
+/// Stops fetching from the wrapped data source for a while once it rate-limits, failing right away
+/// instead of adding to the requests it is limiting. The wait doubles on every rate limit in a row.
+pub struct RateLimitingDataSource {
+    backoff: Duration,
+    data_source: Rc<dyn DataSource>,
+    rate_limits_in_a_row: Cell<u32>,
+    resume_at: Cell<Option<Instant>>,
+}
+
+impl RateLimitingDataSource {
+    pub fn new(data_source: Rc<dyn DataSource>, backoff: Duration) -> Self {
+        Self {
+            backoff,
+            data_source,
+            rate_limits_in_a_row: Cell::new(0),
+            resume_at: Cell::new(None),
+        }
+    }
+
+    fn fetch<T, F>(&self, fetch: F) -> anyhow::Result<T>
+    where
+        F: FnOnce() -> anyhow::Result<T>,
+    {
+        if let Some(resume_at) = self.resume_at.get() {
+            if Instant::now() < resume_at {
+                return Err(RateLimitError.into());
+            }
+        }
+
+        let result = fetch();
+        match &result {
+            Err(err) if err.is::<RateLimitError>() => {
+                let doublings = cmp::min(self.rate_limits_in_a_row.get(), MAX_BACKOFF_DOUBLINGS);
+                let backoff = self.backoff * 2u32.pow(doublings);
+                warn!("rate limited, backing off for {:?}", backoff);
+                self.rate_limits_in_a_row
+                    .set(self.rate_limits_in_a_row.get() + 1);
+                self.resume_at.set(Some(Instant::now() + backoff));
+            }
+            Err(_) => {}
+            Ok(_) => {
+                self.rate_limits_in_a_row.set(0);
+                self.resume_at.set(None);
+            }
+        }
+
+        result
+    }
+}
+
+impl DataSource for RateLimitingDataSource {
+    fn fetch_profile(&self, symbol: &str) -> anyhow::Result<StockProfile> {
+        self.fetch(|| self.data_source.fetch_profile(symbol))
+    }
+
+    fn fetch_bars(&self, symbol: &str, date_range: Option<&DateRange>) -> anyhow::Result<Vec<Bar>> {
+        self.fetch(|| self.data_source.fetch_bars(symbol, date_range))
+    }
+
+    fn fetch_earnings_dates(&self, symbol: &str) -> anyhow::Result<Vec<DateTime<Utc>>> {
+        self.fetch(|| self.data_source.fetch_earnings_dates(symbol))
+    }
+
+    fn fetch_events(&self, symbol: &str) -> anyhow::Result<Vec<CorporateEvent>> {
+        self.fetch(|| self.data_source.fetch_events(symbol))
+    }
+
+    fn fetch_news(&self, symbol: &str) -> anyhow::Result<Vec<NewsHeadline>> {
+        self.fetch(|| self.data_source.fetch_news(symbol))
+    }
+
+    fn fetch_quote(&self, symbol: &str) -> anyhow::Result<Option<StockQuote>> {
+        self.fetch(|| self.data_source.fetch_quote(symbol))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rate-limits every request, counting them.
+    #[derive(Default)]
+    struct RateLimitedDataSource {
+        requests: Cell<usize>,
+    }
+
+    impl DataSource for RateLimitedDataSource {
+        fn fetch_profile(&self, _symbol: &str) -> anyhow::Result<StockProfile> {
+            self.requests.set(self.requests.get() + 1);
+            Err(RateLimitError.into())
+        }
+
+        fn fetch_bars(&self, _symbol: &str, _: Option<&DateRange>) -> anyhow::Result<Vec<Bar>> {
+            self.requests.set(self.requests.get() + 1);
+            Err(RateLimitError.into())
+        }
+    }
+
+    #[test]
+    fn stops_fetching_while_backing_off() {
+        let rate_limited_data_source = Rc::new(RateLimitedDataSource::default());
+        let data_source =
+            RateLimitingDataSource::new(rate_limited_data_source.clone(), Duration::from_secs(60));
+
+        assert!(data_source
+            .fetch_profile("AAPL")
+            .unwrap_err()
+            .is::<RateLimitError>());
+        assert!(data_source
+            .fetch_bars("AAPL", None)
+            .unwrap_err()
+            .is::<RateLimitError>());
+        assert_eq!(rate_limited_data_source.requests.get(), 1);
+    }
+
+    #[test]
+    fn fetches_again_once_the_backoff_is_over() {
+        let rate_limited_data_source = Rc::new(RateLimitedDataSource::default());
+        let data_source =
+            RateLimitingDataSource::new(rate_limited_data_source.clone(), Duration::from_secs(0));
+
+        assert!(data_source.fetch_profile("AAPL").is_err());
+        assert!(data_source.fetch_profile("AAPL").is_err());
+        assert_eq!(rate_limited_data_source.requests.get(), 2);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{
+    app::DateRange,
+    data_source::{block_on_with_timeout, detect_rate_limit, DataSource, RateLimitError},
+    market::Exchange,
+    stock::{CorporateEvent, CorporateEventKind, NewsHeadline, StockProfile, StockQuote},
+};
+use anyhow::Context;
+use chrono::{DateTime, TimeZone, Utc};
+use derive_new::new;
+use log::warn;
+use reqwest::{StatusCode, Url};
+use serde_json::Value;
+use std::time::Duration;
+use yahoo_finance::{history, Bar, Interval, Profile};
+
+const CHART_URL: &str = "https://query1.finance.yahoo.com/v8/finance/chart";
+const NEWS_COUNT: usize = 10;
+const QUOTE_URL: &str = "https://query1.finance.yahoo.com/v7/finance/quote";
+const SEARCH_URL: &str = "https://query1.finance.yahoo.com/v1/finance/search";
+
+#[derive(Debug, new)]
+pub struct YahooDataSource {
+    /// Time after which a request is abandoned.
+    timeout: Duration,
+}
+
+impl DataSource for YahooDataSource {
+    fn fetch_profile(&self, symbol: &str) -> anyhow::Result<StockProfile> {
+        let profile = block_on_with_timeout(self.timeout, Profile::load(symbol))
+            .map_err(detect_rate_limit)?;
+
+        let name = match profile {
+            Profile::Company(company) => company.name,
+            Profile::Fund(fund) => fund.name,
+        };
+        // the profile has no exchange, so it is taken from the quote, leaving it to the suffix of
+        // the symbol if that fails
+        let exchange = self.fetch_exchange(symbol).unwrap_or_else(|err| {
+            warn!("exchange retrieval failed for {}: {}", symbol, err);
+            None
+        });
+
+        Ok(StockProfile { exchange, name })
+    }
+
+    fn fetch_bars(&self, symbol: &str, date_range: Option<&DateRange>) -> anyhow::Result<Vec<Bar>> {
+        let bars = if let Some(date_range) = date_range {
+            block_on_with_timeout(
+                self.timeout,
+                history::retrieve_range(symbol, date_range.start, Some(date_range.end)),
+            )
+            .map_err(detect_rate_limit)?
+        } else {
+            block_on_with_timeout(
+                self.timeout,
+                history::retrieve_interval(symbol, Interval::_max),
+            )
+            .map_err(detect_rate_limit)?
+        };
+
+        Ok(bars)
+    }
+
+    fn fetch_earnings_dates(&self, symbol: &str) -> anyhow::Result<Vec<DateTime<Utc>>> {
+        // the quote only has the latest report and the start of the next one, which may be the same
+        let url = Url::parse_with_params(QUOTE_URL, &[("symbols", symbol)])?;
+
+        let body = block_on_with_timeout(self.timeout, get_text(url))?;
+        let value: Value = serde_json::from_str(&body)?;
+        let result = match value.pointer("/quoteResponse/result/0") {
+            Some(result) => result,
+            None => return Ok(vec![]),
+        };
+
+        let mut earnings_dates: Vec<_> = ["earningsTimestamp", "earningsTimestampStart"]
+            .iter()
+            .filter_map(|name| result.get(name).and_then(Value::as_i64))
+            .map(|timestamp| Utc.timestamp(timestamp, 0))
+            .collect();
+        earnings_dates.sort();
+        earnings_dates.dedup();
+
+        Ok(earnings_dates)
+    }
+
+    fn fetch_events(&self, symbol: &str) -> anyhow::Result<Vec<CorporateEvent>> {
+        // the events are returned for the whole range regardless of the interval, so use the
+        // coarsest one to keep the response small
+        let url = Url::parse_with_params(
+            &format!("{}/{}", CHART_URL, symbol),
+            &[
+                ("range", "max"),
+                ("interval", "3mo"),
+                ("events", "div,split"),
+            ],
+        )?;
+
+        let body = block_on_with_timeout(self.timeout, get_text(url))?;
+        let value: Value = serde_json::from_str(&body)?;
+        let result = value
+            .pointer("/chart/result/0")
+            .with_context(|| "missing chart result")?;
+
+        let dividends = result
+            .pointer("/events/dividends")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flat_map(|dividends| dividends.values())
+            .filter_map(|dividend| {
+                Some(CorporateEvent {
+                    datetime: Utc.timestamp(dividend.get("date")?.as_i64()?, 0),
+                    kind: CorporateEventKind::Dividend {
+                        amount: dividend.get("amount")?.as_f64()?,
+                    },
+                })
+            });
+        let splits = result
+            .pointer("/events/splits")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flat_map(|splits| splits.values())
+            .filter_map(|split| {
+                Some(CorporateEvent {
+                    datetime: Utc.timestamp(split.get("date")?.as_i64()?, 0),
+                    kind: CorporateEventKind::Split {
+                        denominator: split.get("denominator")?.as_f64()?,
+                        numerator: split.get("numerator")?.as_f64()?,
+                    },
+                })
+            });
+
+        let mut events: Vec<_> = dividends.chain(splits).collect();
+        events.sort_by_key(|event| event.datetime);
+
+        Ok(events)
+    }
+
+    fn fetch_news(&self, symbol: &str) -> anyhow::Result<Vec<NewsHeadline>> {
+        let url = Url::parse_with_params(
+            SEARCH_URL,
+            &[
+                ("q", symbol),
+                ("quotesCount", "0"),
+                ("newsCount", &NEWS_COUNT.to_string()),
+            ],
+        )?;
+
+        let body = block_on_with_timeout(self.timeout, get_text(url))?;
+        let value: Value = serde_json::from_str(&body)?;
+        let news = value
+            .get("news")
+            .and_then(Value::as_array)
+            .with_context(|| "missing news")?;
+
+        let mut headlines: Vec<_> = news
+            .iter()
+            .filter_map(|item| {
+                Some(NewsHeadline {
+                    link: item.get("link").and_then(Value::as_str).map(str::to_owned),
+                    published: Utc.timestamp(item.get("providerPublishTime")?.as_i64()?, 0),
+                    publisher: item.get("publisher")?.as_str()?.to_owned(),
+                    title: item.get("title")?.as_str()?.to_owned(),
+                })
+            })
+            .collect();
+        headlines.sort_by(|a, b| b.published.cmp(&a.published));
+
+        Ok(headlines)
+    }
+
+    fn fetch_quote(&self, symbol: &str) -> anyhow::Result<Option<StockQuote>> {
+        let url = Url::parse_with_params(QUOTE_URL, &[("symbols", symbol)])?;
+
+        let body = block_on_with_timeout(self.timeout, get_text(url))?;
+        let value: Value = serde_json::from_str(&body)?;
+        let result = match value.pointer("/quoteResponse/result/0") {
+            Some(result) => result,
+            None => return Ok(None),
+        };
+        // outside of trading hours the bid and ask are reported as zero
+        let price = |name: &str| {
+            result
+                .get(name)
+                .and_then(Value::as_f64)
+                .filter(|&p| p > 0f64)
+        };
+
+        Ok(Some(StockQuote {
+            ask: price("ask"),
+            bid: price("bid"),
+            day_high: price("regularMarketDayHigh"),
+            day_low: price("regularMarketDayLow"),
+        }))
+    }
+}
+
+impl YahooDataSource {
+    fn fetch_exchange(&self, symbol: &str) -> anyhow::Result<Option<Exchange>> {
+        let url = Url::parse_with_params(QUOTE_URL, &[("symbols", symbol)])?;
+
+        let body = block_on_with_timeout(self.timeout, get_text(url))?;
+        let value: Value = serde_json::from_str(&body)?;
+
+        Ok(value
+            .pointer("/quoteResponse/result/0/exchange")
+            .and_then(Value::as_str)
+            .and_then(Exchange::from_code))
+    }
+}
+
+/// Fetches the body of the response, failing with a `RateLimitError` on a 429 response.
+async fn get_text(url: Url) -> anyhow::Result<String> {
+    let response = reqwest::get(url).await?;
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        return Err(RateLimitError.into());
+    }
+
+    Ok(response.text().await?)
+}
@@ -1,15 +1,19 @@
 use crate::{
-    app::UiTarget,
+    app::{
+        self, ChartTool, ChartToolState, ChartViewport, Indicator, ScrollPanelState, TimeFrame,
+        UiTarget,
+    },
     reactive::{Grouped, StreamExt},
     widgets::{SelectMenuState, TextFieldState},
 };
 use bimap::BiMap;
+use chrono::{DateTime, TimeZone, Utc};
 use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent};
 use derivative::Derivative;
 use im::{hashmap, hashmap::HashMap};
 use log::debug;
 use reactive_rs::Stream;
-use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+use std::{cell::RefCell, cmp, collections::VecDeque, rc::Rc};
 use tui::layout::Rect;
 
 #[derive(Clone, Copy, Debug)]
@@ -21,9 +25,16 @@ pub enum InputEvent {
 
 #[derive(Clone, Copy, Debug)]
 pub enum ChartEvent {
+    /// Centers the date range on the date, e.g. as clicked on the minimap.
+    GoTo(DateTime<Utc>),
+    /// Pans by the duration of the time frame, to the date range before the shown one.
     PanBackward,
     PanForward,
     Reset,
+    /// Pans by a fraction of the duration of the time frame, sliding the date range a few bars at a
+    /// time as the key is held or the wheel scrolled.
+    StepBackward,
+    StepForward,
 }
 
 #[derive(Clone, Debug)]
@@ -41,12 +52,44 @@ pub enum SelectMenuEvent {
     Accept(Option<String>),
     Activate,
     Deactivate,
+    Filter(String),
     SelectIndex(usize),
     Toggle,
 }
 
+/// Changes to the indicator, from the menu, the hide toggle and the parameters field.
+#[derive(Clone, Debug)]
+pub enum IndicatorEvent {
+    /// Shows the indicator after the shown one in the menu, or none after the last.
+    Next,
+    Params(String),
+    /// Shows the indicator before the shown one in the menu, or none before the first.
+    Previous,
+    Select(Option<Indicator>),
+    Toggle,
+}
+
+/// Changes to the symbol and time frame selection, which can be undone and redone.
+#[derive(Clone, Debug)]
+pub enum SelectionEvent {
+    Redo,
+    Symbol(String),
+    TimeFrame(TimeFrame),
+    Undo,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ScrollPanelEvent {
+    Activate,
+    Deactivate,
+    /// Opens the item at the top of the panel.
+    Open(usize),
+    Scroll(usize),
+}
+
 #[derive(Clone, Debug)]
 pub enum OverlayEvent {
+    ScrollPanel(ScrollPanelEvent),
     SelectMenu(SelectMenuEvent),
     TextField(TextFieldEvent),
 }
@@ -127,22 +170,159 @@ where
         )
 }
 
-pub fn to_chart_events<'a, S, C>(input_events: S) -> impl Stream<'a, Item = ChartEvent, Context = C>
+pub fn to_chart_events<'a, S, U, C>(
+    input_events: S,
+    ui_target_areas: U,
+) -> impl Stream<'a, Item = ChartEvent, Context = C>
+where
+    S: Stream<'a, Item = InputEvent, Context = C>,
+    U: Stream<'a, Item = (UiTarget, Option<Rect>)>,
+    C: 'a + Clone,
+{
+    let chart_areas = ui_target_areas
+        .filter(|(ui_target, _)| *ui_target == UiTarget::Chart)
+        .map(|(_, area)| *area);
+
+    input_events
+        .with_latest_from(chart_areas, |(ev, chart_area)| (*ev, *chart_area))
+        .filter_map(|(ev, chart_area)| match ev {
+            InputEvent::Key(KeyEvent { code, .. }) => match code {
+                KeyCode::Left => Some(ChartEvent::StepBackward),
+                KeyCode::Right => Some(ChartEvent::StepForward),
+                KeyCode::End | KeyCode::Char('R') => Some(ChartEvent::Reset),
+                KeyCode::PageUp => Some(ChartEvent::PanBackward),
+                KeyCode::PageDown => Some(ChartEvent::PanForward),
+                _ => None,
+            },
+            // Horizontal scrolling is not reported by crossterm, so only vertical scrolling is
+            // mapped.
+            &InputEvent::Mouse(MouseEvent::ScrollUp(x, y, _))
+            | &InputEvent::Mouse(MouseEvent::ScrollDown(x, y, _)) => {
+                let hit = chart_area.map_or(false, |area| {
+                    area.left() <= x && area.right() > x && area.top() <= y && area.bottom() > y
+                });
+                if !hit {
+                    return None;
+                }
+
+                match ev {
+                    InputEvent::Mouse(MouseEvent::ScrollUp(..)) => Some(ChartEvent::StepBackward),
+                    _ => Some(ChartEvent::StepForward),
+                }
+            }
+            _ => None,
+        })
+}
+
+/// Returns the dates clicked on the minimap to go to, while it is shown.
+pub fn to_minimap_chart_events<'a, S, U, C>(
+    input_events: S,
+    minimap_viewports: U,
+) -> impl Stream<'a, Item = ChartEvent, Context = C>
 where
     S: Stream<'a, Item = InputEvent, Context = C>,
+    U: Stream<'a, Item = Option<ChartViewport>>,
     C: 'a + Clone,
 {
-    input_events.filter_map(|ev| match ev {
-        InputEvent::Key(KeyEvent { code, .. }) => match code {
-            KeyCode::Left => Some(ChartEvent::PanBackward),
-            KeyCode::Right => Some(ChartEvent::PanForward),
-            KeyCode::End => Some(ChartEvent::Reset),
-            KeyCode::PageUp => Some(ChartEvent::PanBackward),
-            KeyCode::PageDown => Some(ChartEvent::PanForward),
+    input_events
+        .with_latest_from(minimap_viewports, |(ev, minimap_viewport)| {
+            (*ev, minimap_viewport.clone())
+        })
+        .filter_map(|(ev, minimap_viewport)| match *ev {
+            InputEvent::Mouse(MouseEvent::Up(MouseButton::Left, x, y, _)) => {
+                let (timestamp, _) = minimap_viewport.as_ref()?.point_to_value((x, y))?;
+                Some(ChartEvent::GoTo(Utc.timestamp(timestamp as i64, 0)))
+            }
             _ => None,
-        },
-        _ => None,
-    })
+        })
+}
+
+/// Returns the favorites selected with their function keys or by clicking them in the favorites
+/// bar.
+pub fn to_favorite_selections<'a, S, U, F, C>(
+    input_events: S,
+    ui_target_areas: U,
+    favorite_lists: F,
+) -> impl Stream<'a, Item = String, Context = C>
+where
+    S: Stream<'a, Item = InputEvent, Context = C>,
+    U: Stream<'a, Item = (UiTarget, Option<Rect>)>,
+    F: Stream<'a, Item = Vec<String>>,
+    C: 'a + Clone,
+{
+    let favorites_bar_states = ui_target_areas
+        .filter(|(ui_target, _)| *ui_target == UiTarget::FavoritesBar)
+        .map(|(_, area)| *area)
+        .combine_latest(favorite_lists, |(favorites_bar_area, favorites)| {
+            (*favorites_bar_area, favorites.clone())
+        });
+
+    input_events
+        .with_latest_from(
+            favorites_bar_states,
+            |(ev, (favorites_bar_area, favorites))| (*ev, *favorites_bar_area, favorites.clone()),
+        )
+        .filter_map(move |(ev, favorites_bar_area, favorites)| {
+            let index = match *ev {
+                InputEvent::Key(KeyEvent {
+                    code: KeyCode::F(n),
+                    ..
+                }) => (n as usize).checked_sub(1),
+                InputEvent::Mouse(MouseEvent::Up(MouseButton::Left, x, y, _)) => favorites_bar_area
+                    .and_then(|area| {
+                        app::favorite_slot_areas(favorites, area)
+                            .iter()
+                            .position(|slot_area| {
+                                slot_area.left() <= x
+                                    && slot_area.right() > x
+                                    && slot_area.top() <= y
+                                    && slot_area.bottom() > y
+                            })
+                    }),
+                _ => None,
+            };
+            index.and_then(|index| favorites.get(index)).cloned()
+        })
+}
+
+pub fn to_chart_tool_states<'a, S, U, C>(
+    input_events: S,
+    chart_viewports: U,
+    chart_tool_hotkey_map: HashMap<KeyCode, ChartTool>,
+) -> impl Stream<'a, Item = ChartToolState, Context = C>
+where
+    S: Stream<'a, Item = InputEvent, Context = C>,
+    U: Stream<'a, Item = ChartViewport>,
+    C: 'a + Clone,
+{
+    input_events
+        .with_latest_from(chart_viewports, |(ev, chart_viewport)| {
+            (*ev, chart_viewport.clone())
+        })
+        .fold(
+            ChartToolState::default(),
+            move |acc_chart_tool_state, (ev, chart_viewport)| match ev {
+                InputEvent::Key(KeyEvent {
+                    code: KeyCode::Esc, ..
+                }) => acc_chart_tool_state.clear_crosshair(),
+                InputEvent::Key(KeyEvent {
+                    code: KeyCode::Char('R'),
+                    ..
+                }) => ChartToolState::default(),
+                InputEvent::Key(KeyEvent { code, .. }) => match chart_tool_hotkey_map.get(code) {
+                    Some(&chart_tool) => acc_chart_tool_state.toggle(chart_tool),
+                    None => acc_chart_tool_state.clone(),
+                },
+                &InputEvent::Mouse(MouseEvent::Up(MouseButton::Left, x, y, _)) => {
+                    match chart_viewport.point_to_value((x, y)) {
+                        Some(point) => acc_chart_tool_state.add_anchor(point),
+                        None => acc_chart_tool_state.clone(),
+                    }
+                }
+                _ => acc_chart_tool_state.clone(),
+            },
+        )
+        .distinct_until_changed()
 }
 
 pub fn to_text_field_events<'a, S, O, U, F, C>(
@@ -220,9 +400,10 @@ where
                     return match (acc_overlay_state, overlay_state) {
                         (OverlayState::Inactive, OverlayState::Active) => (
                             Some(TextFieldEvent::Activate),
-                            TextFieldState {
-                                active: true,
-                                value: acc_text_field_state.value.clone(),
+                            {
+                                let mut text_field_state = acc_text_field_state.clone();
+                                text_field_state.activate();
+                                text_field_state
                             },
                             acc_saved_text_field_state.clone(),
                             *overlay_state,
@@ -241,17 +422,43 @@ where
 
                 match ev {
                     InputEvent::Key(KeyEvent { code, .. }) => match code {
-                        KeyCode::Backspace if acc_text_field_state.active => {
-                            let mut value = acc_text_field_state.value.clone();
-                            value.pop();
-                            let map_value_func = map_value_func.clone();
-                            let value = map_value_func(value);
+                        KeyCode::Backspace | KeyCode::Delete if acc_text_field_state.active => {
+                            let text_field_state = {
+                                let mut text_field_state = acc_text_field_state.clone();
+                                if *code == KeyCode::Backspace {
+                                    text_field_state.delete_backward();
+                                } else {
+                                    text_field_state.delete_forward();
+                                }
+                                let map_value_func = map_value_func.clone();
+                                text_field_state
+                                    .set_value(map_value_func(text_field_state.value.clone()));
+                                text_field_state
+                            };
                             (
-                                Some(TextFieldEvent::Input(value.clone())),
-                                TextFieldState {
-                                    value,
-                                    ..*acc_text_field_state
-                                },
+                                Some(TextFieldEvent::Input(text_field_state.value.clone())),
+                                text_field_state,
+                                acc_saved_text_field_state.clone(),
+                                *overlay_state,
+                            )
+                        }
+                        KeyCode::Left | KeyCode::Right | KeyCode::Home | KeyCode::End
+                            if acc_text_field_state.active =>
+                        {
+                            let text_field_state = {
+                                let mut text_field_state = acc_text_field_state.clone();
+                                let cursor = text_field_state.cursor;
+                                text_field_state.move_cursor(match code {
+                                    KeyCode::Left => cursor.saturating_sub(1),
+                                    KeyCode::Right => cursor + 1,
+                                    KeyCode::Home => 0,
+                                    _ => usize::MAX,
+                                });
+                                text_field_state
+                            };
+                            (
+                                Some(TextFieldEvent::MoveCursor(text_field_state.cursor)),
+                                text_field_state,
                                 acc_saved_text_field_state.clone(),
                                 *overlay_state,
                             )
@@ -280,25 +487,27 @@ where
                         {
                             (
                                 Some(TextFieldEvent::Activate),
-                                TextFieldState {
-                                    active: true,
-                                    value: acc_text_field_state.value.clone(),
+                                {
+                                    let mut text_field_state = acc_text_field_state.clone();
+                                    text_field_state.activate();
+                                    text_field_state
                                 },
                                 acc_saved_text_field_state.clone(),
                                 *overlay_state,
                             )
                         }
                         KeyCode::Char(c) if acc_text_field_state.active => {
-                            let mut value = acc_text_field_state.value.clone();
-                            value.push(*c);
-                            let map_value_func = map_value_func.clone();
-                            let value = map_value_func(value);
+                            let text_field_state = {
+                                let mut text_field_state = acc_text_field_state.clone();
+                                text_field_state.insert(*c);
+                                let map_value_func = map_value_func.clone();
+                                text_field_state
+                                    .set_value(map_value_func(text_field_state.value.clone()));
+                                text_field_state
+                            };
                             (
-                                Some(TextFieldEvent::Input(value.clone())),
-                                TextFieldState {
-                                    value,
-                                    ..*acc_text_field_state
-                                },
+                                Some(TextFieldEvent::Input(text_field_state.value.clone())),
+                                text_field_state,
                                 acc_saved_text_field_state.clone(),
                                 *overlay_state,
                             )
@@ -306,7 +515,6 @@ where
                         _ => noop(),
                     },
                     &InputEvent::Mouse(MouseEvent::Up(MouseButton::Left, x, y, _)) => {
-                        let _point = (x, y);
                         let hit = ui_target_areas.iter().find(|(_, area)| {
                             area.left() <= x
                                 && area.right() > x
@@ -315,19 +523,35 @@ where
                         });
 
                         match hit {
-                            Some(&(ui_target, _area))
+                            Some(&(ui_target, area))
                                 if ui_target == self_ui_target && acc_text_field_state.active =>
                             {
-                                noop()
+                                match acc_text_field_state.point_to_cursor(area, (x, y)) {
+                                    Some(cursor) => {
+                                        let text_field_state = {
+                                            let mut text_field_state = acc_text_field_state.clone();
+                                            text_field_state.move_cursor(cursor);
+                                            text_field_state
+                                        };
+                                        (
+                                            Some(TextFieldEvent::MoveCursor(cursor)),
+                                            text_field_state,
+                                            acc_saved_text_field_state.clone(),
+                                            *overlay_state,
+                                        )
+                                    }
+                                    None => noop(),
+                                }
                             }
                             _ => match text_field_event_map
                                 .get(&hit.map(|(ui_target, _)| *ui_target))
                             {
                                 Some(TextFieldEvent::Activate) if !acc_text_field_state.active => (
                                     Some(TextFieldEvent::Activate),
-                                    TextFieldState {
-                                        active: true,
-                                        value: acc_text_field_state.value.clone(),
+                                    {
+                                        let mut text_field_state = acc_text_field_state.clone();
+                                        text_field_state.activate();
+                                        text_field_state
                                     },
                                     acc_saved_text_field_state.clone(),
                                     *overlay_state,
@@ -352,9 +576,10 @@ where
                                 }
                                 Some(TextFieldEvent::Toggle) if !acc_text_field_state.active => (
                                     Some(TextFieldEvent::Activate),
-                                    TextFieldState {
-                                        active: true,
-                                        value: acc_text_field_state.value.clone(),
+                                    {
+                                        let mut text_field_state = acc_text_field_state.clone();
+                                        text_field_state.activate();
+                                        text_field_state
                                     },
                                     acc_saved_text_field_state.clone(),
                                     *overlay_state,
@@ -377,11 +602,13 @@ where
         })
 }
 
-pub fn to_select_menu_events<'a, S, V, O, U, C>(
+pub fn to_select_menu_events<'a, S, V, E, O, U, C>(
     input_events: S,
     init_select_menu_state: SelectMenuState<V>,
+    selections: E,
     overlay_states: O,
     activation_hotkey: KeyCode,
+    select_hotkey_map: HashMap<KeyCode, V>,
     ui_target_areas: U,
     self_ui_target: UiTarget,
     select_menu_event_map: HashMap<Option<UiTarget>, SelectMenuEvent>,
@@ -389,6 +616,7 @@ pub fn to_select_menu_events<'a, S, V, O, U, C>(
 where
     S: Stream<'a, Item = InputEvent, Context = C>,
     V: 'a + Clone + PartialEq + ToString,
+    E: Stream<'a, Item = Option<V>>,
     O: Stream<'a, Item = OverlayState>,
     U: Stream<'a, Item = (UiTarget, Option<Rect>)>,
     C: 'a + Clone,
@@ -412,71 +640,214 @@ where
                 .collect::<Vec<_>>()
         });
 
-    input_events
-        .combine_latest(
-            overlay_states.distinct_until_changed(),
-            |(ev, overlay_state)| (*ev, *overlay_state),
-        )
-        .with_latest_from(
-            ui_target_area_bufs,
-            |((ev, overlay_state), ui_target_areas)| (*ev, *overlay_state, ui_target_areas.clone()),
-        )
-        .fold(
+    let transition = move |(
+        _,
+        acc_select_menu_state,
+        acc_saved_select_menu_state,
+        acc_overlay_state,
+    ): &(
+        Option<SelectMenuEvent>,
+        SelectMenuState<V>,
+        SelectMenuState<V>,
+        OverlayState,
+    ),
+                           (ev, overlay_state, ui_target_areas): &(
+        InputEvent,
+        OverlayState,
+        Vec<(UiTarget, Rect)>,
+    )| {
+        let noop = || {
             (
                 None,
-                init_select_menu_state.clone(),
-                init_select_menu_state,
-                OverlayState::default(),
-            ),
-            move |(_, acc_select_menu_state, acc_saved_select_menu_state, acc_overlay_state),
-                  (ev, overlay_state, ui_target_areas)| {
-                let noop = || {
+                acc_select_menu_state.clone(),
+                acc_saved_select_menu_state.clone(),
+                *overlay_state,
+            )
+        };
+
+        let overlay_state_transitioned = acc_overlay_state != overlay_state;
+        if overlay_state_transitioned {
+            let overlay_state_changed = match overlay_state {
+                OverlayState::Active => !acc_select_menu_state.active,
+                OverlayState::Inactive => acc_select_menu_state.active,
+            };
+            if !overlay_state_changed {
+                return noop();
+            }
+
+            return match (acc_overlay_state, overlay_state) {
+                (OverlayState::Inactive, OverlayState::Active) => (
+                    Some(SelectMenuEvent::Activate),
+                    {
+                        let mut select_menu_state = acc_saved_select_menu_state.clone();
+                        select_menu_state.active = true;
+                        select_menu_state
+                    },
+                    acc_saved_select_menu_state.clone(),
+                    *overlay_state,
+                ),
+                (OverlayState::Active, OverlayState::Inactive) => (
+                    Some(SelectMenuEvent::Deactivate),
+                    acc_saved_select_menu_state.clone(),
+                    acc_saved_select_menu_state.clone(),
+                    *overlay_state,
+                ),
+                _ => {
+                    unreachable!();
+                }
+            };
+        }
+
+        match ev {
+            InputEvent::Key(KeyEvent { code, .. }) => match code {
+                KeyCode::Backspace
+                    if acc_select_menu_state.active
+                        && !acc_select_menu_state.filter().is_empty() =>
+                {
+                    let select_menu_state = {
+                        let mut select_menu_state = acc_select_menu_state.clone();
+                        let mut filter = select_menu_state.filter().to_owned();
+                        filter.pop();
+                        select_menu_state.set_filter(filter).unwrap();
+                        select_menu_state
+                    };
                     (
-                        None,
-                        acc_select_menu_state.clone(),
+                        Some(SelectMenuEvent::Filter(
+                            select_menu_state.filter().to_owned(),
+                        )),
+                        select_menu_state,
                         acc_saved_select_menu_state.clone(),
                         *overlay_state,
                     )
-                };
-
-                let overlay_state_transitioned = acc_overlay_state != overlay_state;
-                if overlay_state_transitioned {
-                    let overlay_state_changed = match overlay_state {
-                        OverlayState::Active => !acc_select_menu_state.active,
-                        OverlayState::Inactive => acc_select_menu_state.active,
+                }
+                KeyCode::Enter
+                    if acc_select_menu_state.active
+                        && acc_select_menu_state.selected_index().is_some() =>
+                {
+                    let select_menu_state = {
+                        let mut select_menu_state = acc_select_menu_state.clone();
+                        select_menu_state.set_filter(String::new()).unwrap();
+                        select_menu_state.active = false;
+                        select_menu_state
                     };
-                    if !overlay_state_changed {
-                        return noop();
-                    }
-
-                    return match (acc_overlay_state, overlay_state) {
-                        (OverlayState::Inactive, OverlayState::Active) => (
-                            Some(SelectMenuEvent::Activate),
-                            {
-                                let mut select_menu_state = acc_saved_select_menu_state.clone();
-                                select_menu_state.active = true;
-                                select_menu_state
-                            },
+                    (
+                        Some(SelectMenuEvent::Accept(
+                            select_menu_state.selected().map(|s| s.to_string()),
+                        )),
+                        select_menu_state.clone(),
+                        select_menu_state,
+                        *overlay_state,
+                    )
+                }
+                KeyCode::Esc if acc_select_menu_state.active => (
+                    Some(SelectMenuEvent::Deactivate),
+                    acc_saved_select_menu_state.clone(),
+                    acc_saved_select_menu_state.clone(),
+                    *overlay_state,
+                ),
+                KeyCode::Up if acc_select_menu_state.active => {
+                    let select_menu_state = {
+                        let mut select_menu_state = acc_select_menu_state.clone();
+                        select_menu_state.select_prev().unwrap();
+                        select_menu_state
+                    };
+                    if let Some(n) = select_menu_state.selected_index() {
+                        (
+                            Some(SelectMenuEvent::SelectIndex(n)),
+                            select_menu_state,
                             acc_saved_select_menu_state.clone(),
                             *overlay_state,
-                        ),
-                        (OverlayState::Active, OverlayState::Inactive) => (
-                            Some(SelectMenuEvent::Deactivate),
-                            acc_saved_select_menu_state.clone(),
+                        )
+                    } else {
+                        noop()
+                    }
+                }
+                KeyCode::Down if acc_select_menu_state.active => {
+                    let select_menu_state = {
+                        let mut select_menu_state = acc_select_menu_state.clone();
+                        select_menu_state.select_next().unwrap();
+                        select_menu_state
+                    };
+                    if let Some(n) = select_menu_state.selected_index() {
+                        (
+                            Some(SelectMenuEvent::SelectIndex(n)),
+                            select_menu_state,
                             acc_saved_select_menu_state.clone(),
                             *overlay_state,
-                        ),
-                        _ => {
-                            unreachable!();
-                        }
+                        )
+                    } else {
+                        noop()
+                    }
+                }
+                key_code
+                    if !acc_select_menu_state.active
+                        && select_hotkey_map.contains_key(key_code) =>
+                {
+                    let select_menu_state = {
+                        let mut select_menu_state = acc_saved_select_menu_state.clone();
+                        select_menu_state
+                            .select(select_hotkey_map.get(key_code).cloned())
+                            .unwrap();
+                        select_menu_state
+                    };
+                    (
+                        Some(SelectMenuEvent::Accept(
+                            select_menu_state.selected().map(|s| s.to_string()),
+                        )),
+                        select_menu_state.clone(),
+                        select_menu_state,
+                        *overlay_state,
+                    )
+                }
+                &key_code if key_code == activation_hotkey && !acc_select_menu_state.active => (
+                    Some(SelectMenuEvent::Activate),
+                    {
+                        let mut select_menu_state = acc_saved_select_menu_state.clone();
+                        select_menu_state.active = true;
+                        select_menu_state
+                    },
+                    acc_saved_select_menu_state.clone(),
+                    *overlay_state,
+                ),
+                KeyCode::Char(c) if acc_select_menu_state.active => {
+                    let select_menu_state = {
+                        let mut select_menu_state = acc_select_menu_state.clone();
+                        let mut filter = select_menu_state.filter().to_owned();
+                        filter.push(*c);
+                        select_menu_state.set_filter(filter).unwrap();
+                        select_menu_state
                     };
+                    (
+                        Some(SelectMenuEvent::Filter(
+                            select_menu_state.filter().to_owned(),
+                        )),
+                        select_menu_state,
+                        acc_saved_select_menu_state.clone(),
+                        *overlay_state,
+                    )
                 }
+                _ => (
+                    None,
+                    acc_select_menu_state.clone(),
+                    acc_saved_select_menu_state.clone(),
+                    *overlay_state,
+                ),
+            },
+            &InputEvent::Mouse(MouseEvent::Up(MouseButton::Left, x, y, _)) => {
+                let point = (x, y);
+                let hit = ui_target_areas.iter().find(|(_, area)| {
+                    area.left() <= x && area.right() > x && area.top() <= y && area.bottom() > y
+                });
 
-                match ev {
-                    InputEvent::Key(KeyEvent { code, .. }) => match code {
-                        KeyCode::Enter if acc_select_menu_state.active => {
+                match hit {
+                    Some(&(ui_target, area))
+                        if ui_target == self_ui_target && acc_select_menu_state.active =>
+                    {
+                        if let Some(n) = acc_select_menu_state.point_to_index(area, point) {
                             let select_menu_state = {
                                 let mut select_menu_state = acc_select_menu_state.clone();
+                                select_menu_state.select_index(n).unwrap();
+                                select_menu_state.set_filter(String::new()).unwrap();
                                 select_menu_state.active = false;
                                 select_menu_state
                             };
@@ -488,163 +859,266 @@ where
                                 select_menu_state,
                                 *overlay_state,
                             )
+                        } else {
+                            noop()
                         }
-                        KeyCode::Esc if acc_select_menu_state.active => (
-                            Some(SelectMenuEvent::Deactivate),
-                            acc_saved_select_menu_state.clone(),
+                    }
+                    _ => match select_menu_event_map.get(&hit.map(|(ui_target, _)| *ui_target)) {
+                        Some(SelectMenuEvent::Activate) if !acc_select_menu_state.active => (
+                            Some(SelectMenuEvent::Activate),
+                            {
+                                let mut select_menu_state = acc_saved_select_menu_state.clone();
+                                select_menu_state.active = true;
+                                select_menu_state
+                            },
                             acc_saved_select_menu_state.clone(),
                             *overlay_state,
                         ),
-                        KeyCode::Up if acc_select_menu_state.active => {
-                            let select_menu_state = {
-                                let mut select_menu_state = acc_select_menu_state.clone();
-                                select_menu_state.select_prev().unwrap();
-                                select_menu_state
-                            };
+                        Some(SelectMenuEvent::Activate) if acc_select_menu_state.active => noop(),
+                        Some(SelectMenuEvent::Deactivate) | Some(SelectMenuEvent::Toggle)
+                            if acc_select_menu_state.active =>
+                        {
                             (
-                                Some(SelectMenuEvent::SelectIndex(
-                                    select_menu_state.selected_index().unwrap(),
-                                )),
-                                select_menu_state,
+                                Some(SelectMenuEvent::Deactivate),
                                 acc_saved_select_menu_state.clone(),
-                                *overlay_state,
-                            )
-                        }
-                        KeyCode::Down if acc_select_menu_state.active => {
-                            let select_menu_state = {
-                                let mut select_menu_state = acc_select_menu_state.clone();
-                                select_menu_state.select_next().unwrap();
-                                select_menu_state
-                            };
-                            (
-                                Some(SelectMenuEvent::SelectIndex(
-                                    select_menu_state.selected_index().unwrap(),
-                                )),
-                                select_menu_state,
                                 acc_saved_select_menu_state.clone(),
                                 *overlay_state,
                             )
                         }
-                        &key_code
-                            if key_code == activation_hotkey && !acc_select_menu_state.active =>
-                        {
-                            (
-                                Some(SelectMenuEvent::Activate),
-                                {
-                                    let mut select_menu_state = acc_saved_select_menu_state.clone();
-                                    select_menu_state.active = true;
-                                    select_menu_state
-                                },
-                                acc_saved_select_menu_state.clone(),
-                                *overlay_state,
-                            )
+                        Some(SelectMenuEvent::Deactivate) if !acc_select_menu_state.active => {
+                            noop()
+                        }
+                        Some(SelectMenuEvent::Toggle) if !acc_select_menu_state.active => (
+                            Some(SelectMenuEvent::Activate),
+                            {
+                                let mut select_menu_state = acc_saved_select_menu_state.clone();
+                                select_menu_state.active = true;
+                                select_menu_state
+                            },
+                            acc_saved_select_menu_state.clone(),
+                            *overlay_state,
+                        ),
+                        Some(ev) => {
+                            unimplemented!("unhandled select menu event: {:?}", ev);
                         }
-                        // KeyCode::Char(c) if acc_select_menu_state.active => {
-                        //     todo!();
-                        // }
-                        _ => (
-                            None,
+                        None => noop(),
+                    },
+                }
+            }
+            _ => noop(),
+        }
+    };
+
+    input_events
+        .combine_latest(
+            overlay_states.distinct_until_changed(),
+            |(ev, overlay_state)| (*ev, *overlay_state),
+        )
+        .with_latest_from(
+            ui_target_area_bufs,
+            |((ev, overlay_state), ui_target_areas)| (*ev, *overlay_state, ui_target_areas.clone()),
+        )
+        .with_latest_from(
+            selections,
+            |((ev, overlay_state, ui_target_areas), selection)| {
+                (
+                    *ev,
+                    *overlay_state,
+                    ui_target_areas.clone(),
+                    selection.clone(),
+                )
+            },
+        )
+        .fold(
+            (
+                None,
+                init_select_menu_state.clone(),
+                init_select_menu_state.clone(),
+                OverlayState::default(),
+                init_select_menu_state.selected(),
+            ),
+            move |(
+                acc_ev,
+                acc_select_menu_state,
+                acc_saved_select_menu_state,
+                acc_overlay_state,
+                acc_selection,
+            ),
+                  (ev, overlay_state, ui_target_areas, selection)| {
+                // A selection made elsewhere, e.g. by undoing, is taken over while the menu is
+                // closed, before the event is handled.
+                let (acc_select_menu_state, acc_saved_select_menu_state, acc_selection) =
+                    if selection != acc_selection && !acc_select_menu_state.active {
+                        let mut select_menu_state = acc_select_menu_state.clone();
+                        let mut saved_select_menu_state = acc_saved_select_menu_state.clone();
+                        // an item missing from the menu leaves the selection as it is
+                        select_menu_state.select(selection.clone()).ok();
+                        saved_select_menu_state.select(selection.clone()).ok();
+                        (
+                            select_menu_state,
+                            saved_select_menu_state,
+                            selection.clone(),
+                        )
+                    } else {
+                        (
                             acc_select_menu_state.clone(),
                             acc_saved_select_menu_state.clone(),
+                            acc_selection.clone(),
+                        )
+                    };
+                let (ev, mut select_menu_state, mut saved_select_menu_state, overlay_state) =
+                    transition(
+                        &(
+                            acc_ev.clone(),
+                            acc_select_menu_state,
+                            acc_saved_select_menu_state,
+                            *acc_overlay_state,
+                        ),
+                        &(*ev, *overlay_state, ui_target_areas.clone()),
+                    );
+                // Scrolled here rather than when drawn, which only scrolls a copy of the state, so
+                // that a click picks the row drawn under it.
+                if let Some((_, area)) = ui_target_areas
+                    .iter()
+                    .find(|(ui_target, _)| *ui_target == self_ui_target)
+                {
+                    let list_height = area.height.saturating_sub(2) as usize;
+                    select_menu_state.scroll_to_selected(list_height);
+                    saved_select_menu_state.scroll_to_selected(list_height);
+                }
+                (
+                    ev,
+                    select_menu_state,
+                    saved_select_menu_state,
+                    overlay_state,
+                    acc_selection,
+                )
+            },
+        )
+        .filter_map(|(ev, select_menu_state, ..)| {
+            ev.as_ref()
+                .cloned()
+                .map(|ev| (ev, select_menu_state.clone()))
+        })
+}
+
+pub fn to_scroll_panel_events<'a, S, O, U, N, C>(
+    input_events: S,
+    overlay_states: O,
+    activation_hotkey: KeyCode,
+    ui_target_areas: U,
+    self_ui_target: UiTarget,
+    item_counts: N,
+) -> impl Stream<'a, Item = (ScrollPanelEvent, ScrollPanelState), Context = C>
+where
+    S: Stream<'a, Item = InputEvent, Context = C>,
+    O: Stream<'a, Item = OverlayState>,
+    U: Stream<'a, Item = (UiTarget, Option<Rect>)>,
+    N: Stream<'a, Item = usize>,
+    C: 'a + Clone,
+{
+    let self_areas = ui_target_areas
+        .filter(move |(ui_target, _)| *ui_target == self_ui_target)
+        .map(|(_, area)| *area);
+
+    input_events
+        .combine_latest(
+            overlay_states.distinct_until_changed(),
+            |(ev, overlay_state)| (*ev, *overlay_state),
+        )
+        .with_latest_from(self_areas, |((ev, overlay_state), area)| {
+            (*ev, *overlay_state, *area)
+        })
+        .with_latest_from(item_counts, |((ev, overlay_state, area), item_count)| {
+            (*ev, *overlay_state, *area, *item_count)
+        })
+        .fold(
+            (None, ScrollPanelState::default(), OverlayState::default()),
+            move |(_, acc_scroll_panel_state, acc_overlay_state),
+                  (ev, overlay_state, area, item_count)| {
+                let noop = || (None, *acc_scroll_panel_state, *overlay_state);
+                let activate = || {
+                    (
+                        Some(ScrollPanelEvent::Activate),
+                        ScrollPanelState {
+                            active: true,
+                            scroll: 0,
+                        },
+                        *overlay_state,
+                    )
+                };
+                let deactivate = || {
+                    (
+                        Some(ScrollPanelEvent::Deactivate),
+                        ScrollPanelState::default(),
+                        *overlay_state,
+                    )
+                };
+                let scroll = |scroll| {
+                    (
+                        Some(ScrollPanelEvent::Scroll(scroll)),
+                        ScrollPanelState {
+                            scroll,
+                            ..*acc_scroll_panel_state
+                        },
+                        *overlay_state,
+                    )
+                };
+
+                if acc_overlay_state != overlay_state {
+                    return match overlay_state {
+                        OverlayState::Active if !acc_scroll_panel_state.active => activate(),
+                        OverlayState::Inactive if acc_scroll_panel_state.active => deactivate(),
+                        _ => noop(),
+                    };
+                }
+
+                match ev {
+                    InputEvent::Key(KeyEvent { code, .. }) => match code {
+                        KeyCode::Up | KeyCode::Char('k') if acc_scroll_panel_state.active => {
+                            scroll(acc_scroll_panel_state.scroll.saturating_sub(1))
+                        }
+                        KeyCode::Down | KeyCode::Char('j') if acc_scroll_panel_state.active => {
+                            scroll(cmp::min(
+                                acc_scroll_panel_state.scroll + 1,
+                                item_count.saturating_sub(1),
+                            ))
+                        }
+                        KeyCode::Enter if acc_scroll_panel_state.active && item_count > 0 => (
+                            Some(ScrollPanelEvent::Open(acc_scroll_panel_state.scroll)),
+                            *acc_scroll_panel_state,
                             *overlay_state,
                         ),
+                        KeyCode::Esc if acc_scroll_panel_state.active => deactivate(),
+                        &key_code if key_code == activation_hotkey => {
+                            if acc_scroll_panel_state.active {
+                                deactivate()
+                            } else {
+                                activate()
+                            }
+                        }
+                        _ => noop(),
                     },
-                    &InputEvent::Mouse(MouseEvent::Up(MouseButton::Left, x, y, _)) => {
-                        let point = (x, y);
-                        let hit = ui_target_areas.iter().find(|(_, area)| {
+                    &InputEvent::Mouse(MouseEvent::Up(MouseButton::Left, x, y, _))
+                        if acc_scroll_panel_state.active =>
+                    {
+                        let hit = area.map_or(false, |area| {
                             area.left() <= x
                                 && area.right() > x
                                 && area.top() <= y
                                 && area.bottom() > y
                         });
-
-                        match hit {
-                            Some(&(ui_target, area))
-                                if ui_target == self_ui_target && acc_select_menu_state.active =>
-                            {
-                                if let Some(n) = acc_select_menu_state.point_to_index(area, point) {
-                                    let select_menu_state = {
-                                        let mut select_menu_state = acc_select_menu_state.clone();
-                                        select_menu_state.select_index(n).unwrap();
-                                        select_menu_state.active = false;
-                                        select_menu_state
-                                    };
-                                    (
-                                        Some(SelectMenuEvent::Accept(
-                                            select_menu_state.selected().map(|s| s.to_string()),
-                                        )),
-                                        select_menu_state.clone(),
-                                        select_menu_state,
-                                        *overlay_state,
-                                    )
-                                } else {
-                                    noop()
-                                }
-                            }
-                            _ => match select_menu_event_map
-                                .get(&hit.map(|(ui_target, _)| *ui_target))
-                            {
-                                Some(SelectMenuEvent::Activate)
-                                    if !acc_select_menu_state.active =>
-                                {
-                                    (
-                                        Some(SelectMenuEvent::Activate),
-                                        {
-                                            let mut select_menu_state =
-                                                acc_saved_select_menu_state.clone();
-                                            select_menu_state.active = true;
-                                            select_menu_state
-                                        },
-                                        acc_saved_select_menu_state.clone(),
-                                        *overlay_state,
-                                    )
-                                }
-                                Some(SelectMenuEvent::Activate) if acc_select_menu_state.active => {
-                                    noop()
-                                }
-                                Some(SelectMenuEvent::Deactivate)
-                                | Some(SelectMenuEvent::Toggle)
-                                    if acc_select_menu_state.active =>
-                                {
-                                    (
-                                        Some(SelectMenuEvent::Deactivate),
-                                        acc_saved_select_menu_state.clone(),
-                                        acc_saved_select_menu_state.clone(),
-                                        *overlay_state,
-                                    )
-                                }
-                                Some(SelectMenuEvent::Deactivate)
-                                    if !acc_select_menu_state.active =>
-                                {
-                                    noop()
-                                }
-                                Some(SelectMenuEvent::Toggle) if !acc_select_menu_state.active => (
-                                    Some(SelectMenuEvent::Activate),
-                                    {
-                                        let mut select_menu_state =
-                                            acc_saved_select_menu_state.clone();
-                                        select_menu_state.active = true;
-                                        select_menu_state
-                                    },
-                                    acc_saved_select_menu_state.clone(),
-                                    *overlay_state,
-                                ),
-                                Some(ev) => {
-                                    unimplemented!("unhandled select menu event: {:?}", ev);
-                                }
-                                None => noop(),
-                            },
+                        if hit {
+                            noop()
+                        } else {
+                            deactivate()
                         }
                     }
                     _ => noop(),
                 }
             },
         )
-        .filter_map(|(ev, select_menu_state, ..)| {
-            ev.as_ref()
-                .cloned()
-                .map(|ev| (ev, select_menu_state.clone()))
-        })
+        .filter_map(|(ev, scroll_panel_state, _)| ev.map(|ev| (ev, *scroll_panel_state)))
 }
 
 /// Queues the overlay states to send on next tick.
@@ -679,6 +1153,13 @@ pub fn queue_overlay_states_for_next_tick<'a, S>(
                         }
                         _ => acc_overlay_state,
                     },
+                    OverlayEvent::ScrollPanel(ev) => match ev {
+                        ScrollPanelEvent::Activate => OverlayState::Active,
+                        ScrollPanelEvent::Deactivate => OverlayState::Inactive,
+                        ScrollPanelEvent::Scroll(_) | ScrollPanelEvent::Open(_) => {
+                            acc_overlay_state
+                        }
+                    },
                     OverlayEvent::SelectMenu(ev) => match ev {
                         SelectMenuEvent::Activate => OverlayState::Active,
                         SelectMenuEvent::Accept(_) | SelectMenuEvent::Deactivate => {
@@ -760,3 +1241,287 @@ where
             debug!("active overlay: {:?}", active_overlay);
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{click, key, type_str, OverlayInputs, Recorder};
+    use reactive_rs::Broadcast;
+    use strum::IntoEnumIterator;
+
+    const FIELD_AREA: Rect = Rect {
+        x: 0,
+        y: 0,
+        width: 20,
+        height: 3,
+    };
+
+    fn text_field_events(inputs: &OverlayInputs) -> Recorder<(TextFieldEvent, TextFieldState)> {
+        let text_field_events = Recorder::subscribe(to_text_field_events(
+            inputs.input_events.clone(),
+            TextFieldState::default(),
+            inputs.overlay_states.clone(),
+            KeyCode::Char('s'),
+            inputs.ui_target_areas.clone(),
+            UiTarget::StockSymbolField,
+            hashmap! {
+                None => TextFieldEvent::Deactivate,
+            },
+            |value: String| value.to_ascii_uppercase(),
+        ));
+        inputs.init(&[(UiTarget::StockSymbolField, Some(FIELD_AREA))]);
+        text_field_events
+    }
+
+    #[test]
+    fn text_field_accepts_mapped_input() {
+        let inputs = OverlayInputs::default();
+        let text_field_events = text_field_events(&inputs);
+
+        inputs.send_all(vec![key(KeyCode::Char('s'))]);
+        inputs.send_all(type_str("aapl "));
+        let events = text_field_events.take();
+
+        assert!(matches!(events[0], (TextFieldEvent::Activate, ref state) if state.active));
+        assert!(
+            matches!(events.last(), Some((TextFieldEvent::Input(value), _)) if value == "AAPL ")
+        );
+
+        inputs.send_all(vec![key(KeyCode::Enter)]);
+
+        assert!(matches!(
+            text_field_events.take().as_slice(),
+            [(TextFieldEvent::Accept(value), state)] if value == "AAPL" && !state.active
+        ));
+    }
+
+    #[test]
+    fn text_field_discards_input_on_esc() {
+        let inputs = OverlayInputs::default();
+        let text_field_events = text_field_events(&inputs);
+
+        inputs.send_all(vec![key(KeyCode::Char('s'))]);
+        inputs.send_all(type_str("msft"));
+        text_field_events.take();
+        inputs.send_all(vec![key(KeyCode::Esc)]);
+
+        assert!(matches!(
+            text_field_events.take().as_slice(),
+            [(TextFieldEvent::Deactivate, state)] if state.value.is_empty() && !state.active
+        ));
+    }
+
+    #[test]
+    fn text_field_deactivates_on_click_outside() {
+        let inputs = OverlayInputs::default();
+        let text_field_events = text_field_events(&inputs);
+
+        inputs.send_all(vec![key(KeyCode::Char('s'))]);
+        text_field_events.take();
+        inputs.send_all(vec![click(5, 1)]);
+
+        assert!(matches!(
+            text_field_events.take().as_slice(),
+            [(TextFieldEvent::MoveCursor(_), _)]
+        ));
+
+        inputs.send_all(vec![click(30, 10)]);
+
+        assert!(matches!(
+            text_field_events.take().as_slice(),
+            [(TextFieldEvent::Deactivate, _)]
+        ));
+    }
+
+    #[test]
+    fn text_field_follows_overlay_state() {
+        let inputs = OverlayInputs::default();
+        let text_field_events = text_field_events(&inputs);
+
+        inputs.send_all(vec![InputEvent::Tick]);
+        inputs.overlay_states.send(OverlayState::Active);
+        inputs.overlay_states.send(OverlayState::Inactive);
+
+        assert!(matches!(
+            text_field_events.take().as_slice(),
+            [
+                (TextFieldEvent::Activate, _),
+                (TextFieldEvent::Deactivate, _)
+            ]
+        ));
+    }
+
+    fn time_frame_menu_events(
+        inputs: &OverlayInputs,
+        menu_area: Option<Rect>,
+        selections: &Broadcast<'static, (), Option<TimeFrame>>,
+    ) -> Recorder<(SelectMenuEvent, SelectMenuState<TimeFrame>)> {
+        let init_select_menu_state = {
+            let mut select_menu_state = SelectMenuState::new(TimeFrame::iter());
+            select_menu_state.select(Some(TimeFrame::OneMonth)).unwrap();
+            select_menu_state
+        };
+        let select_menu_events = Recorder::subscribe(to_select_menu_events(
+            inputs.input_events.clone(),
+            init_select_menu_state,
+            selections.clone(),
+            inputs.overlay_states.clone(),
+            KeyCode::Char('t'),
+            hashmap! {
+                KeyCode::Char('1') => TimeFrame::FiveDays,
+            },
+            inputs.ui_target_areas.clone(),
+            UiTarget::TimeFrameMenu,
+            hashmap! {
+                None => SelectMenuEvent::Deactivate,
+            },
+        ));
+        selections.send(Some(TimeFrame::OneMonth));
+        inputs.init(&[(UiTarget::TimeFrameMenu, menu_area)]);
+        select_menu_events
+    }
+
+    #[test]
+    fn select_menu_selects_by_hotkey_without_activating() {
+        let inputs = OverlayInputs::default();
+        let select_menu_events = time_frame_menu_events(&inputs, None, &Broadcast::new());
+
+        inputs.send_all(vec![key(KeyCode::Char('1'))]);
+
+        assert!(matches!(
+            select_menu_events.take().as_slice(),
+            [(SelectMenuEvent::Accept(Some(value)), state)]
+                if value == "5D" && state.selected() == Some(TimeFrame::FiveDays) && !state.active
+        ));
+    }
+
+    #[test]
+    fn select_menu_accepts_the_highlighted_item() {
+        let inputs = OverlayInputs::default();
+        let select_menu_events = time_frame_menu_events(&inputs, None, &Broadcast::new());
+
+        inputs.send_all(vec![
+            key(KeyCode::Char('t')),
+            key(KeyCode::Down),
+            key(KeyCode::Enter),
+        ]);
+
+        assert!(matches!(
+            select_menu_events.take().as_slice(),
+            [
+                (SelectMenuEvent::Activate, _),
+                (SelectMenuEvent::SelectIndex(3), _),
+                (SelectMenuEvent::Accept(Some(value)), state),
+            ] if value == "3M" && !state.active
+        ));
+    }
+
+    #[test]
+    fn select_menu_filters_the_items() {
+        let inputs = OverlayInputs::default();
+        let select_menu_events = time_frame_menu_events(&inputs, None, &Broadcast::new());
+
+        inputs.send_all(vec![key(KeyCode::Char('t'))]);
+        inputs.send_all(type_str("yx"));
+        inputs.send_all(vec![key(KeyCode::Backspace), key(KeyCode::Enter)]);
+
+        assert!(matches!(
+            select_menu_events.take().as_slice(),
+            [
+                (SelectMenuEvent::Activate, _),
+                (SelectMenuEvent::Filter(_), _),
+                (SelectMenuEvent::Filter(_), _),
+                (SelectMenuEvent::Filter(filter), _),
+                (SelectMenuEvent::Accept(Some(value)), _),
+            ] if filter == "y" && value == "YTD"
+        ));
+    }
+
+    #[test]
+    fn select_menu_restores_the_selection_on_esc() {
+        let inputs = OverlayInputs::default();
+        let select_menu_events = time_frame_menu_events(&inputs, None, &Broadcast::new());
+
+        inputs.send_all(vec![
+            key(KeyCode::Char('t')),
+            key(KeyCode::Down),
+            key(KeyCode::Down),
+        ]);
+        select_menu_events.take();
+        inputs.send_all(vec![key(KeyCode::Esc)]);
+
+        assert!(matches!(
+            select_menu_events.take().as_slice(),
+            [(SelectMenuEvent::Deactivate, state)]
+                if state.selected() == Some(TimeFrame::OneMonth) && !state.active
+        ));
+    }
+
+    #[test]
+    fn select_menu_takes_over_a_selection_made_elsewhere() {
+        let inputs = OverlayInputs::default();
+        let selections = Broadcast::new();
+        let select_menu_events = time_frame_menu_events(&inputs, None, &selections);
+
+        selections.send(Some(TimeFrame::OneYear));
+        inputs.send_all(vec![key(KeyCode::Char('t')), key(KeyCode::Enter)]);
+
+        assert!(matches!(
+            select_menu_events.take().as_slice(),
+            [
+                (SelectMenuEvent::Activate, state),
+                (SelectMenuEvent::Accept(Some(value)), _),
+            ] if state.selected() == Some(TimeFrame::OneYear) && value == "1Y"
+        ));
+    }
+
+    #[test]
+    fn select_menu_accepts_the_clicked_item_when_scrolled() {
+        let inputs = OverlayInputs::default();
+        // the borders leave 3 rows in view
+        let select_menu_events =
+            time_frame_menu_events(&inputs, Some(Rect::new(0, 0, 10, 5)), &Broadcast::new());
+
+        inputs.send_all(vec![
+            key(KeyCode::Char('t')),
+            key(KeyCode::Down),
+            key(KeyCode::Down),
+        ]);
+        select_menu_events.take();
+        inputs.send_all(vec![click(2, 1)]);
+
+        assert!(matches!(
+            select_menu_events.take().as_slice(),
+            [(SelectMenuEvent::Accept(Some(value)), _)] if value == "1M"
+        ));
+    }
+
+    #[test]
+    fn selects_favorites_by_function_key_or_click() {
+        let inputs = OverlayInputs::default();
+        let favorite_lists: Broadcast<(), Vec<String>> = Broadcast::new();
+        let favorite_selections = Recorder::subscribe(to_favorite_selections(
+            inputs.input_events.clone(),
+            inputs.ui_target_areas.clone(),
+            favorite_lists.clone(),
+        ));
+        favorite_lists.send(vec![
+            "AAPL".to_owned(),
+            "MSFT".to_owned(),
+            "TSLA".to_owned(),
+        ]);
+        // the slots are "F1 AAPL" at 1, "F2 MSFT" at 10 and "F3 TSLA" at 19
+        inputs.init(&[(UiTarget::FavoritesBar, Some(Rect::new(1, 1, 30, 1)))]);
+
+        inputs.send_all(vec![
+            key(KeyCode::F(2)),
+            key(KeyCode::F(5)),
+            click(11, 1),
+            click(8, 1),
+            click(19, 1),
+            click(19, 2),
+        ]);
+
+        assert_eq!(favorite_selections.take(), vec!["MSFT", "MSFT", "TSLA"]);
+    }
+}
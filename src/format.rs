@@ -0,0 +1,246 @@
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Duration, TimeZone, Utc,
+};
+use log::warn;
+use std::{cmp, fmt, str::FromStr};
+use thiserror::Error;
+
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+/// Most decimal places of a price, whether based on its magnitude or set for the symbol.
+pub const MAX_PRICE_PRECISION: usize = 10;
+const SI_SUFFIXES: [(f64, &str); 4] = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")];
+
+/// Format of the displayed dates, in strftime syntax.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DateFormat(String);
+
+impl Default for DateFormat {
+    fn default() -> Self {
+        Self(DEFAULT_DATE_FORMAT.to_owned())
+    }
+}
+
+impl DateFormat {
+    /// Falls back to the default format if the format is empty or invalid, which chrono would
+    /// otherwise only report by panicking when formatting.
+    pub fn new(format: &str) -> Self {
+        if format.is_empty() || StrftimeItems::new(format).any(|item| item == Item::Error) {
+            warn!(
+                "invalid date format {:?}, using {:?} instead",
+                format, DEFAULT_DATE_FORMAT
+            );
+            return Self::default();
+        }
+
+        Self(format.to_owned())
+    }
+
+    pub fn format<Tz>(&self, datetime: &DateTime<Tz>) -> String
+    where
+        Tz: TimeZone,
+        Tz::Offset: fmt::Display,
+    {
+        datetime.format(&self.0).to_string()
+    }
+
+    /// Returns the width of a formatted date, for spacing the axis labels.
+    pub fn width(&self) -> u16 {
+        self.format(&Utc.ymd(2000, 12, 28).and_hms(23, 59, 59))
+            .chars()
+            .count() as u16
+    }
+}
+
+/// Number format conventions of a locale.
+///
+/// The default format has a `.` decimal mark and no digit grouping.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct NumberFormat {
+    pub decimal_mark: char,
+    pub grouping_separator: Option<char>,
+    /// Decimal places of the prices, or based on their magnitude if `None`.
+    pub price_precision: Option<usize>,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            decimal_mark: '.',
+            grouping_separator: None,
+            price_precision: None,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Formats a number with the given number of decimal places.
+    pub fn format_decimal(&self, value: f64, precision: usize) -> String {
+        let formatted = format!("{:.*}", precision, value.abs());
+        let (integer_part, fraction_part) = match formatted.find('.') {
+            Some(i) => (&formatted[..i], Some(&formatted[(i + 1)..])),
+            None => (formatted.as_str(), None),
+        };
+
+        let mut s = String::new();
+        if value.is_sign_negative() && formatted.chars().any(|c| c.is_digit(10) && c != '0') {
+            s.push('-');
+        }
+        for (i, c) in integer_part.chars().enumerate() {
+            if i > 0 && (integer_part.len() - i) % 3 == 0 {
+                if let Some(grouping_separator) = self.grouping_separator {
+                    s.push(grouping_separator);
+                }
+            }
+            s.push(c);
+        }
+        if let Some(fraction_part) = fraction_part {
+            s.push(self.decimal_mark);
+            s.push_str(fraction_part);
+        }
+
+        s
+    }
+
+    /// Formats a price with the number of decimal places set for the symbol, or else based on its
+    /// magnitude, so that sub-dollar prices keep their significant digits.
+    pub fn format_price(&self, price: f64) -> String {
+        let precision = self
+            .price_precision
+            .unwrap_or_else(|| price_precision(price));
+        self.format_decimal(price, precision)
+    }
+
+    pub fn with_price_precision(self, price_precision: Option<usize>) -> Self {
+        Self {
+            price_precision,
+            ..self
+        }
+    }
+
+    /// Formats a number using a K/M/B/T suffix, e.g. `12.3M`.
+    pub fn format_si(&self, value: f64) -> String {
+        SI_SUFFIXES
+            .iter()
+            .find(|(magnitude, _)| value.abs() >= *magnitude)
+            .map_or_else(
+                || self.format_decimal(value, 0),
+                |(magnitude, suffix)| {
+                    format!("{}{}", self.format_decimal(value / magnitude, 1), suffix)
+                },
+            )
+    }
+}
+
+impl FromStr for NumberFormat {
+    type Err = ParseNumberFormatError;
+
+    /// Parses a locale tag, e.g. `en-US` or `de_DE`. Only the language, and the region where it
+    /// matters, are taken into account.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseNumberFormatError::Empty);
+        }
+
+        let s = s.replace('_', "-").to_lowercase();
+        let language = s.split('-').next().unwrap();
+
+        let (decimal_mark, grouping_separator) = match (s.as_str(), language) {
+            ("de-ch", _) | ("it-ch", _) | ("fr-ch", _) => ('.', '\''),
+            (_, "en") | (_, "ja") | (_, "ko") | (_, "zh") | (_, "th") | (_, "he") => ('.', ','),
+            (_, "de")
+            | (_, "da")
+            | (_, "es")
+            | (_, "id")
+            | (_, "it")
+            | (_, "nl")
+            | (_, "pt")
+            | (_, "tr") => (',', '.'),
+            (_, "cs")
+            | (_, "fi")
+            | (_, "fr")
+            | (_, "nb")
+            | (_, "no")
+            | (_, "pl")
+            | (_, "ru")
+            | (_, "sk")
+            | (_, "sv")
+            | (_, "uk") => (',', '\u{a0}'),
+            _ => return Err(ParseNumberFormatError::Unsupported),
+        };
+
+        Ok(Self {
+            decimal_mark,
+            grouping_separator: Some(grouping_separator),
+            price_precision: None,
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseNumberFormatError {
+    #[error("cannot parse locale from empty string")]
+    Empty,
+    #[error("unsupported locale")]
+    Unsupported,
+}
+
+/// Formats a duration in its two largest units, e.g. `2d 5h`, `3h 12m` or `45m`.
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Returns the decimal places of a price based on its magnitude: 2 from a dollar up, 4 from a cent,
+/// and below that enough to keep 4 significant digits.
+pub fn price_precision(price: f64) -> usize {
+    let price = price.abs();
+
+    if price == 0f64 || !price.is_finite() || price >= 1f64 {
+        2
+    } else if price >= 0.01 {
+        4
+    } else {
+        let leading_zeros = -price.log10().floor() as usize - 1;
+        cmp::min(leading_zeros + 4, MAX_PRICE_PRECISION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_significant_digits_of_small_prices() {
+        let number_format = NumberFormat::default();
+
+        assert_eq!(number_format.format_price(123.456), "123.46");
+        assert_eq!(number_format.format_price(0.12345), "0.1235");
+        assert_eq!(number_format.format_price(0.0042), "0.004200");
+        assert_eq!(number_format.format_price(0.00000042), "0.0000004200");
+        assert_eq!(price_precision(1e-15), MAX_PRICE_PRECISION);
+    }
+
+    #[test]
+    fn formats_prices_with_the_precision_of_the_symbol() {
+        let number_format = NumberFormat::default().with_price_precision(Some(4));
+
+        assert_eq!(number_format.format_price(1.08), "1.0800");
+        assert_eq!(number_format.format_price(0.0042), "0.0042");
+    }
+
+    #[test]
+    fn formats_durations_in_the_two_largest_units() {
+        assert_eq!(format_duration(Duration::minutes(45)), "45m");
+        assert_eq!(format_duration(Duration::minutes(192)), "3h 12m");
+        assert_eq!(format_duration(Duration::hours(53)), "2d 5h");
+    }
+}
@@ -0,0 +1,256 @@
+use crate::app::Indicator;
+use ta::{indicators, DataItem, Next};
+use yahoo_finance::{Bar, Timestamped};
+
+/// An indicator computed from the bars, to be drawn over the prices. Another one is added with its
+/// implementation and an entry in `INDICATOR_REGISTRY`.
+pub trait ComputeIndicator {
+    /// Number of bars before the first full value of the indicator, which are fetched ahead of the
+    /// date range.
+    fn lookback(&self) -> u16;
+
+    /// Computes the indicator from the bars, oldest first.
+    fn compute(&self, bars: &[Bar]) -> IndicatorOutput;
+}
+
+/// The lines of a computed indicator, in the order they are drawn.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IndicatorOutput {
+    pub lines: Vec<IndicatorLine>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndicatorLine {
+    /// Name of the line, as shown in the tooltip.
+    pub name: String,
+    pub role: IndicatorLineRole,
+    /// The values of the line, by the timestamp of their bar in seconds.
+    pub data: Vec<(f64, f64)>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IndicatorLineRole {
+    /// A band around the main line, drawn dimmed.
+    Band,
+    /// Drawn in the color of the indicator.
+    Main,
+}
+
+/// Most parameters an indicator of the registry takes.
+pub const MAX_INDICATOR_PARAMS: usize = 4;
+
+/// The indicators offered in the indicator menu, in its order, each parsed from and displayed as its
+/// name followed by its comma separated parameters, e.g. `BB(20, 2)`.
+pub static INDICATOR_REGISTRY: &[IndicatorEntry] = &[
+    IndicatorEntry {
+        name: "BB",
+        long_name: "bollinger_bands",
+        params: &[
+            IndicatorParam {
+                name: "period",
+                default: 20,
+                is_period: true,
+            },
+            IndicatorParam {
+                name: "multiplier",
+                default: 2,
+                is_period: false,
+            },
+        ],
+        factory: |params| {
+            Box::new(BollingerBands {
+                n: params[0],
+                k: f64::from(params[1]),
+            })
+        },
+    },
+    IndicatorEntry {
+        name: "EMA",
+        long_name: "exponential_moving_average",
+        params: &[IndicatorParam {
+            name: "period",
+            default: 50,
+            is_period: true,
+        }],
+        factory: |params| Box::new(ExponentialMovingAverage { n: params[0] }),
+    },
+    IndicatorEntry {
+        name: "SMA",
+        long_name: "simple_moving_average",
+        params: &[IndicatorParam {
+            name: "period",
+            default: 50,
+            is_period: true,
+        }],
+        factory: |params| Box::new(SimpleMovingAverage { n: params[0] }),
+    },
+];
+
+/// An indicator of the registry, without its parameters.
+pub struct IndicatorEntry {
+    /// Name written before the parameters, e.g. `SMA` in `SMA(50)`.
+    pub name: &'static str,
+    /// Name in full, in snake case, as the config file may name the indicator.
+    pub long_name: &'static str,
+    /// The parameters in the order they are written, at most `MAX_INDICATOR_PARAMS`.
+    pub params: &'static [IndicatorParam],
+    /// Makes the indicator from the parameters, which are positive.
+    pub factory: fn(&[u16]) -> Box<dyn ComputeIndicator>,
+}
+
+pub struct IndicatorParam {
+    pub name: &'static str,
+    pub default: u16,
+    /// Whether the parameter is a number of bars, scaled along with the time frame.
+    pub is_period: bool,
+}
+
+impl ComputeIndicator for Indicator {
+    fn lookback(&self) -> u16 {
+        self.to_compute().lookback()
+    }
+
+    fn compute(&self, bars: &[Bar]) -> IndicatorOutput {
+        self.to_compute().compute(bars)
+    }
+}
+
+impl Indicator {
+    fn to_compute(self) -> Box<dyn ComputeIndicator> {
+        (self.kind().entry().factory)(self.params())
+    }
+}
+
+pub struct BollingerBands {
+    pub n: u16,
+    pub k: f64,
+}
+
+impl ComputeIndicator for BollingerBands {
+    fn lookback(&self) -> u16 {
+        self.n
+    }
+
+    fn compute(&self, bars: &[Bar]) -> IndicatorOutput {
+        let mut bb = indicators::BollingerBands::new(self.n as u32, self.k).unwrap();
+        let (upper_data, middle_data, lower_data) =
+            bars.iter()
+                .fold((vec![], vec![], vec![]), |mut acc_data, bar| {
+                    let bb_output = bb.next(&data_item(bar));
+                    let timestamp = bar.timestamp_seconds() as f64;
+                    acc_data.0.push((timestamp, bb_output.upper));
+                    acc_data.1.push((timestamp, bb_output.average));
+                    acc_data.2.push((timestamp, bb_output.lower));
+                    acc_data
+                });
+
+        IndicatorOutput {
+            lines: vec![
+                IndicatorLine {
+                    name: "BB Upper".to_owned(),
+                    role: IndicatorLineRole::Band,
+                    data: upper_data,
+                },
+                IndicatorLine {
+                    name: "BB Lower".to_owned(),
+                    role: IndicatorLineRole::Band,
+                    data: lower_data,
+                },
+                IndicatorLine {
+                    name: "BB Middle".to_owned(),
+                    role: IndicatorLineRole::Main,
+                    data: middle_data,
+                },
+            ],
+        }
+    }
+}
+
+pub struct ExponentialMovingAverage {
+    pub n: u16,
+}
+
+impl ComputeIndicator for ExponentialMovingAverage {
+    fn lookback(&self) -> u16 {
+        self.n
+    }
+
+    fn compute(&self, bars: &[Bar]) -> IndicatorOutput {
+        let mut ema = indicators::ExponentialMovingAverage::new(self.n as u32).unwrap();
+        let data = bars
+            .iter()
+            .map(|bar| (bar.timestamp_seconds() as f64, ema.next(&data_item(bar))))
+            .collect();
+
+        IndicatorOutput {
+            lines: vec![IndicatorLine {
+                name: "EMA".to_owned(),
+                role: IndicatorLineRole::Main,
+                data,
+            }],
+        }
+    }
+}
+
+pub struct SimpleMovingAverage {
+    pub n: u16,
+}
+
+impl ComputeIndicator for SimpleMovingAverage {
+    fn lookback(&self) -> u16 {
+        self.n
+    }
+
+    fn compute(&self, bars: &[Bar]) -> IndicatorOutput {
+        let mut sma = indicators::SimpleMovingAverage::new(self.n as u32).unwrap();
+        let data = bars
+            .iter()
+            .map(|bar| (bar.timestamp_seconds() as f64, sma.next(&data_item(bar))))
+            .collect();
+
+        IndicatorOutput {
+            lines: vec![IndicatorLine {
+                name: "SMA".to_owned(),
+                role: IndicatorLineRole::Main,
+                data,
+            }],
+        }
+    }
+}
+
+fn data_item(bar: &Bar) -> DataItem {
+    let data_item = DataItem::builder()
+        .open(bar.open)
+        .high(bar.high)
+        .low(bar.low)
+        .close(bar.close);
+    let data_item = if let Some(volume) = bar.volume {
+        data_item.volume(volume as f64)
+    } else {
+        data_item
+    };
+    data_item.build().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::stock_fixture;
+
+    #[test]
+    fn computes_a_line_per_bar() {
+        let bars: Vec<_> = stock_fixture().bars.into_iter().collect();
+        let indicator: Indicator = "SMA(2)".parse().unwrap();
+        let output = indicator.compute(&bars);
+
+        assert_eq!(indicator.lookback(), 2);
+        assert_eq!(output.lines.len(), 1);
+        assert_eq!(output.lines[0].data.len(), bars.len());
+        let (timestamp, sma) = output.lines[0].data[bars.len() - 1];
+        assert_eq!(timestamp, bars[bars.len() - 1].timestamp_seconds() as f64);
+        assert_eq!(
+            sma,
+            (bars[bars.len() - 2].close + bars[bars.len() - 1].close) / 2f64
+        );
+    }
+}
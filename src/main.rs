@@ -1,63 +1,237 @@
 use crate::{
-    app::{App, Indicator, TimeFrame, UiState, UiTarget},
-    event::{ChartEvent, InputEvent, OverlayEvent, OverlayState, SelectMenuEvent, TextFieldEvent},
+    app::{
+        App, ChartAnchor, ChartSeries, ChartStyle, ChartTool, ChartToolState, ChartViewport,
+        CrossoverPeriods, FooterMessage, FrameRateCounter, History, Indicator, LineStyle, Overlay,
+        PriceDirection, PriceFlash, ScrollPanelState, TimeFrame, UiState, UiTarget,
+    },
+    config::Config,
+    data_source::{
+        AlphaVantageDataSource, CsvDataSource, DataSource, DataSourceKind, ProfilingDataSource,
+        RateLimitingDataSource, YahooDataSource, DEFAULT_CSV_DATE_FORMAT,
+    },
+    event::{
+        ChartEvent, IndicatorEvent, InputEvent, OverlayEvent, OverlayState, ScrollPanelEvent,
+        SelectMenuEvent, SelectionEvent, TextFieldEvent,
+    },
+    format::{self, DateFormat, NumberFormat},
+    raster::Raster,
     reactive::StreamExt as ReactiveStreamExt,
+    script::Script,
+    stats::PriceChange,
     stock::Stock,
+    theme::Theme,
     widgets::{SelectMenuState, TextFieldState},
 };
+use anyhow::Context;
 use argh::FromArgs;
 use async_std::stream::{self, StreamExt};
 use bimap::BiMap;
+use chrono::{TimeZone, Utc};
 use crossterm::{
     cursor,
-    event::{Event, EventStream, KeyCode, KeyEvent},
+    event::{Event, EventStream, KeyCode, KeyEvent, KeyModifiers},
     execute, terminal,
 };
-use im::hashmap;
-use log::debug;
+use im::{hashmap, HashMap, HashSet};
+use log::{debug, info, warn};
 use reactive_rs::{Broadcast, Stream};
 use simplelog::{Config as LoggerConfig, LevelFilter, WriteLogger};
 use std::{
     cell::RefCell,
+    cmp,
     collections::VecDeque,
     fs::File,
-    io::{self, Write},
+    io::{self, BufRead, Write},
     panic,
+    path::PathBuf,
+    pin::Pin,
     rc::Rc,
     sync::atomic::{self, AtomicBool},
     time,
 };
 use strum::IntoEnumIterator;
-use tui::{backend::CrosstermBackend, layout::Rect, Terminal};
+use tui::{
+    backend::{CrosstermBackend, TestBackend},
+    layout::Rect,
+    Terminal,
+};
+use yahoo_finance::Timestamped;
 
 mod app;
+mod browser;
+mod clipboard;
+mod config;
+mod data_source;
 mod event;
+mod format;
+mod indicator;
+mod market;
+mod raster;
 mod reactive;
+mod script;
+mod stats;
 mod stock;
+#[cfg(test)]
+mod testing;
+mod theme;
 mod ui;
 mod widgets;
 
+const DEFAULT_FETCH_TIMEOUT: u64 = 10;
+const DEFAULT_FRAME_TIME_WINDOW: usize = 5;
+const DEFAULT_MAX_BARS: usize = 2_000;
+const DEFAULT_RATE_LIMIT_BACKOFF: u64 = 30;
 const DEFAULT_SYMBOL: &str = "TSLA";
+const OUTPUT_IMAGE_HEIGHT: u16 = 40;
+const OUTPUT_IMAGE_WIDTH: u16 = 120;
+const SELECTION_HISTORY_CAPACITY: usize = 50;
 const TICK_RATE: u64 = 100;
 
 /// Stocks dashboard
 #[derive(Debug, FromArgs)]
 struct Args {
+    /// API key for the data source, required by alphavantage
+    #[argh(option)]
+    api_key: Option<String>,
+    /// what the right edge of the chart is pinned to: last-bar, now (default: last-bar)
+    #[argh(option, default = "ChartAnchor::default()")]
+    chart_anchor: ChartAnchor,
+    /// marker for drawing the chart lines: braille, block, dot
+    #[argh(option, default = "ChartStyle::default()")]
+    chart_style: ChartStyle,
+    /// always show the header on a single line, as on terminals narrower than 80 columns
+    #[argh(switch)]
+    compact_header: bool,
+    /// path to a TOML config file
+    #[argh(option)]
+    config: Option<PathBuf>,
+    /// periods of the fast and slow simple moving averages to mark golden and death crosses of,
+    /// e.g. 50,200
+    #[argh(option)]
+    crossover: Option<CrossoverPeriods>,
+    /// round the price of the crosshair to multiples of this step, e.g. 0.5
+    #[argh(option)]
+    crosshair_step: Option<f64>,
+    /// date format of the CSV data source, in strftime syntax
+    #[argh(option, default = "DEFAULT_CSV_DATE_FORMAT.to_owned()")]
+    csv_date_format: String,
+    /// format of the displayed dates, in strftime syntax, e.g. %d/%m/%Y (default: %Y-%m-%d)
+    #[argh(option)]
+    date_format: Option<String>,
     /// debug draw
     #[argh(switch)]
     debug_draw: bool,
+    /// draw only every n ticks of 100 ms, for less CPU use on slow terminals (default: 1)
+    #[argh(option, default = "1")]
+    draw_every: u32,
+    /// seconds after which a request to the data source is abandoned
+    #[argh(option, default = "DEFAULT_FETCH_TIMEOUT")]
+    fetch_timeout: u64,
+    /// switch to the shortest time frame covering the history of a symbol with much less history
+    /// than the time frame, once per symbol
+    #[argh(switch)]
+    fit_time_frame: bool,
+    /// number of seconds the frame time drawn with --debug-draw is averaged over
+    #[argh(option, default = "DEFAULT_FRAME_TIME_WINDOW")]
+    frame_time_window: usize,
+    /// show only the chart, hiding the header and footer
+    #[argh(switch)]
+    fullscreen_chart: bool,
+    /// mark gaps of at least this percent between the open of a bar and the previous close
+    #[argh(option)]
+    gap_threshold: Option<f64>,
+    /// draw gridlines dividing the price chart into this many parts each way
+    #[argh(option)]
+    gridlines: Option<u16>,
     /// indicator for technical analysis
     #[argh(option, short = 'i')]
     indicator: Option<Indicator>,
+    /// locale for formatting numbers, e.g. de-DE (default: no digit grouping and a . decimal mark)
+    #[argh(option)]
+    locale: Option<NumberFormat>,
+    /// how the price line connects the bars: line, smooth, markers
+    #[argh(option, default = "LineStyle::default()")]
+    line_style: LineStyle,
     /// path to log file
     #[argh(option)]
     log_file: Option<String>,
-    /// stock symbol
-    #[argh(option, short = 's', default = "DEFAULT_SYMBOL.to_owned()")]
-    symbol: String,
+    /// least severe messages written to the log file: error, warn, info, debug, trace
+    #[argh(option, default = "LevelFilter::Info")]
+    log_level: LevelFilter,
+    /// maximum number of bars to draw, beyond which they are rolled up into weekly or monthly bars
+    #[argh(option, default = "DEFAULT_MAX_BARS")]
+    max_bars: usize,
+    /// most date labels on the x-axis, fewer where they would overprint
+    #[argh(option)]
+    max_x_labels: Option<usize>,
+    /// do not ring the terminal bell on keys that do nothing
+    #[argh(switch)]
+    no_beep: bool,
+    /// do not show the splash screen while the first symbol is loading
+    #[argh(switch)]
+    no_splash: bool,
+    /// overlay to open on launch: symbol, time-frame, indicator, news
+    #[argh(option)]
+    open: Option<Overlay>,
+    /// render the chart to a PNG image at this path and exit, without starting the dashboard
+    #[argh(option)]
+    output_image: Option<PathBuf>,
+    /// path to a CSV file, or a directory of <SYMBOL>.csv files, for the csv data source
+    #[argh(option)]
+    path: Option<PathBuf>,
+    /// draw a dashed line at the previous close, the reference for the change on the day
+    #[argh(switch)]
+    previous_close: bool,
+    /// log the time taken by the startup, each data fetch and each draw, requires --log-file
+    #[argh(switch)]
+    profile: bool,
+    /// seconds to stop fetching for once the data source rate-limits, doubled on every rate limit
+    /// in a row
+    #[argh(option, default = "DEFAULT_RATE_LIMIT_BACKOFF")]
+    rate_limit_backoff: u64,
+    /// stock symbol to compare against, plotting the price ratio in a separate pane
+    #[argh(option, short = 'r')]
+    relative_strength: Option<String>,
+    /// path to a script of input events to replay instead of reading the terminal, for
+    /// reproducible demos, quitting once it runs out: key <key>, type <text>, click <x> <y>,
+    /// wait <ms>
+    #[argh(option)]
+    script: Option<PathBuf>,
+    /// data source for stock profiles and historical prices: yahoo, alphavantage, csv
+    #[argh(option, default = "DataSourceKind::default()")]
+    source: DataSourceKind,
+    /// time frame of the chart shown beside the main one in the split view, toggled with m
+    #[argh(option, default = "TimeFrame::OneYear")]
+    split_time_frame: TimeFrame,
+    /// warn in the footer when the next bar is overdue by this many minutes while the market is
+    /// open, as when the data source returns stale bars
+    #[argh(option)]
+    stale_after: Option<u64>,
+    /// print the change of the stocks from the previous close and exit, without starting the
+    /// dashboard
+    #[argh(switch)]
+    summary: bool,
+    /// open in a summary of the statistics and a sparkline, expanded to the chart with Enter
+    #[argh(switch)]
+    summary_view: bool,
+    /// stock symbol, or comma separated symbols with --summary (default: TSLA, or one symbol per
+    /// line read from stdin with --summary)
+    #[argh(option, short = 's')]
+    symbol: Option<String>,
+    /// preset of the colors, overridden by the config file: default, colorblind (blue / orange
+    /// instead of green / red)
+    #[argh(option, default = "Theme::default()")]
+    theme: Theme,
     /// time frame for historical prices
     #[argh(option, short = 't', default = "TimeFrame::default()")]
     time_frame: TimeFrame,
+    /// project cones of one and two standard deviations ahead of the last bar, from the volatility
+    /// of the returns over this many bars
+    #[argh(option)]
+    volatility_cone: Option<u16>,
+    /// period of the exponential moving average drawn over the volume bars of the volume pane
+    #[argh(option)]
+    volume_ema: Option<u16>,
 }
 
 fn setup_terminal() {
@@ -122,6 +296,7 @@ fn setup_panic_hook() {
     panic::set_hook(Box::new(|panic_info| {
         cleanup_terminal();
         better_panic::Settings::auto().create_panic_handler()(panic_info);
+        log::logger().flush();
     }));
 }
 
@@ -129,16 +304,228 @@ fn setup_panic_hook() {
 async fn main() -> anyhow::Result<()> {
     better_panic::install();
 
+    let startup_instant = time::Instant::now();
+
     let args: Args = argh::from_env();
 
+    anyhow::ensure!(
+        !args.profile || args.log_file.is_some(),
+        "--profile requires --log-file"
+    );
+
     if let Some(log_file) = args.log_file {
         WriteLogger::init(
-            LevelFilter::Debug,
+            args.log_level,
             LoggerConfig::default(),
             File::create(log_file)?,
         )?;
     }
 
+    let config = args
+        .config
+        .as_deref()
+        .map(Config::load)
+        .transpose()?
+        .unwrap_or_default();
+    let script = args
+        .script
+        .as_deref()
+        .map(|path| Script::load(path, TICK_RATE))
+        .transpose()?;
+    let color_scheme = args.theme.color_scheme().merge(&config.colors);
+    let date_format = args
+        .date_format
+        .as_deref()
+        .map(DateFormat::new)
+        .unwrap_or_default();
+
+    anyhow::ensure!(
+        args.crosshair_step
+            .map_or(true, |step| step.is_finite() && step > 0f64),
+        "--crosshair-step must be greater than 0"
+    );
+    anyhow::ensure!(
+        args.stale_after.map_or(true, |minutes| minutes > 0),
+        "--stale-after must be at least 1 minute"
+    );
+    anyhow::ensure!(
+        args.volatility_cone.map_or(true, |lookback| lookback > 1),
+        "--volatility-cone must look back over at least 2 bars"
+    );
+    anyhow::ensure!(
+        args.volume_ema.map_or(true, |period| period > 0),
+        "--volume-ema must be a positive period"
+    );
+
+    let fetch_timeout = time::Duration::from_secs(args.fetch_timeout);
+    let data_source: Rc<dyn DataSource> = match args.source {
+        DataSourceKind::AlphaVantage => Rc::new(AlphaVantageDataSource::new(
+            args.api_key
+                .context("--api-key is required for the alphavantage data source")?,
+            fetch_timeout,
+        )),
+        DataSourceKind::Csv => Rc::new(CsvDataSource::new(
+            args.path
+                .context("--path is required for the csv data source")?,
+            args.csv_date_format,
+        )),
+        DataSourceKind::Yahoo => Rc::new(YahooDataSource::new(fetch_timeout)),
+    };
+    let data_source: Rc<dyn DataSource> = Rc::new(RateLimitingDataSource::new(
+        data_source,
+        time::Duration::from_secs(args.rate_limit_backoff),
+    ));
+    let data_source: Rc<dyn DataSource> = if args.profile {
+        Rc::new(ProfilingDataSource::new(data_source))
+    } else {
+        data_source
+    };
+
+    if args.summary {
+        // a week has at least two trading days to take the daily change from, whatever the time
+        // frame
+        let date_range = TimeFrame::OneWeek.now_date_range();
+        let format_signed = |value: f64, formatted: String| {
+            if value > 0f64 {
+                format!("+{}", formatted)
+            } else {
+                formatted
+            }
+        };
+
+        let symbols = match &args.symbol {
+            Some(symbol) => symbol.split(',').map(str::to_owned).collect(),
+            None => io::stdin()
+                .lock()
+                .lines()
+                .collect::<Result<Vec<_>, _>>()
+                .context("cannot read symbols from stdin")?,
+        };
+
+        for symbol in symbols
+            .iter()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| config.symbols.resolve(s))
+        {
+            let number_format = args
+                .locale
+                .unwrap_or_default()
+                .with_price_precision(config.price_precision.get(&symbol));
+            let stock = match stock::fetch_stock(
+                data_source.as_ref(),
+                &symbol,
+                date_range.as_ref(),
+                None,
+                None,
+            ) {
+                Ok(stock) => stock,
+                Err(err) => {
+                    eprintln!("{}: {}", symbol, err);
+                    continue;
+                }
+            };
+
+            match PriceChange::daily(&stock) {
+                Some(price_change) => println!(
+                    "{}\t{}\t{}\t{}\t{}%\t{}",
+                    symbol,
+                    stock.name().unwrap_or(""),
+                    number_format.format_price(price_change.last),
+                    format_signed(
+                        price_change.change,
+                        number_format.format_price(price_change.change)
+                    ),
+                    format_signed(
+                        price_change.change_percent,
+                        number_format.format_decimal(price_change.change_percent, 2)
+                    ),
+                    price_change
+                        .volume
+                        .map_or_else(String::new, |volume| number_format.format_si(volume as f64)),
+                ),
+                None => eprintln!("{}: no previous close", symbol),
+            }
+        }
+
+        return Ok(());
+    }
+
+    let symbol = config
+        .symbols
+        .resolve(&args.symbol.unwrap_or_else(|| DEFAULT_SYMBOL.to_owned()));
+    let relative_strength_symbol = args
+        .relative_strength
+        .as_deref()
+        .map(|relative_strength_symbol| config.symbols.resolve(relative_strength_symbol));
+
+    if let Some(output_image) = args.output_image {
+        let date_range = args.time_frame.now_date_range();
+        let stock = stock::fetch_stock(
+            data_source.as_ref(),
+            &symbol,
+            date_range.as_ref(),
+            args.indicator,
+            args.crossover,
+        )?
+        .downsampled(args.max_bars);
+        let relative_strength_stock = relative_strength_symbol
+            .as_ref()
+            .map(|relative_strength_symbol| {
+                stock::fetch_stock(
+                    data_source.as_ref(),
+                    relative_strength_symbol,
+                    date_range.as_ref(),
+                    None,
+                    None,
+                )
+                .map(|stock| stock.downsampled(args.max_bars))
+            })
+            .transpose()?;
+        let app = App {
+            relative_strength_stock,
+            split_stock: None,
+            stock,
+            ui_state: UiState {
+                chart_anchor: args.chart_anchor,
+                chart_style: args.chart_style,
+                color_scheme,
+                compact_header: args.compact_header,
+                crosshair_step: args.crosshair_step,
+                crossover_periods: args.crossover,
+                date_format,
+                date_range,
+                fullscreen_chart: args.fullscreen_chart,
+                gap_threshold: args.gap_threshold,
+                gridlines: args.gridlines,
+                indicator: args.indicator,
+                indicator_precision: config.indicators.precision,
+                line_style: args.line_style,
+                max_x_labels: args.max_x_labels,
+                number_format: args
+                    .locale
+                    .unwrap_or_default()
+                    .with_price_precision(config.price_precision.get(&symbol)),
+                pane_split: config.layout.pane_split(),
+                previous_close: args.previous_close,
+                split_time_frame: args.split_time_frame,
+                time_frame: args.time_frame,
+                volatility_cone: args.volatility_cone,
+                volume_ema_period: args.volume_ema,
+                ..UiState::default()
+            },
+        };
+
+        let mut terminal =
+            Terminal::new(TestBackend::new(OUTPUT_IMAGE_WIDTH, OUTPUT_IMAGE_HEIGHT))?;
+        terminal.draw(|mut f| {
+            ui::draw(&mut f, &app).expect("draw failed");
+        })?;
+        Raster::from_buffer(terminal.backend().buffer()).write_png(&output_image)?;
+
+        return Ok(());
+    }
+
     let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
@@ -146,6 +533,8 @@ async fn main() -> anyhow::Result<()> {
     setup_terminal();
 
     let should_quit = AtomicBool::new(false);
+    let beep = !args.no_beep;
+    let max_bars = args.max_bars;
 
     let ui_target_areas: Broadcast<(), (UiTarget, Option<Rect>)> = Broadcast::new();
 
@@ -184,15 +573,21 @@ async fn main() -> anyhow::Result<()> {
 
     let hotkey_overlay_map = {
         let mut bimap = BiMap::new();
+        bimap.insert(KeyCode::Char('E'), UiTarget::EntryPriceField);
         bimap.insert(KeyCode::Char('i'), UiTarget::IndicatorMenu);
+        bimap.insert(KeyCode::Char('n'), UiTarget::NewsPanel);
+        bimap.insert(KeyCode::Char('p'), UiTarget::IndicatorParamsField);
         bimap.insert(KeyCode::Char('s'), UiTarget::StockSymbolField);
         bimap.insert(KeyCode::Char('t'), UiTarget::TimeFrameMenu);
         bimap
     };
 
     let associated_overlay_map = hashmap! {
+        UiTarget::EntryPriceField => UiTarget::EntryPriceField,
         UiTarget::IndicatorBox => UiTarget::IndicatorMenu,
         UiTarget::IndicatorMenu => UiTarget::IndicatorMenu,
+        UiTarget::IndicatorParamsField => UiTarget::IndicatorParamsField,
+        UiTarget::NewsPanel => UiTarget::NewsPanel,
         UiTarget::StockNameButton => UiTarget::StockSymbolField,
         UiTarget::StockSymbolButton => UiTarget::StockSymbolField,
         UiTarget::StockSymbolField => UiTarget::StockSymbolField,
@@ -215,7 +610,56 @@ async fn main() -> anyhow::Result<()> {
         .switch()
         .broadcast();
 
-    let chart_events = event::to_chart_events(non_overlay_user_input_events.clone()).broadcast();
+    let chart_viewports: Broadcast<(), ChartViewport> = Broadcast::new();
+
+    let minimap_viewports: Broadcast<(), Option<ChartViewport>> = Broadcast::new();
+
+    let chart_tool_hotkey_map = hashmap! {
+        KeyCode::Char('F') => ChartTool::FibonacciRetracement,
+        KeyCode::Char('L') => ChartTool::Trendline,
+        KeyCode::Char('M') => ChartTool::Measure,
+    };
+
+    let chart_tool_states = event::to_chart_tool_states(
+        non_overlay_user_input_events.clone(),
+        chart_viewports.clone(),
+        chart_tool_hotkey_map.clone(),
+    )
+    .broadcast();
+
+    let chart_events = event::to_chart_events(
+        non_overlay_user_input_events.clone(),
+        ui_target_areas.clone(),
+    )
+    .merge(event::to_minimap_chart_events(
+        non_overlay_user_input_events.clone(),
+        minimap_viewports.clone(),
+    ))
+    .broadcast();
+
+    // aliases are resolved up front, so that the bar can highlight the symbol shown
+    let favorites: Vec<String> = config
+        .favorites
+        .iter()
+        .map(|favorite| config.symbols.resolve(favorite))
+        .collect();
+    // the order is saved with the favorites as written in the config file, aliases and all
+    let favorite_entries: Vec<_> = config
+        .favorites
+        .iter()
+        .cloned()
+        .zip(favorites.iter().cloned())
+        .collect();
+    let favorites_config_path = args.config.clone().filter(|_| config.save_favorites_order);
+
+    let favorite_lists: Broadcast<(), Vec<String>> = Broadcast::new();
+
+    let favorite_selections = event::to_favorite_selections(
+        non_overlay_user_input_events.clone(),
+        ui_target_areas.clone(),
+        favorite_lists.clone(),
+    )
+    .broadcast();
 
     let init_stock_symbol_field_state = TextFieldState::default();
 
@@ -244,18 +688,101 @@ async fn main() -> anyhow::Result<()> {
     )
     .broadcast();
 
+    let init_entry_price_field_state = TextFieldState::default();
+
+    // a price, or nothing to clear the entry
+    let entry_price_text_field_events = event::to_text_field_events(
+        grouped_user_input_events
+            .clone()
+            .filter(|grouped| grouped.key == Some(UiTarget::EntryPriceField))
+            .switch(),
+        init_entry_price_field_state.clone(),
+        grouped_overlay_states
+            .clone()
+            .filter(|grouped| grouped.key == UiTarget::EntryPriceField)
+            .switch(),
+        hotkey_overlay_map
+            .get_by_right(&UiTarget::EntryPriceField)
+            .copied()
+            .unwrap(),
+        ui_target_areas.clone(),
+        UiTarget::EntryPriceField,
+        hashmap! {
+            None => TextFieldEvent::Deactivate,
+        },
+        |v| {
+            v.chars()
+                .filter(|c| c.is_ascii_digit() || *c == '.')
+                .collect()
+        },
+    )
+    .broadcast();
+
+    let init_indicator_params_field_state = TextFieldState::default();
+
+    // the parameters of the current indicator, comma separated, e.g. 20, 2
+    let indicator_params_text_field_events = event::to_text_field_events(
+        grouped_user_input_events
+            .clone()
+            .filter(|grouped| grouped.key == Some(UiTarget::IndicatorParamsField))
+            .switch(),
+        init_indicator_params_field_state.clone(),
+        grouped_overlay_states
+            .clone()
+            .filter(|grouped| grouped.key == UiTarget::IndicatorParamsField)
+            .switch(),
+        hotkey_overlay_map
+            .get_by_right(&UiTarget::IndicatorParamsField)
+            .copied()
+            .unwrap(),
+        ui_target_areas.clone(),
+        UiTarget::IndicatorParamsField,
+        hashmap! {
+            None => TextFieldEvent::Deactivate,
+        },
+        |v| {
+            v.chars()
+                .filter(|c| c.is_ascii_digit() || *c == ',' || *c == ' ')
+                .collect()
+        },
+    )
+    .broadcast();
+
     let init_time_frame_menu_state = {
         let mut select_menu_state = SelectMenuState::new(TimeFrame::iter());
         select_menu_state.select(Some(args.time_frame))?;
         select_menu_state
     };
 
+    let time_frame_hotkey_map: HashMap<_, _> = TimeFrame::iter()
+        .map(|time_frame| (KeyCode::Char(time_frame.hotkey()), time_frame))
+        .collect();
+
+    // The time frames and indicators selected otherwise than through their menus, e.g. by undoing,
+    // sent to the menus on the next tick, as the menus select them too.
+    let time_frame_menu_selections: Broadcast<(), Option<TimeFrame>> = Broadcast::new();
+    let time_frame_menu_selection_queue = Rc::new(RefCell::new(None));
+    let indicator_menu_selections: Broadcast<(), Option<Indicator>> = Broadcast::new();
+    let indicator_menu_selection_queue = Rc::new(RefCell::new(None));
+
     let time_frame_select_menu_events = event::to_select_menu_events(
         grouped_user_input_events
             .clone()
             .filter(|grouped| grouped.key == Some(UiTarget::TimeFrameMenu))
-            .switch(),
+            .switch()
+            .merge(non_overlay_user_input_events.clone().filter({
+                let time_frame_hotkey_map = time_frame_hotkey_map.clone();
+                // with Alt, the digits toggle the series of the chart legend instead
+                move |ev| match ev {
+                    InputEvent::Key(KeyEvent { code, modifiers }) => {
+                        !modifiers.contains(KeyModifiers::ALT)
+                            && time_frame_hotkey_map.contains_key(code)
+                    }
+                    _ => false,
+                }
+            })),
         init_time_frame_menu_state.clone(),
+        time_frame_menu_selections.clone(),
         grouped_overlay_states
             .clone()
             .filter(|grouped| grouped.key == UiTarget::TimeFrameMenu)
@@ -264,6 +791,7 @@ async fn main() -> anyhow::Result<()> {
             .get_by_right(&UiTarget::TimeFrameMenu)
             .copied()
             .unwrap(),
+        time_frame_hotkey_map.clone(),
         ui_target_areas.clone(),
         UiTarget::TimeFrameMenu,
         hashmap! {
@@ -274,7 +802,16 @@ async fn main() -> anyhow::Result<()> {
     .broadcast();
 
     let init_indicator_menu_state = {
-        let mut select_menu_state = SelectMenuState::new(Indicator::iter());
+        // the indicator given on the command line takes the place of the default of its kind
+        let indicators = config
+            .indicators
+            .default_indicators()
+            .into_iter()
+            .map(|indicator| match args.indicator {
+                Some(args_indicator) if args_indicator.kind() == indicator.kind() => args_indicator,
+                _ => indicator,
+            });
+        let mut select_menu_state = SelectMenuState::new(indicators);
         select_menu_state.allow_empty_selection = true;
         select_menu_state.select(args.indicator)?;
         select_menu_state
@@ -286,6 +823,7 @@ async fn main() -> anyhow::Result<()> {
             .filter(|grouped| grouped.key == Some(UiTarget::IndicatorMenu))
             .switch(),
         init_indicator_menu_state.clone(),
+        indicator_menu_selections.clone(),
         grouped_overlay_states
             .clone()
             .filter(|grouped| grouped.key == UiTarget::IndicatorMenu)
@@ -294,6 +832,7 @@ async fn main() -> anyhow::Result<()> {
             .get_by_right(&UiTarget::IndicatorMenu)
             .copied()
             .unwrap(),
+        hashmap! {},
         ui_target_areas.clone(),
         UiTarget::IndicatorMenu,
         hashmap! {
@@ -303,6 +842,27 @@ async fn main() -> anyhow::Result<()> {
     )
     .broadcast();
 
+    let news_headline_counts: Broadcast<(), usize> = Broadcast::new();
+
+    let news_panel_events = event::to_scroll_panel_events(
+        grouped_user_input_events
+            .clone()
+            .filter(|grouped| grouped.key == Some(UiTarget::NewsPanel))
+            .switch(),
+        grouped_overlay_states
+            .clone()
+            .filter(|grouped| grouped.key == UiTarget::NewsPanel)
+            .switch(),
+        hotkey_overlay_map
+            .get_by_right(&UiTarget::NewsPanel)
+            .copied()
+            .unwrap(),
+        ui_target_areas.clone(),
+        UiTarget::NewsPanel,
+        news_headline_counts.clone(),
+    )
+    .broadcast();
+
     let overlay_events = stock_symbol_text_field_events
         .clone()
         .map(|(ev, ..)| {
@@ -323,6 +883,23 @@ async fn main() -> anyhow::Result<()> {
                 OverlayEvent::SelectMenu(ev.clone()),
             )
         }))
+        .merge(indicator_params_text_field_events.clone().map(|(ev, ..)| {
+            (
+                UiTarget::IndicatorParamsField,
+                OverlayEvent::TextField(ev.clone()),
+            )
+        }))
+        .merge(entry_price_text_field_events.clone().map(|(ev, ..)| {
+            (
+                UiTarget::EntryPriceField,
+                OverlayEvent::TextField(ev.clone()),
+            )
+        }))
+        .merge(
+            news_panel_events
+                .clone()
+                .map(|(ev, ..)| (UiTarget::NewsPanel, OverlayEvent::ScrollPanel(*ev))),
+        )
         .inspect(|(ui_target, ev)| {
             debug!("overlay event: {:?}", (ui_target, ev));
         })
@@ -330,66 +907,276 @@ async fn main() -> anyhow::Result<()> {
 
     event::queue_overlay_states_for_next_tick(overlay_events.clone(), overlay_state_queue.clone());
 
-    let stock_symbols = stock_symbol_text_field_events
+    // Time frames fitting the history of young symbols, found on a draw and sent on the next tick,
+    // as they change the stock being drawn.
+    let fitted_time_frames: Broadcast<(), TimeFrame> = Broadcast::new();
+    let fitted_time_frame_queue = Rc::new(RefCell::new(None));
+
+    let selections = stock_symbol_text_field_events
         .clone()
-        .fold(args.symbol.clone(), |acc_symbol, (ev, ..)| {
-            if let TextFieldEvent::Accept(symbol) = ev {
-                symbol.clone()
-            } else {
-                acc_symbol.clone()
-            }
+        .filter_map(|(ev, ..)| match ev {
+            TextFieldEvent::Accept(symbol) => Some(SelectionEvent::Symbol(symbol.clone())),
+            _ => None,
+        })
+        .merge(
+            favorite_selections
+                .clone()
+                .map(|symbol| SelectionEvent::Symbol(symbol.clone())),
+        )
+        .merge(
+            time_frame_select_menu_events
+                .clone()
+                .filter_map(|(ev, ..)| match ev {
+                    SelectMenuEvent::Accept(Some(time_frame)) => match time_frame.parse() {
+                        Ok(time_frame) => Some(SelectionEvent::TimeFrame(time_frame)),
+                        Err(err) => {
+                            warn!("ignoring invalid time frame {:?}: {}", time_frame, err);
+                            None
+                        }
+                    },
+                    SelectMenuEvent::Accept(None) => {
+                        warn!("ignoring empty time frame selection");
+                        None
+                    }
+                    _ => None,
+                }),
+        )
+        .merge(
+            fitted_time_frames
+                .clone()
+                .map(|time_frame| SelectionEvent::TimeFrame(*time_frame)),
+        )
+        .merge(
+            non_overlay_user_input_events
+                .clone()
+                .filter_map(|ev| match ev {
+                    InputEvent::Key(KeyEvent {
+                        code: KeyCode::Char('z'),
+                        modifiers,
+                    }) if modifiers.contains(KeyModifiers::CONTROL) => Some(SelectionEvent::Undo),
+                    InputEvent::Key(KeyEvent {
+                        code: KeyCode::Char('y'),
+                        modifiers,
+                    }) if modifiers.contains(KeyModifiers::CONTROL) => Some(SelectionEvent::Redo),
+                    _ => None,
+                }),
+        )
+        .fold(
+            History::new(
+                (symbol.clone(), args.time_frame),
+                SELECTION_HISTORY_CAPACITY,
+            ),
+            |acc_history, ev| match ev {
+                SelectionEvent::Symbol(symbol) => {
+                    let (_, time_frame) = acc_history.present();
+                    acc_history.push((symbol.clone(), *time_frame))
+                }
+                SelectionEvent::TimeFrame(time_frame) => {
+                    let (symbol, _) = acc_history.present();
+                    acc_history.push((symbol.clone(), *time_frame))
+                }
+                SelectionEvent::Undo => acc_history.undo(),
+                SelectionEvent::Redo => acc_history.redo(),
+            },
+        )
+        .map(|history| history.present().clone())
+        .broadcast();
+
+    let stock_symbols = selections
+        .clone()
+        .map({
+            let symbols_config = config.symbols.clone();
+            move |(symbol, _)| symbols_config.resolve(symbol)
         })
         .distinct_until_changed()
         .broadcast();
 
-    let time_frames = time_frame_select_menu_events
+    // the shown symbol is moved back and ahead in the favorites bar with P and N
+    let favorite_orders = non_overlay_user_input_events
         .clone()
-        .fold(args.time_frame, |acc_time_frame, (ev, ..)| {
-            if let SelectMenuEvent::Accept(time_frame) = ev {
-                time_frame.as_ref().unwrap().parse().unwrap()
-            } else {
-                *acc_time_frame
-            }
+        .with_latest_from(stock_symbols.clone(), |(ev, stock_symbol)| {
+            (*ev, stock_symbol.clone())
         })
+        .fold(
+            favorites.clone(),
+            |acc_favorites, (ev, stock_symbol)| match ev {
+                InputEvent::Key(KeyEvent {
+                    code: KeyCode::Char('N'),
+                    ..
+                }) => app::move_favorite(acc_favorites, stock_symbol, 1),
+                InputEvent::Key(KeyEvent {
+                    code: KeyCode::Char('P'),
+                    ..
+                }) => app::move_favorite(acc_favorites, stock_symbol, -1),
+                _ => acc_favorites.clone(),
+            },
+        )
+        .distinct_until_changed()
+        .broadcast();
+
+    let latest_favorites = Rc::new(RefCell::new(favorites.clone()));
+    favorite_orders.clone().subscribe({
+        let favorite_lists = favorite_lists.clone();
+        let latest_favorites = latest_favorites.clone();
+        move |favorites| {
+            favorite_lists.send(favorites.clone());
+            *latest_favorites.borrow_mut() = favorites.clone();
+        }
+    });
+
+    let time_frames = selections
+        .clone()
+        .map(|(_, time_frame)| *time_frame)
         .distinct_until_changed()
         .inspect(|time_frame| {
             debug!("selected time frame: {:?}", time_frame);
         })
         .broadcast();
 
+    time_frames.clone().subscribe({
+        let time_frame_menu_selection_queue = time_frame_menu_selection_queue.clone();
+        move |time_frame| *time_frame_menu_selection_queue.borrow_mut() = Some(Some(*time_frame))
+    });
+
     let date_ranges = app::to_date_ranges(
         chart_events.clone(),
         stock_symbols.clone(),
-        args.symbol.clone(),
+        symbol.clone(),
         time_frames.clone(),
         args.time_frame,
     )
     .broadcast();
 
-    let indicators = indicator_select_menu_events
+    // The shown indicator and the hidden one, where toggling swaps them, and a selection from the
+    // menu or new parameters are always shown. Neither depends on the symbol, so both carry over
+    // to every chart switched to.
+    let indicator_states = indicator_select_menu_events
         .clone()
-        .fold(args.indicator, |acc_indicator, (ev, ..)| {
-            if let SelectMenuEvent::Accept(indicator) = ev {
-                indicator.as_ref().map(|s| s.parse().unwrap())
-            } else {
-                *acc_indicator
+        .filter_map(|(ev, ..)| match ev {
+            SelectMenuEvent::Accept(Some(indicator)) => match indicator.parse() {
+                Ok(indicator) => Some(IndicatorEvent::Select(Some(indicator))),
+                Err(err) => {
+                    warn!("ignoring invalid indicator {:?}: {}", indicator, err);
+                    None
+                }
+            },
+            SelectMenuEvent::Accept(None) => Some(IndicatorEvent::Select(None)),
+            _ => None,
+        })
+        .merge(
+            non_overlay_user_input_events
+                .clone()
+                .filter_map(|ev| match ev {
+                    InputEvent::Key(KeyEvent {
+                        code: KeyCode::Char('h'),
+                        ..
+                    }) => Some(IndicatorEvent::Toggle),
+                    InputEvent::Key(KeyEvent {
+                        code: KeyCode::Char('R'),
+                        ..
+                    }) => Some(IndicatorEvent::Select(None)),
+                    InputEvent::Key(KeyEvent {
+                        code: KeyCode::Char('{'),
+                        ..
+                    }) => Some(IndicatorEvent::Previous),
+                    InputEvent::Key(KeyEvent {
+                        code: KeyCode::Char('}'),
+                        ..
+                    }) => Some(IndicatorEvent::Next),
+                    _ => None,
+                }),
+        )
+        .merge(
+            indicator_params_text_field_events
+                .clone()
+                .filter_map(|(ev, ..)| match ev {
+                    TextFieldEvent::Accept(params) => Some(IndicatorEvent::Params(params.clone())),
+                    _ => None,
+                }),
+        )
+        .fold((args.indicator, None), {
+            let default_indicators = config.indicators.default_indicators();
+            move |&(acc_indicator, acc_hidden_indicator), ev| match ev {
+                IndicatorEvent::Next => (
+                    app::cycle_indicator(acc_indicator, &default_indicators, 1),
+                    None,
+                ),
+                IndicatorEvent::Previous => (
+                    app::cycle_indicator(acc_indicator, &default_indicators, -1),
+                    None,
+                ),
+                IndicatorEvent::Select(indicator) => (*indicator, None),
+                IndicatorEvent::Toggle => (acc_hidden_indicator, acc_indicator),
+                IndicatorEvent::Params(params) => match acc_indicator.or(acc_hidden_indicator) {
+                    Some(indicator) => match indicator.with_params(params) {
+                        Ok(indicator) => (Some(indicator), None),
+                        Err(err) => {
+                            warn!("ignoring invalid parameters {:?}: {}", params, err);
+                            (acc_indicator, acc_hidden_indicator)
+                        }
+                    },
+                    None => {
+                        warn!("ignoring parameters {:?} without an indicator", params);
+                        (acc_indicator, acc_hidden_indicator)
+                    }
+                },
             }
         })
+        .broadcast();
+
+    let selected_indicators = indicator_states
+        .clone()
+        .map(|(indicator, _)| *indicator)
         .distinct_until_changed()
         .broadcast();
 
-    let stock_profiles = stock::to_stock_profiles(stock_symbols.clone())
-        .map(|stock_profile| Some(stock_profile.clone()))
+    selected_indicators.clone().subscribe({
+        let indicator_menu_selection_queue = indicator_menu_selection_queue.clone();
+        move |indicator| *indicator_menu_selection_queue.borrow_mut() = Some(*indicator)
+    });
+
+    let indicators = app::to_scaled_indicators(
+        selected_indicators.clone(),
+        time_frames.clone(),
+        args.indicator,
+        args.time_frame,
+        config
+            .indicators
+            .period_scales
+            .iter()
+            .map(|(time_frame, scale)| (*time_frame, *scale))
+            .collect(),
+    )
+    .broadcast();
+
+    let hidden_indicators = indicator_states
+        .clone()
+        .map(|(_, hidden_indicator)| *hidden_indicator)
+        .distinct_until_changed()
         .broadcast();
 
+    let stock_profiles =
+        stock::to_stock_profiles(stock_symbols.clone(), data_source.clone()).broadcast();
+
     let stock_bar_sets = stock::to_stock_bar_sets(
         stock_symbols.clone(),
         time_frames.clone(),
         date_ranges.clone(),
         indicators.clone(),
+        args.crossover,
+        data_source.clone(),
     )
     .broadcast();
 
+    let stock_events =
+        stock::to_stock_events(stock_symbols.clone(), data_source.clone()).broadcast();
+
+    let stock_earnings_dates =
+        stock::to_stock_earnings_dates(stock_symbols.clone(), data_source.clone()).broadcast();
+
+    let stock_quotes =
+        stock::to_stock_quotes(stock_symbols.clone(), data_source.clone()).broadcast();
+
     let stocks = stock_symbols
         .clone()
         .combine_latest(stock_profiles.clone(), |(stock_symbol, stock_profile)| {
@@ -398,12 +1185,122 @@ async fn main() -> anyhow::Result<()> {
         .combine_latest(
             stock_bar_sets.clone(),
             |((stock_symbol, stock_profile), stock_bar_set)| Stock {
-                bars: stock_bar_set.clone(),
-                profile: stock_profile.clone(),
+                bars: stock_bar_set.clone().unwrap_or_default(),
+                fetch_error: stock_profile
+                    .as_ref()
+                    .err()
+                    .or_else(|| stock_bar_set.as_ref().err())
+                    .cloned(),
+                profile: stock_profile.as_ref().ok().cloned(),
                 symbol: stock_symbol.clone(),
                 ..Stock::default()
             },
         )
+        .combine_latest(stock_quotes.clone(), |(stock, stock_quote)| Stock {
+            quote: stock_quote.clone(),
+            ..stock.clone()
+        })
+        .combine_latest(
+            stock_earnings_dates.clone(),
+            |(stock, stock_earnings_dates)| Stock {
+                earnings_dates: stock_earnings_dates.clone(),
+                ..stock.clone()
+            },
+        )
+        .combine_latest(stock_events.clone(), move |(stock, stock_events)| {
+            Stock {
+                events: stock_events.clone(),
+                ..stock.clone()
+            }
+            .downsampled(max_bars)
+        })
+        .broadcast();
+
+    let relative_strength_symbols: Broadcast<(), String> = Broadcast::new();
+
+    let relative_strength_bar_sets = stock::to_stock_bar_sets(
+        relative_strength_symbols.clone(),
+        time_frames.clone(),
+        date_ranges.clone(),
+        indicators.clone(),
+        None,
+        data_source.clone(),
+    )
+    .broadcast();
+
+    let relative_strength_stocks = relative_strength_symbols
+        .clone()
+        .combine_latest(
+            relative_strength_bar_sets.clone(),
+            move |(relative_strength_symbol, relative_strength_bar_set)| {
+                Some(
+                    Stock {
+                        bars: relative_strength_bar_set.clone().unwrap_or_default(),
+                        fetch_error: relative_strength_bar_set.as_ref().err().cloned(),
+                        symbol: relative_strength_symbol.clone(),
+                        ..Stock::default()
+                    }
+                    .downsampled(max_bars),
+                )
+            },
+        )
+        .broadcast();
+
+    let split_views = non_overlay_user_input_events
+        .clone()
+        .fold(false, |acc_split_view, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('m'),
+                ..
+            }) => !acc_split_view,
+            _ => *acc_split_view,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    let split_time_frames: Broadcast<(), TimeFrame> = Broadcast::new();
+
+    // the bars at the split time frame are only fetched while the split view is shown
+    let split_bar_sets = stock::to_stock_bar_sets(
+        stock_symbols
+            .clone()
+            .combine_latest(split_views.clone(), |(stock_symbol, split_view)| {
+                (stock_symbol.clone(), *split_view)
+            })
+            .filter_map(|(stock_symbol, split_view)| {
+                if *split_view {
+                    Some(stock_symbol.clone())
+                } else {
+                    None
+                }
+            }),
+        split_time_frames.clone(),
+        split_time_frames
+            .clone()
+            .map(|split_time_frame| split_time_frame.now_date_range()),
+        indicators.clone(),
+        args.crossover,
+        data_source.clone(),
+    )
+    .broadcast();
+
+    let split_stocks = stocks
+        .clone()
+        .combine_latest(split_bar_sets.clone(), move |(stock, split_bar_set)| {
+            Stock {
+                bars: split_bar_set.clone().unwrap_or_default(),
+                fetch_error: split_bar_set.as_ref().err().cloned(),
+                ..stock.clone()
+            }
+            .downsampled(max_bars)
+        })
+        .combine_latest(split_views.clone(), |(split_stock, split_view)| {
+            if *split_view {
+                Some(split_stock.clone())
+            } else {
+                None
+            }
+        })
         .broadcast();
 
     let stock_symbol_field_states = stock_symbol_text_field_events
@@ -411,6 +1308,34 @@ async fn main() -> anyhow::Result<()> {
         .map(|(_, text_field_state)| text_field_state.clone())
         .broadcast();
 
+    let indicator_params_field_states = indicator_params_text_field_events
+        .clone()
+        .map(|(_, text_field_state)| text_field_state.clone())
+        .broadcast();
+
+    let entry_price_field_states = entry_price_text_field_events
+        .clone()
+        .map(|(_, text_field_state)| text_field_state.clone())
+        .broadcast();
+
+    // the entry is of a position in the stock, so it goes with the symbol
+    let entry_prices = entry_price_text_field_events
+        .clone()
+        .filter_map(|(ev, ..)| match ev {
+            TextFieldEvent::Accept(price) if price.is_empty() => Some(None),
+            TextFieldEvent::Accept(price) => match price.parse::<f64>() {
+                Ok(price) if price > 0f64 => Some(Some(price)),
+                _ => {
+                    warn!("ignoring invalid entry price {:?}", price);
+                    None
+                }
+            },
+            _ => None,
+        })
+        .merge(stock_symbols.clone().map(|_| None))
+        .distinct_until_changed()
+        .broadcast();
+
     let time_frame_menu_states = time_frame_select_menu_events
         .clone()
         .map(|(_, select_menu_state)| select_menu_state.clone())
@@ -421,125 +1346,670 @@ async fn main() -> anyhow::Result<()> {
         .map(|(_, select_menu_state)| select_menu_state.clone())
         .broadcast();
 
-    let debug_draws: Broadcast<(), bool> = Broadcast::new();
-
+    let news_panel_states = news_panel_events
+        .clone()
+        .map(|(_, scroll_panel_state)| *scroll_panel_state)
+        .broadcast();
+
+    // the headlines are only fetched while the panel is open
+    let news_headlines = stock::to_stock_news(
+        stock_symbols
+            .clone()
+            .combine_latest(
+                news_panel_states.clone(),
+                |(stock_symbol, news_panel_state)| (stock_symbol.clone(), news_panel_state.active),
+            )
+            .filter_map(|(stock_symbol, active)| {
+                if *active {
+                    Some(stock_symbol.clone())
+                } else {
+                    None
+                }
+            }),
+        data_source.clone(),
+    )
+    .inspect({
+        let news_headline_counts = news_headline_counts.clone();
+        move |news_headlines| news_headline_counts.send(news_headlines.len())
+    })
+    .broadcast();
+
+    let debug_draws: Broadcast<(), bool> = Broadcast::new();
+
+    let fullscreen_charts = non_overlay_user_input_events
+        .clone()
+        .fold(args.fullscreen_chart, |acc_fullscreen_chart, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('f'),
+                ..
+            }) => !acc_fullscreen_chart,
+            _ => *acc_fullscreen_chart,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    let init_pane_split = config.layout.pane_split();
+
+    let pane_splits = non_overlay_user_input_events
+        .clone()
+        .fold(init_pane_split, |acc_pane_split, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('['),
+                ..
+            }) => acc_pane_split.shrink(),
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char(']'),
+                ..
+            }) => acc_pane_split.grow(),
+            _ => *acc_pane_split,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    // a new time frame, or resetting the view, restores its default
+    let adjusted_closes = app::to_adjusted_closes(
+        non_overlay_user_input_events
+            .clone()
+            .filter(|ev| {
+                matches!(
+                    ev,
+                    InputEvent::Key(KeyEvent {
+                        code: KeyCode::Char('a'),
+                        ..
+                    })
+                )
+            })
+            .map(|_| ()),
+        non_overlay_user_input_events
+            .clone()
+            .filter(|ev| {
+                matches!(
+                    ev,
+                    InputEvent::Key(KeyEvent {
+                        code: KeyCode::Char('R'),
+                        ..
+                    })
+                )
+            })
+            .map(|_| ()),
+        time_frames.clone(),
+    )
+    .broadcast();
+
+    let percent_axes = non_overlay_user_input_events
+        .clone()
+        .fold(false, |acc_percent_axis, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('%'),
+                ..
+            }) => !acc_percent_axis,
+            _ => *acc_percent_axis,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    // the baseline is picked at the crosshair, and cleared when there is none
+    let percent_baselines = non_overlay_user_input_events
+        .clone()
+        .with_latest_from(chart_tool_states.clone(), |(ev, chart_tool_state)| {
+            (*ev, chart_tool_state.crosshair)
+        })
+        .fold(None, |acc_percent_baseline, (ev, crosshair)| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('B'),
+                ..
+            }) => crosshair.map(|(x, _)| Utc.timestamp(x as i64, 0)),
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('R'),
+                ..
+            }) => None,
+            _ => *acc_percent_baseline,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    let stats_panels = non_overlay_user_input_events
+        .clone()
+        .fold(false, |acc_stats_panel, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('x'),
+                ..
+            }) => !acc_stats_panel,
+            _ => *acc_stats_panel,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    let return_histograms = non_overlay_user_input_events
+        .clone()
+        .fold(false, |acc_return_histogram, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('d'),
+                ..
+            }) => !acc_return_histogram,
+            _ => *acc_return_histogram,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    let ma_shadings = non_overlay_user_input_events
+        .clone()
+        .fold(false, |acc_ma_shading, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('b'),
+                ..
+            }) => !acc_ma_shading,
+            _ => *acc_ma_shading,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    // the summary view is only left, for a quick glance at startup
+    let summary_views = non_overlay_user_input_events
+        .clone()
+        .fold(args.summary_view, |acc_summary_view, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Enter,
+                ..
+            }) => false,
+            _ => *acc_summary_view,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    let inverted_axes = non_overlay_user_input_events
+        .clone()
+        .fold(false, |acc_inverted_axis, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('I'),
+                ..
+            }) => !acc_inverted_axis,
+            _ => *acc_inverted_axis,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    let ema_ribbons = non_overlay_user_input_events
+        .clone()
+        .fold(false, |acc_ema_ribbon, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('e'),
+                ..
+            }) => !acc_ema_ribbon,
+            _ => *acc_ema_ribbon,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    let hidden_series = non_overlay_user_input_events
+        .clone()
+        .fold(HashSet::new(), |acc_hidden_series, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char(c),
+                modifiers,
+            }) if modifiers.contains(KeyModifiers::ALT) => match ChartSeries::from_hotkey(*c) {
+                Some(series) if acc_hidden_series.contains(&series) => {
+                    acc_hidden_series.without(&series)
+                }
+                Some(series) => acc_hidden_series.update(series),
+                None => acc_hidden_series.clone(),
+            },
+            _ => acc_hidden_series.clone(),
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    let earnings = non_overlay_user_input_events
+        .clone()
+        .fold(false, |acc_earnings, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('r'),
+                ..
+            }) => !acc_earnings,
+            _ => *acc_earnings,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    let up_down_ribbons = non_overlay_user_input_events
+        .clone()
+        .fold(false, |acc_up_down_ribbon, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('u'),
+                ..
+            }) => !acc_up_down_ribbon,
+            _ => *acc_up_down_ribbon,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    let minimaps = non_overlay_user_input_events
+        .clone()
+        .fold(false, |acc_minimap, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('o'),
+                ..
+            }) => !acc_minimap,
+            _ => *acc_minimap,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    let volume_panes = non_overlay_user_input_events
+        .clone()
+        .fold(false, |acc_volume_pane, ev| match ev {
+            InputEvent::Key(KeyEvent {
+                code: KeyCode::Char('v'),
+                ..
+            }) => !acc_volume_pane,
+            _ => *acc_volume_pane,
+        })
+        .distinct_until_changed()
+        .broadcast();
+
+    let footer_messages = tick_input_events
+        .clone()
+        .map(|_| None)
+        .merge(fitted_time_frames.clone().map(|time_frame| {
+            Some(FooterMessage::new(format!(
+                "Switched to {} to fit the history of the symbol",
+                time_frame
+            )))
+        }))
+        .merge(
+            non_overlay_user_input_events
+                .clone()
+                .with_latest_from(stocks.clone(), |(ev, stock)| (*ev, stock.clone()))
+                .filter_map(|(ev, stock)| match ev {
+                    InputEvent::Key(KeyEvent {
+                        code: KeyCode::Char('y'),
+                        modifiers,
+                    }) if !modifiers.contains(KeyModifiers::CONTROL) => Some(stock.symbol.clone()),
+                    InputEvent::Key(KeyEvent {
+                        code: KeyCode::Char('Y'),
+                        ..
+                    }) => Some(match stock.bars.get_max() {
+                        Some(latest_bar) => format!(
+                            "{} {:.*}",
+                            stock.symbol,
+                            format::price_precision(latest_bar.close),
+                            latest_bar.close
+                        ),
+                        None => stock.symbol.clone(),
+                    }),
+                    _ => None,
+                })
+                .map(|text| {
+                    Some(match clipboard::copy(text) {
+                        Ok(()) => FooterMessage::new(format!("Copied {} to clipboard", text)),
+                        Err(err) => {
+                            FooterMessage::new(format!("Cannot copy to clipboard: {}", err))
+                        }
+                    })
+                }),
+        )
+        .merge(
+            news_panel_events
+                .clone()
+                .filter_map(|(ev, _)| match ev {
+                    ScrollPanelEvent::Open(index) => Some(*index),
+                    _ => None,
+                })
+                .with_latest_from(news_headlines.clone(), |(index, news_headlines)| {
+                    news_headlines
+                        .get(*index)
+                        .and_then(|news_headline| news_headline.link.clone())
+                })
+                .map(|link| {
+                    Some(match link {
+                        Some(link) => match browser::open(link) {
+                            Ok(()) => FooterMessage::new(format!("Opening {}", link)),
+                            Err(err) => {
+                                FooterMessage::new(format!("Cannot open {}: {}", link, err))
+                            }
+                        },
+                        None => FooterMessage::new("No link for this headline".to_owned()),
+                    })
+                }),
+        )
+        .fold(
+            None,
+            |acc_footer_message: &Option<FooterMessage>, footer_message| match footer_message {
+                Some(_) => footer_message.clone(),
+                None => acc_footer_message.clone().and_then(FooterMessage::tick),
+            },
+        )
+        .distinct_until_changed()
+        .broadcast();
+
+    // Only a change of the latest close, with the bar before it unchanged, is flashed, so that
+    // switching symbols or loading more history does not trigger it.
+    let price_flashes = tick_input_events
+        .clone()
+        .map(|_| None)
+        .merge(stocks.clone().map(|stock| {
+            let mut latest_bars = stock.bars.iter().rev();
+            latest_bars.next().map(|latest_bar| {
+                (
+                    stock.symbol.clone(),
+                    latest_bar.timestamp,
+                    latest_bar.close,
+                    latest_bars.next().map(|previous_bar| previous_bar.close),
+                )
+            })
+        }))
+        .fold(
+            (None, None),
+            |(acc_price_sample, acc_price_flash): &(Option<_>, Option<PriceFlash>),
+             price_sample| match price_sample {
+                Some((symbol, timestamp, close, previous_close)) => {
+                    let price_flash = match acc_price_sample {
+                        Some((acc_symbol, acc_timestamp, acc_close, acc_previous_close))
+                            if acc_symbol == symbol && acc_timestamp == timestamp =>
+                        {
+                            if acc_previous_close != previous_close || acc_close == close {
+                                *acc_price_flash
+                            } else if close > acc_close {
+                                Some(PriceFlash::new(PriceDirection::Up))
+                            } else {
+                                Some(PriceFlash::new(PriceDirection::Down))
+                            }
+                        }
+                        _ => None,
+                    };
+                    (price_sample.clone(), price_flash)
+                }
+                None => (
+                    acc_price_sample.clone(),
+                    acc_price_flash.and_then(PriceFlash::tick),
+                ),
+            },
+        )
+        .map(|(_, price_flash)| *price_flash)
+        .distinct_until_changed()
+        .broadcast();
+
+    // shown only until the first symbol has loaded, not for a later one that fails to
+    let splashes = stocks
+        .clone()
+        .fold(!args.no_splash, |acc_splash, stock| {
+            *acc_splash && stock.is_loading()
+        })
+        .distinct_until_changed()
+        .broadcast();
+
     let init_ui_state = UiState {
+        chart_anchor: args.chart_anchor,
+        chart_style: args.chart_style,
+        chart_viewports: chart_viewports.clone(),
+        color_scheme,
+        compact_header: args.compact_header,
+        crosshair_step: args.crosshair_step,
+        crossover_periods: args.crossover,
+        date_format,
         date_range: args.time_frame.now_date_range(),
         debug_draw: args.debug_draw,
+        entry_price_field_state: Rc::new(RefCell::new(init_entry_price_field_state.clone())),
+        favorites,
+        frame_rate_counter: Rc::new(RefCell::new(
+            FrameRateCounter::new(chrono::Duration::seconds(1)).smoothed(args.frame_time_window),
+        )),
+        fullscreen_chart: args.fullscreen_chart,
+        gap_threshold: args.gap_threshold,
+        gridlines: args.gridlines,
         indicator: args.indicator,
         indicator_menu_state: Rc::new(RefCell::new(init_indicator_menu_state.clone())),
+        indicator_precision: config.indicators.precision,
+        line_style: args.line_style,
+        max_x_labels: args.max_x_labels,
+        minimap_viewports: minimap_viewports.clone(),
+        indicator_params_field_state: Rc::new(RefCell::new(
+            init_indicator_params_field_state.clone(),
+        )),
+        number_format: args.locale.unwrap_or_default(),
+        pane_split: init_pane_split,
+        previous_close: args.previous_close,
+        splash: !args.no_splash,
+        split_time_frame: args.split_time_frame,
+        stale_after: args
+            .stale_after
+            .map(|minutes| chrono::Duration::minutes(minutes as i64)),
         stock_symbol_field_state: Rc::new(RefCell::new(init_stock_symbol_field_state.clone())),
+        summary_view: args.summary_view,
         time_frame: args.time_frame,
         time_frame_menu_state: Rc::new(RefCell::new(init_time_frame_menu_state.clone())),
+        ui_target_areas: ui_target_areas.clone(),
+        volatility_cone: args.volatility_cone,
+        volume_ema_period: args.volume_ema,
         ..UiState::default()
     };
 
     let ui_states = time_frames
         .clone()
-        .combine_latest(date_ranges.clone(), |(time_frame, date_range)| {
-            (*time_frame, date_range.clone())
+        .map({
+            let init_ui_state = init_ui_state.clone();
+            move |time_frame| UiState {
+                time_frame: *time_frame,
+                ..init_ui_state.clone()
+            }
+        })
+        .combine_latest(date_ranges.clone(), |(ui_state, date_range)| UiState {
+            date_range: date_range.clone(),
+            ..ui_state.clone()
+        })
+        .combine_latest(stock_symbols.clone(), {
+            let price_precision_config = config.price_precision.clone();
+            move |(ui_state, stock_symbol)| UiState {
+                number_format: ui_state
+                    .number_format
+                    .with_price_precision(price_precision_config.get(stock_symbol)),
+                ..ui_state.clone()
+            }
+        })
+        .combine_latest(hidden_indicators.clone(), |(ui_state, hidden_indicator)| {
+            UiState {
+                hidden_indicator: *hidden_indicator,
+                ..ui_state.clone()
+            }
+        })
+        .combine_latest(indicators.clone(), |(ui_state, indicator)| UiState {
+            indicator: *indicator,
+            ..ui_state.clone()
+        })
+        .combine_latest(adjusted_closes.clone(), |(ui_state, adjusted_close)| {
+            UiState {
+                adjusted_close: *adjusted_close,
+                ..ui_state.clone()
+            }
         })
-        .combine_latest(
-            indicators.clone(),
-            |((time_frame, date_range), indicator)| (*time_frame, date_range.clone(), *indicator),
-        )
         .combine_latest(
             stock_symbol_field_states.clone(),
-            |((time_frame, date_range, indicator), stock_symbol_field_state)| {
-                (
-                    *time_frame,
-                    date_range.clone(),
-                    *indicator,
-                    stock_symbol_field_state.clone(),
-                )
+            |(ui_state, stock_symbol_field_state)| UiState {
+                stock_symbol_field_state: Rc::new(RefCell::new(stock_symbol_field_state.clone())),
+                ..ui_state.clone()
             },
         )
         .combine_latest(
             time_frame_menu_states.clone(),
-            |(
-                (time_frame, date_range, indicator, stock_symbol_field_state),
-                time_frame_menu_state,
-            )| {
-                (
-                    *time_frame,
-                    date_range.clone(),
-                    *indicator,
-                    stock_symbol_field_state.clone(),
-                    time_frame_menu_state.clone(),
-                )
+            |(ui_state, time_frame_menu_state)| UiState {
+                time_frame_menu_state: Rc::new(RefCell::new(time_frame_menu_state.clone())),
+                ..ui_state.clone()
             },
         )
         .combine_latest(
             indicator_menu_states.clone(),
-            |(
-                (
-                    time_frame,
-                    date_range,
-                    indicator,
-                    stock_symbol_field_state,
-                    time_frame_menu_state,
-                ),
-                indicator_menu_state,
-            )| {
-                (
-                    *time_frame,
-                    date_range.clone(),
-                    *indicator,
-                    stock_symbol_field_state.clone(),
-                    time_frame_menu_state.clone(),
-                    indicator_menu_state.clone(),
-                )
+            |(ui_state, indicator_menu_state)| UiState {
+                indicator_menu_state: Rc::new(RefCell::new(indicator_menu_state.clone())),
+                ..ui_state.clone()
             },
         )
         .combine_latest(
-            debug_draws.clone(),
-            |(
-                (
-                    time_frame,
-                    date_range,
-                    indicator,
-                    stock_symbol_field_state,
-                    time_frame_menu_state,
-                    indicator_menu_state,
-                ),
-                debug_draw,
-            )| {
-                (
-                    *time_frame,
-                    date_range.clone(),
-                    *indicator,
-                    stock_symbol_field_state.clone(),
-                    time_frame_menu_state.clone(),
-                    indicator_menu_state.clone(),
-                    *debug_draw,
-                )
+            indicator_params_field_states.clone(),
+            |(ui_state, indicator_params_field_state)| UiState {
+                indicator_params_field_state: Rc::new(RefCell::new(
+                    indicator_params_field_state.clone(),
+                )),
+                ..ui_state.clone()
             },
         )
-        .fold(init_ui_state.clone(), {
-            let ui_target_areas = ui_target_areas.clone();
-            move |acc_ui_state,
-                  (
-                time_frame,
-                date_range,
-                indicator,
-                stock_symbol_field_state,
-                time_frame_menu_state,
-                indicator_menu_state,
-                debug_draw,
-            )| UiState {
-                date_range: date_range.clone(),
-                debug_draw: *debug_draw,
-                indicator: *indicator,
-                indicator_menu_state: Rc::new(RefCell::new(indicator_menu_state.clone())),
-                stock_symbol_field_state: Rc::new(RefCell::new(stock_symbol_field_state.clone())),
-                time_frame: *time_frame,
-                time_frame_menu_state: Rc::new(RefCell::new(time_frame_menu_state.clone())),
-                ui_target_areas: ui_target_areas.clone(),
-                ..acc_ui_state.clone()
+        .combine_latest(
+            entry_price_field_states.clone(),
+            |(ui_state, entry_price_field_state)| UiState {
+                entry_price_field_state: Rc::new(RefCell::new(entry_price_field_state.clone())),
+                ..ui_state.clone()
+            },
+        )
+        .combine_latest(entry_prices.clone(), |(ui_state, entry_price)| UiState {
+            entry_price: *entry_price,
+            ..ui_state.clone()
+        })
+        .combine_latest(favorite_orders.clone(), |(ui_state, favorites)| UiState {
+            favorites: favorites.clone(),
+            ..ui_state.clone()
+        })
+        .combine_latest(chart_tool_states.clone(), |(ui_state, chart_tool_state)| {
+            UiState {
+                chart_tool_state: chart_tool_state.clone(),
+                ..ui_state.clone()
+            }
+        })
+        .combine_latest(debug_draws.clone(), |(ui_state, debug_draw)| UiState {
+            debug_draw: *debug_draw,
+            ..ui_state.clone()
+        })
+        .combine_latest(fullscreen_charts.clone(), |(ui_state, fullscreen_chart)| {
+            UiState {
+                fullscreen_chart: *fullscreen_chart,
+                ..ui_state.clone()
             }
         })
+        .combine_latest(inverted_axes.clone(), |(ui_state, inverted_axis)| UiState {
+            inverted_axis: *inverted_axis,
+            ..ui_state.clone()
+        })
+        .combine_latest(percent_axes.clone(), |(ui_state, percent_axis)| UiState {
+            percent_axis: *percent_axis,
+            ..ui_state.clone()
+        })
+        .combine_latest(percent_baselines.clone(), |(ui_state, percent_baseline)| {
+            UiState {
+                percent_baseline: *percent_baseline,
+                ..ui_state.clone()
+            }
+        })
+        .combine_latest(stats_panels.clone(), |(ui_state, stats_panel)| UiState {
+            stats_panel: *stats_panel,
+            ..ui_state.clone()
+        })
+        .combine_latest(return_histograms.clone(), |(ui_state, return_histogram)| {
+            UiState {
+                return_histogram: *return_histogram,
+                ..ui_state.clone()
+            }
+        })
+        .combine_latest(ma_shadings.clone(), |(ui_state, ma_shading)| UiState {
+            ma_shading: *ma_shading,
+            ..ui_state.clone()
+        })
+        .combine_latest(summary_views.clone(), |(ui_state, summary_view)| UiState {
+            summary_view: *summary_view,
+            ..ui_state.clone()
+        })
+        .combine_latest(pane_splits.clone(), |(ui_state, pane_split)| UiState {
+            pane_split: *pane_split,
+            ..ui_state.clone()
+        })
+        .combine_latest(split_views.clone(), |(ui_state, split_view)| UiState {
+            split_view: *split_view,
+            ..ui_state.clone()
+        })
+        .combine_latest(ema_ribbons.clone(), |(ui_state, ema_ribbon)| UiState {
+            ema_ribbon: *ema_ribbon,
+            ..ui_state.clone()
+        })
+        .combine_latest(earnings.clone(), |(ui_state, earnings)| UiState {
+            earnings: *earnings,
+            ..ui_state.clone()
+        })
+        .combine_latest(hidden_series.clone(), |(ui_state, hidden_series)| UiState {
+            hidden_series: hidden_series.clone(),
+            ..ui_state.clone()
+        })
+        .combine_latest(up_down_ribbons.clone(), |(ui_state, up_down_ribbon)| {
+            UiState {
+                up_down_ribbon: *up_down_ribbon,
+                ..ui_state.clone()
+            }
+        })
+        .combine_latest(minimaps.clone(), |(ui_state, minimap)| UiState {
+            minimap: *minimap,
+            ..ui_state.clone()
+        })
+        .combine_latest(volume_panes.clone(), |(ui_state, volume_pane)| UiState {
+            volume_pane: *volume_pane,
+            ..ui_state.clone()
+        })
+        .combine_latest(footer_messages.clone(), |(ui_state, footer_message)| {
+            UiState {
+                footer_message: footer_message.clone(),
+                ..ui_state.clone()
+            }
+        })
+        .combine_latest(news_headlines.clone(), |(ui_state, news_headlines)| {
+            UiState {
+                news_headlines: news_headlines.clone(),
+                ..ui_state.clone()
+            }
+        })
+        .combine_latest(news_panel_states.clone(), |(ui_state, news_panel_state)| {
+            UiState {
+                news_panel_state: *news_panel_state,
+                ..ui_state.clone()
+            }
+        })
+        .combine_latest(price_flashes.clone(), |(ui_state, price_flash)| UiState {
+            price_flash: *price_flash,
+            ..ui_state.clone()
+        })
+        .combine_latest(splashes.clone(), |(ui_state, splash)| UiState {
+            splash: *splash,
+            ..ui_state.clone()
+        })
         .broadcast();
 
+    let indicator_params_cursor_points = indicator_params_field_states
+        .clone()
+        .combine_latest(
+            ui_target_areas
+                .clone()
+                .filter(|(ui_target, ..)| matches!(ui_target, UiTarget::IndicatorParamsField)),
+            |(text_field_state, (_, area))| (text_field_state.clone(), *area),
+        )
+        .map(|(text_field_state, area)| {
+            if let Some(area) = *area {
+                text_field_state.cursor_point(area)
+            } else {
+                None
+            }
+        });
+
+    let entry_price_cursor_points = entry_price_field_states
+        .clone()
+        .combine_latest(
+            ui_target_areas
+                .clone()
+                .filter(|(ui_target, ..)| matches!(ui_target, UiTarget::EntryPriceField)),
+            |(text_field_state, (_, area))| (text_field_state.clone(), *area),
+        )
+        .map(|(text_field_state, area)| {
+            if let Some(area) = *area {
+                text_field_state.cursor_point(area)
+            } else {
+                None
+            }
+        });
+
+    // at most one text field is active, and has the cursor
     let cursor_points = stock_symbol_field_states
         .clone()
         .combine_latest(
@@ -555,8 +2025,26 @@ async fn main() -> anyhow::Result<()> {
                 None
             }
         })
+        .combine_latest(
+            indicator_params_cursor_points,
+            |(stock_symbol_cursor_point, indicator_params_cursor_point)| {
+                stock_symbol_cursor_point.or(*indicator_params_cursor_point)
+            },
+        )
+        .combine_latest(
+            entry_price_cursor_points,
+            |(cursor_point, entry_price_cursor_point)| cursor_point.or(*entry_price_cursor_point),
+        )
         .broadcast();
 
+    let profile = args.profile;
+    let mut profile_frame_rate_counter = FrameRateCounter::new(chrono::Duration::seconds(1));
+    // the input on the ticks skipped in between is still handled, and shows on the next draw
+    let draw_every = cmp::max(args.draw_every, 1);
+    let mut tick_count = 0u32;
+    let fit_time_frame = args.fit_time_frame;
+    let mut fitted_symbol: Option<String> = None;
+
     tick_input_events
         .clone()
         .merge(non_overlay_user_input_events.clone())
@@ -570,87 +2058,246 @@ async fn main() -> anyhow::Result<()> {
                 (*ev, stock.clone(), ui_state.clone(), *cursor_point)
             },
         )
-        .subscribe(|(ev, stock, ui_state, cursor_point)| match ev {
-            InputEvent::Key(KeyEvent { code, .. }) => match code {
-                KeyCode::Char('q') => {
-                    should_quit.store(true, atomic::Ordering::Relaxed);
-                }
-                KeyCode::Char(_) => {
-                    execute!(terminal.backend_mut(), crossterm::style::Print("\x07"),).unwrap();
+        .with_latest_from(
+            relative_strength_stocks.clone(),
+            |((ev, stock, ui_state, cursor_point), relative_strength_stock)| {
+                (
+                    *ev,
+                    stock.clone(),
+                    ui_state.clone(),
+                    *cursor_point,
+                    relative_strength_stock.clone(),
+                )
+            },
+        )
+        .with_latest_from(
+            split_stocks.clone(),
+            |((ev, stock, ui_state, cursor_point, relative_strength_stock), split_stock)| {
+                (
+                    *ev,
+                    stock.clone(),
+                    ui_state.clone(),
+                    *cursor_point,
+                    relative_strength_stock.clone(),
+                    split_stock.clone(),
+                )
+            },
+        )
+        .subscribe(
+            |(ev, stock, ui_state, cursor_point, relative_strength_stock, split_stock)| match ev {
+                InputEvent::Key(KeyEvent { code, modifiers }) => match code {
+                    KeyCode::Char('q') => {
+                        should_quit.store(true, atomic::Ordering::Relaxed);
+                    }
+                    KeyCode::Char('y') | KeyCode::Char('z')
+                        if modifiers.contains(KeyModifiers::CONTROL) => {}
+                    KeyCode::Char('a')
+                    | KeyCode::Char('e')
+                    | KeyCode::Char('f')
+                    | KeyCode::Char('h')
+                    | KeyCode::Char('I')
+                    | KeyCode::Char('m')
+                    | KeyCode::Char('N')
+                    | KeyCode::Char('o')
+                    | KeyCode::Char('P')
+                    | KeyCode::Char('u')
+                    | KeyCode::Char('v')
+                    | KeyCode::Char('x')
+                    | KeyCode::Char('d')
+                    | KeyCode::Char('r')
+                    | KeyCode::Char('b')
+                    | KeyCode::Char('[')
+                    | KeyCode::Char(']')
+                    | KeyCode::Char('{')
+                    | KeyCode::Char('}')
+                    | KeyCode::Char('y')
+                    | KeyCode::Char('Y')
+                    | KeyCode::Char('B')
+                    | KeyCode::Char('R')
+                    | KeyCode::Char('%') => {}
+                    key_code if chart_tool_hotkey_map.contains_key(key_code) => {}
+                    key_code if time_frame_hotkey_map.contains_key(key_code) => {}
+                    KeyCode::Char(_) if beep => {
+                        execute!(terminal.backend_mut(), crossterm::style::Print("\x07"),).unwrap();
+                    }
+                    _ => {}
+                },
+                InputEvent::Tick => {
+                    let skipped = tick_count % draw_every != 0;
+                    tick_count = tick_count.wrapping_add(1);
+                    if skipped {
+                        return;
+                    }
+
+                    if fit_time_frame
+                        && !stock.bars.is_empty()
+                        && fitted_symbol.as_ref() != Some(&stock.symbol)
+                    {
+                        fitted_symbol = Some(stock.symbol.clone());
+                        let earliest = stock.bars.get_min().unwrap().datetime();
+                        *fitted_time_frame_queue.borrow_mut() =
+                            app::fitting_time_frame(ui_state.time_frame, earliest, Utc::now());
+                    }
+
+                    let app = App {
+                        relative_strength_stock: relative_strength_stock.clone(),
+                        split_stock: split_stock.clone(),
+                        stock: stock.clone(),
+                        ui_state: ui_state.clone(),
+                    };
+                    let draw_instant = time::Instant::now();
+                    terminal
+                        .draw(|mut f| {
+                            ui::draw(&mut f, &app).expect("draw failed");
+                        })
+                        .unwrap();
+                    if profile {
+                        info!("drawing took {} ms", draw_instant.elapsed().as_millis());
+                        if let Some(frame_time) = profile_frame_rate_counter.incr() {
+                            info!("frame time: {} ms", frame_time.num_milliseconds());
+                        }
+                    }
+                    if let Some((cx, cy)) = *cursor_point {
+                        execute!(
+                            terminal.backend_mut(),
+                            cursor::Show,
+                            cursor::EnableBlinking,
+                            cursor::MoveTo(cx, cy),
+                        )
+                        .unwrap();
+                    } else {
+                        execute!(
+                            terminal.backend_mut(),
+                            cursor::Hide,
+                            cursor::DisableBlinking,
+                        )
+                        .unwrap();
+                    }
                 }
                 _ => {}
             },
-            InputEvent::Tick => {
-                let app = App {
-                    stock: stock.clone(),
-                    ui_state: ui_state.clone(),
-                };
-                terminal
-                    .draw(|mut f| {
-                        ui::draw(&mut f, &app).expect("draw failed");
-                    })
-                    .unwrap();
-                if let Some((cx, cy)) = *cursor_point {
-                    execute!(
-                        terminal.backend_mut(),
-                        cursor::Show,
-                        cursor::EnableBlinking,
-                        cursor::MoveTo(cx, cy),
-                    )
-                    .unwrap();
-                } else {
-                    execute!(
-                        terminal.backend_mut(),
-                        cursor::Hide,
-                        cursor::DisableBlinking,
-                    )
-                    .unwrap();
-                }
-            }
-            _ => {}
-        });
+        );
 
-    let input_event_stream = EventStream::new()
-        .filter(|ev| match ev {
-            Ok(Event::Key(_)) | Ok(Event::Mouse(_)) => true,
-            _ => false,
-        })
-        .map(|ev| match ev {
-            Ok(Event::Key(key_event)) => InputEvent::Key(key_event),
-            Ok(Event::Mouse(mouse_event)) => InputEvent::Mouse(mouse_event),
-            _ => unreachable!(),
-        });
     let tick_stream = stream::interval(time::Duration::from_millis(TICK_RATE));
-    let input_tick_stream = tick_stream.map(|()| InputEvent::Tick);
-    let mut input_event_stream = input_event_stream.merge(input_tick_stream);
+    let mut input_event_stream: Pin<Box<dyn stream::Stream<Item = InputEvent>>> =
+        if let Some(script) = script {
+            // a scripted input event per tick, which includes the ticks of the script itself
+            Box::pin(
+                tick_stream
+                    .zip(stream::from_iter(script.input_events))
+                    .map(|((), ev)| ev),
+            )
+        } else {
+            let input_event_stream = EventStream::new()
+                .filter(|ev| match ev {
+                    Ok(Event::Key(_)) | Ok(Event::Mouse(_)) => true,
+                    _ => false,
+                })
+                .map(|ev| match ev {
+                    Ok(Event::Key(key_event)) => InputEvent::Key(key_event),
+                    Ok(Event::Mouse(mouse_event)) => InputEvent::Mouse(mouse_event),
+                    _ => unreachable!(),
+                });
+            let input_tick_stream = tick_stream.map(|()| InputEvent::Tick);
+            Box::pin(input_event_stream.merge(input_tick_stream))
+        };
 
     // draw once before hitting the network, as it is blocking
     stocks.send(Stock {
-        symbol: args.symbol.clone(),
+        symbol: symbol.clone(),
         ..Stock::default()
     });
     ui_states.send(init_ui_state);
     cursor_points.send(None);
+    relative_strength_stocks.send(None);
+    split_stocks.send(None);
     input_events.send(InputEvent::Tick);
 
     // send the initial values
     chart_events.send(ChartEvent::Reset);
     time_frames.send(args.time_frame);
-    indicators.send(args.indicator);
-    stock_symbols.send(args.symbol);
+    selected_indicators.send(args.indicator);
+    favorite_orders.send(latest_favorites.borrow().clone());
+    hidden_indicators.send(None);
+    stock_symbols.send(symbol);
+    if let Some(relative_strength_symbol) = relative_strength_symbol {
+        relative_strength_symbols.send(relative_strength_symbol);
+    }
     stock_symbol_field_states.send(init_stock_symbol_field_state);
+    time_frame_menu_selections.send(Some(args.time_frame));
+    indicator_menu_selections.send(args.indicator);
     time_frame_menu_states.send(init_time_frame_menu_state);
     indicator_menu_states.send(init_indicator_menu_state);
+    indicator_params_field_states.send(init_indicator_params_field_state);
+    entry_price_field_states.send(init_entry_price_field_state);
+    chart_tool_states.send(ChartToolState::default());
     debug_draws.send(args.debug_draw);
+    fullscreen_charts.send(args.fullscreen_chart);
+    percent_axes.send(false);
+    inverted_axes.send(false);
+    percent_baselines.send(None);
+    stats_panels.send(false);
+    return_histograms.send(false);
+    ma_shadings.send(false);
+    summary_views.send(args.summary_view);
+    ema_ribbons.send(false);
+    hidden_series.send(HashSet::new());
+    earnings.send(false);
+    up_down_ribbons.send(false);
+    minimaps.send(false);
+    volume_panes.send(false);
+    split_views.send(false);
+    split_time_frames.send(args.split_time_frame);
+    adjusted_closes.send(None);
+    pane_splits.send(init_pane_split);
+    news_headline_counts.send(0);
+    news_headlines.send(vec![]);
+    news_panel_states.send(ScrollPanelState::default());
+    footer_messages.send(None);
+    price_flashes.send(None);
+    splashes.send(!args.no_splash);
     active_overlays.send(None);
+    let open_overlay = args.open;
+    let init_overlay_state = |ui_target| match open_overlay {
+        Some(overlay) if overlay.ui_target() == ui_target => OverlayState::Active,
+        _ => OverlayState::default(),
+    };
     overlay_states.feed(
         vec![
-            (UiTarget::StockSymbolField, OverlayState::default()),
-            (UiTarget::TimeFrameMenu, OverlayState::default()),
-            (UiTarget::IndicatorMenu, OverlayState::default()),
+            (
+                UiTarget::StockSymbolField,
+                init_overlay_state(UiTarget::StockSymbolField),
+            ),
+            (
+                UiTarget::TimeFrameMenu,
+                init_overlay_state(UiTarget::TimeFrameMenu),
+            ),
+            (
+                UiTarget::IndicatorMenu,
+                init_overlay_state(UiTarget::IndicatorMenu),
+            ),
+            (
+                UiTarget::IndicatorParamsField,
+                init_overlay_state(UiTarget::IndicatorParamsField),
+            ),
+            (
+                UiTarget::EntryPriceField,
+                init_overlay_state(UiTarget::EntryPriceField),
+            ),
+            (UiTarget::NewsPanel, init_overlay_state(UiTarget::NewsPanel)),
         ]
         .iter(),
     );
+    // the overlay only reacts to its state along with an input event, so send its hotkey to open it
+    if let Some(overlay) = open_overlay {
+        if let Some(&hotkey) = hotkey_overlay_map.get_by_right(&overlay.ui_target()) {
+            input_events.send(InputEvent::Key(KeyEvent::new(hotkey, KeyModifiers::NONE)));
+        }
+    }
+
+    if profile {
+        info!("startup took {} ms", startup_instant.elapsed().as_millis());
+    }
 
     while !should_quit.load(atomic::Ordering::Relaxed) {
         let drained_overlay_states: VecDeque<_> =
@@ -662,10 +2309,49 @@ async fn main() -> anyhow::Result<()> {
             );
             overlay_states.send((ui_target, overlay_state));
         }
-        input_events.send(input_event_stream.next().await.unwrap());
+        let fitted_time_frame = fitted_time_frame_queue.borrow_mut().take();
+        if let Some(time_frame) = fitted_time_frame {
+            debug!("fitting the time frame to the history: {:?}", time_frame);
+            fitted_time_frames.send(time_frame);
+        }
+        let time_frame_menu_selection = time_frame_menu_selection_queue.borrow_mut().take();
+        if let Some(time_frame) = time_frame_menu_selection {
+            time_frame_menu_selections.send(time_frame);
+        }
+        let indicator_menu_selection = indicator_menu_selection_queue.borrow_mut().take();
+        if let Some(indicator) = indicator_menu_selection {
+            indicator_menu_selections.send(indicator);
+        }
+        match input_event_stream.next().await {
+            Some(input_event) => input_events.send(input_event),
+            // only a script runs out of input events
+            None => break,
+        }
     }
 
     cleanup_terminal();
+    // Fetches run on the main thread and complete before the next input event is handled, so
+    // nothing is in flight by now and only the log is left to flush.
+    log::logger().flush();
+
+    if let Some(favorites_config_path) = favorites_config_path {
+        let favorites = latest_favorites.borrow();
+        let is_reordered = !favorite_entries
+            .iter()
+            .map(|(_, favorite)| favorite)
+            .eq(favorites.iter());
+        if is_reordered {
+            let mut favorite_entries = favorite_entries;
+            favorite_entries.sort_by_key(|(_, favorite)| {
+                favorites.iter().position(|reordered| reordered == favorite)
+            });
+            let favorite_entries: Vec<_> = favorite_entries
+                .into_iter()
+                .map(|(entry, _)| entry)
+                .collect();
+            Config::save_favorites(&favorites_config_path, &favorite_entries)?;
+        }
+    }
 
     Ok(())
 }
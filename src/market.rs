@@ -0,0 +1,159 @@
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use derive_more::Display;
+
+/// Quote currencies of cryptocurrency pairs on Yahoo Finance. Share classes, e.g. `BRK-B`, use the
+/// same separator.
+const CRYPTO_QUOTE_CURRENCIES: [&str; 12] = [
+    "AUD", "BTC", "CAD", "CNY", "ETH", "EUR", "GBP", "INR", "JPY", "KRW", "USD", "USDT",
+];
+
+#[derive(Clone, Copy, Debug, Display, Eq, PartialEq)]
+pub enum MarketStatus {
+    #[display(fmt = "CLOSED")]
+    Closed,
+    #[display(fmt = "OPEN")]
+    Open,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Exchange {
+    Australia,
+    Crypto,
+    Euronext,
+    HongKong,
+    India,
+    London,
+    Shanghai,
+    Switzerland,
+    Tokyo,
+    Toronto,
+    UnitedStates,
+    Xetra,
+}
+
+impl Exchange {
+    /// Resolves the exchange from the suffix of a Yahoo Finance symbol, e.g. `BMW.DE`.
+    ///
+    /// Cryptocurrency pairs, e.g. `BTC-USD`, are recognized by their quote currency. Symbols
+    /// without a known suffix are assumed to be listed on a US exchange.
+    pub fn from_symbol(symbol: &str) -> Self {
+        if let Some(quote_currency) = symbol.rsplit('-').next().filter(|_| symbol.contains('-')) {
+            if CRYPTO_QUOTE_CURRENCIES.contains(&quote_currency) {
+                return Self::Crypto;
+            }
+        }
+
+        let suffix = symbol.rsplit('.').next().filter(|_| symbol.contains('.'));
+
+        match suffix {
+            Some("AX") => Self::Australia,
+            Some("AS") | Some("BR") | Some("LS") | Some("PA") => Self::Euronext,
+            Some("HK") => Self::HongKong,
+            Some("BO") | Some("NS") => Self::India,
+            Some("L") => Self::London,
+            Some("SS") | Some("SZ") => Self::Shanghai,
+            Some("SW") => Self::Switzerland,
+            Some("T") => Self::Tokyo,
+            Some("TO") | Some("V") => Self::Toronto,
+            Some("DE") | Some("F") => Self::Xetra,
+            _ => Self::UnitedStates,
+        }
+    }
+
+    /// Resolves the exchange from its code as reported by the data source, e.g. `NMS` by Yahoo
+    /// Finance or `NASDAQ` by Alpha Vantage.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "ASX" => Some(Self::Australia),
+            "CCC" => Some(Self::Crypto),
+            "AMS" | "BRU" | "LIS" | "PAR" => Some(Self::Euronext),
+            "HKG" => Some(Self::HongKong),
+            "BSE" | "NSI" => Some(Self::India),
+            "LSE" => Some(Self::London),
+            "SHH" | "SHZ" => Some(Self::Shanghai),
+            "EBS" => Some(Self::Switzerland),
+            "JPX" => Some(Self::Tokyo),
+            "TOR" | "VAN" => Some(Self::Toronto),
+            "ASE" | "BTS" | "NAS" | "NASDAQ" | "NCM" | "NGM" | "NMS" | "NYQ" | "NYSE" | "PCX" => {
+                Some(Self::UnitedStates)
+            }
+            "FRA" | "GER" => Some(Self::Xetra),
+            _ => None,
+        }
+    }
+
+    pub fn time_zone(self) -> Tz {
+        match self {
+            Self::Australia => chrono_tz::Australia::Sydney,
+            Self::Crypto => chrono_tz::UTC,
+            Self::Euronext => chrono_tz::Europe::Paris,
+            Self::HongKong => chrono_tz::Asia::Hong_Kong,
+            Self::India => chrono_tz::Asia::Kolkata,
+            Self::London => chrono_tz::Europe::London,
+            Self::Shanghai => chrono_tz::Asia::Shanghai,
+            Self::Switzerland => chrono_tz::Europe::Zurich,
+            Self::Tokyo => chrono_tz::Asia::Tokyo,
+            Self::Toronto => chrono_tz::America::Toronto,
+            Self::UnitedStates => chrono_tz::America::New_York,
+            Self::Xetra => chrono_tz::Europe::Berlin,
+        }
+    }
+
+    /// Returns the regular trading sessions in the exchange's local time, none for an always open
+    /// market.
+    pub fn trading_sessions(self) -> Vec<(NaiveTime, NaiveTime)> {
+        let session = |(open_h, open_m): (u32, u32), (close_h, close_m): (u32, u32)| {
+            (
+                NaiveTime::from_hms(open_h, open_m, 0),
+                NaiveTime::from_hms(close_h, close_m, 0),
+            )
+        };
+
+        match self {
+            Self::Australia => vec![session((10, 0), (16, 0))],
+            Self::Crypto => vec![],
+            Self::Euronext => vec![session((9, 0), (17, 30))],
+            Self::HongKong => vec![session((9, 30), (12, 0)), session((13, 0), (16, 0))],
+            Self::India => vec![session((9, 15), (15, 30))],
+            Self::London => vec![session((8, 0), (16, 30))],
+            Self::Shanghai => vec![session((9, 30), (11, 30)), session((13, 0), (15, 0))],
+            Self::Switzerland => vec![session((9, 0), (17, 30))],
+            Self::Tokyo => vec![session((9, 0), (11, 30)), session((12, 30), (15, 0))],
+            Self::Toronto => vec![session((9, 30), (16, 0))],
+            Self::UnitedStates => vec![session((9, 30), (16, 0))],
+            Self::Xetra => vec![session((9, 0), (17, 30))],
+        }
+    }
+
+    /// Returns whether the market trades around the clock, every day.
+    pub fn is_always_open(self) -> bool {
+        self == Self::Crypto
+    }
+
+    /// Returns whether the exchange is in a regular trading session at the given time.
+    ///
+    /// Public holidays are not taken into account.
+    pub fn market_status(self, now: DateTime<Utc>) -> MarketStatus {
+        if self.is_always_open() {
+            return MarketStatus::Open;
+        }
+
+        let local_now = now.with_timezone(&self.time_zone());
+
+        if matches!(local_now.weekday(), Weekday::Sat | Weekday::Sun) {
+            return MarketStatus::Closed;
+        }
+
+        let local_time = local_now.time();
+        if self
+            .trading_sessions()
+            .iter()
+            .any(|(open, close)| *open <= local_time && local_time < *close)
+        {
+            MarketStatus::Open
+        } else {
+            MarketStatus::Closed
+        }
+    }
+}
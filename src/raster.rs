@@ -0,0 +1,279 @@
+use std::{fs::File, io::Write, path::Path};
+use tui::{buffer::Buffer, style::Color};
+
+const CELL_WIDTH: u32 = 8;
+const CELL_HEIGHT: u32 = 14;
+const GLYPH_SCALE: u32 = 2;
+
+const DEFAULT_FG: [u8; 3] = [0xd0, 0xd0, 0xd0];
+const DEFAULT_BG: [u8; 3] = [0x10, 0x10, 0x10];
+
+/// An RGB image rasterized from a terminal buffer, one cell at a time.
+///
+/// Braille, block and box drawing characters are drawn as shapes, so charts come out as they are
+/// on the terminal. Text is drawn with a small built-in 3x5 font, which only covers ASCII.
+pub struct Raster {
+    height: u32,
+    pixels: Vec<u8>,
+    width: u32,
+}
+
+impl Raster {
+    pub fn from_buffer(buffer: &Buffer) -> Self {
+        let area = buffer.area();
+        let width = area.width as u32 * CELL_WIDTH;
+        let height = area.height as u32 * CELL_HEIGHT;
+        let mut raster = Self {
+            height,
+            pixels: vec![0; (width * height * 3) as usize],
+            width,
+        };
+
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = buffer.get(x, y);
+                let fg = rgb(cell.fg).unwrap_or(DEFAULT_FG);
+                let bg = rgb(cell.bg).unwrap_or(DEFAULT_BG);
+                let origin = (
+                    (x - area.left()) as u32 * CELL_WIDTH,
+                    (y - area.top()) as u32 * CELL_HEIGHT,
+                );
+
+                raster.fill_rect(origin, (CELL_WIDTH, CELL_HEIGHT), bg);
+                if let Some(c) = cell.symbol.chars().next() {
+                    raster.draw_char(origin, c, fg);
+                }
+            }
+        }
+
+        raster
+    }
+
+    pub fn write_png(&self, path: &Path) -> anyhow::Result<()> {
+        let mut scanlines = Vec::with_capacity(((self.width * 3 + 1) * self.height) as usize);
+        for row in self.pixels.chunks((self.width * 3) as usize) {
+            // no filter
+            scanlines.push(0);
+            scanlines.extend_from_slice(row);
+        }
+
+        let mut header = vec![];
+        header.extend_from_slice(&self.width.to_be_bytes());
+        header.extend_from_slice(&self.height.to_be_bytes());
+        // 8-bit depth, RGB, deflate, no filter, no interlace
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        let mut file = File::create(path)?;
+        file.write_all(b"\x89PNG\r\n\x1a\n")?;
+        write_png_chunk(&mut file, b"IHDR", &header)?;
+        write_png_chunk(
+            &mut file,
+            b"IDAT",
+            &miniz_oxide::deflate::compress_to_vec_zlib(&scanlines, 6),
+        )?;
+        write_png_chunk(&mut file, b"IEND", &[])?;
+
+        Ok(())
+    }
+
+    fn fill_rect(&mut self, (x, y): (u32, u32), (width, height): (u32, u32), color: [u8; 3]) {
+        for py in y..(y + height).min(self.height) {
+            for px in x..(x + width).min(self.width) {
+                let i = ((py * self.width + px) * 3) as usize;
+                self.pixels[i..(i + 3)].copy_from_slice(&color);
+            }
+        }
+    }
+
+    fn draw_char(&mut self, (x, y): (u32, u32), c: char, color: [u8; 3]) {
+        let (half_width, half_height) = (CELL_WIDTH / 2, CELL_HEIGHT / 2);
+
+        match c {
+            ' ' => {}
+            '\u{2800}'..='\u{28ff}' => {
+                let dots = c as u32 - 0x2800;
+                // bit order of the braille dots, as (column, row)
+                let positions = [
+                    (0, 0),
+                    (0, 1),
+                    (0, 2),
+                    (1, 0),
+                    (1, 1),
+                    (1, 2),
+                    (0, 3),
+                    (1, 3),
+                ];
+                for (bit, &(column, row)) in positions.iter().enumerate() {
+                    if dots & (1 << bit) != 0 {
+                        self.fill_rect((x + 1 + column * 4, y + 1 + row * 3), (2, 2), color);
+                    }
+                }
+            }
+            '█' => self.fill_rect((x, y), (CELL_WIDTH, CELL_HEIGHT), color),
+            '▀' => self.fill_rect((x, y), (CELL_WIDTH, half_height), color),
+            '▄' => self.fill_rect((x, y + half_height), (CELL_WIDTH, half_height), color),
+            '•' => self.fill_rect((x + half_width - 1, y + half_height - 1), (2, 2), color),
+            _ => {
+                if let Some((left, right, up, down)) = box_drawing_arms(c) {
+                    if left {
+                        self.fill_rect((x, y + half_height), (half_width + 1, 1), color);
+                    }
+                    if right {
+                        self.fill_rect((x + half_width, y + half_height), (half_width, 1), color);
+                    }
+                    if up {
+                        self.fill_rect((x + half_width, y), (1, half_height + 1), color);
+                    }
+                    if down {
+                        self.fill_rect((x + half_width, y + half_height), (1, half_height), color);
+                    }
+                } else if let Some(rows) = glyph(c) {
+                    let origin = (x + 1, y + 2);
+                    for (row, bits) in rows.iter().enumerate() {
+                        for column in 0..3 {
+                            if bits & (0b100 >> column) != 0 {
+                                self.fill_rect(
+                                    (
+                                        origin.0 + column * GLYPH_SCALE,
+                                        origin.1 + row as u32 * GLYPH_SCALE,
+                                    ),
+                                    (GLYPH_SCALE, GLYPH_SCALE),
+                                    color,
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn write_png_chunk<W: Write>(w: &mut W, chunk_type: &[u8; 4], data: &[u8]) -> anyhow::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(chunk_type)?;
+    w.write_all(data)?;
+    w.write_all(&crc32(chunk_type.iter().chain(data)).to_be_bytes())?;
+
+    Ok(())
+}
+
+fn crc32<'a, I>(bytes: I) -> u32
+where
+    I: IntoIterator<Item = &'a u8>,
+{
+    let crc = bytes.into_iter().fold(0xffff_ffffu32, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            if crc & 1 != 0 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            }
+        })
+    });
+
+    !crc
+}
+
+fn rgb(color: Color) -> Option<[u8; 3]> {
+    match color {
+        Color::Reset => None,
+        Color::Black => Some([0x00, 0x00, 0x00]),
+        Color::Red => Some([0xcd, 0x31, 0x31]),
+        Color::Green => Some([0x0d, 0xbc, 0x79]),
+        Color::Yellow => Some([0xe5, 0xe5, 0x10]),
+        Color::Blue => Some([0x24, 0x72, 0xc8]),
+        Color::Magenta => Some([0xbc, 0x3f, 0xbc]),
+        Color::Cyan => Some([0x11, 0xa8, 0xcd]),
+        Color::Gray => Some([0xc0, 0xc0, 0xc0]),
+        Color::DarkGray => Some([0x66, 0x66, 0x66]),
+        Color::LightRed => Some([0xf1, 0x4c, 0x4c]),
+        Color::LightGreen => Some([0x23, 0xd1, 0x8b]),
+        Color::LightYellow => Some([0xf5, 0xf5, 0x43]),
+        Color::LightBlue => Some([0x3b, 0x8e, 0xea]),
+        Color::LightMagenta => Some([0xd6, 0x70, 0xd6]),
+        Color::LightCyan => Some([0x29, 0xb8, 0xdb]),
+        Color::White => Some([0xff, 0xff, 0xff]),
+        Color::Rgb(r, g, b) => Some([r, g, b]),
+        Color::Indexed(_) => None,
+    }
+}
+
+/// Returns which of the left, right, up and down arms a box drawing character has.
+fn box_drawing_arms(c: char) -> Option<(bool, bool, bool, bool)> {
+    match c {
+        '─' => Some((true, true, false, false)),
+        '│' => Some((false, false, true, true)),
+        '┌' => Some((false, true, false, true)),
+        '┐' => Some((true, false, false, true)),
+        '└' => Some((false, true, true, false)),
+        '┘' => Some((true, false, true, false)),
+        '├' => Some((false, true, true, true)),
+        '┤' => Some((true, false, true, true)),
+        '┬' => Some((true, true, false, true)),
+        '┴' => Some((true, true, true, false)),
+        '┼' => Some((true, true, true, true)),
+        _ => None,
+    }
+}
+
+/// Returns the rows of the 3x5 glyph for the char, with the leftmost pixel in the highest bit.
+/// Lowercase letters use the uppercase glyphs.
+fn glyph(c: char) -> Option<[u8; 5]> {
+    let rows = match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        '$' => [0b011, 0b110, 0b010, 0b011, 0b110],
+        '\'' => [0b010, 0b010, 0b000, 0b000, 0b000],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '?' => [0b111, 0b001, 0b010, 0b000, 0b010],
+        _ => return None,
+    };
+
+    Some(rows)
+}
@@ -0,0 +1,210 @@
+//! Scripted input events, replayed in place of the terminal's for reproducible demos.
+//!
+//! A script has a step per line, blank lines and lines starting with `#` being skipped:
+//!
+//! ```text
+//! # look up AAPL, then go back to the previous symbol
+//! key s
+//! type AAPL
+//! key enter
+//! wait 1000
+//! key ctrl+z
+//! click 10 5
+//! ```
+
+use crate::event::InputEvent;
+use anyhow::Context;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent};
+use std::{fs, path::Path};
+
+#[derive(Clone, Debug, Default)]
+pub struct Script {
+    pub input_events: Vec<InputEvent>,
+}
+
+impl Script {
+    pub fn load(path: &Path, tick_rate: u64) -> anyhow::Result<Self> {
+        let script = fs::read_to_string(path)
+            .with_context(|| format!("cannot read script {}", path.display()))?;
+        Self::parse(&script, tick_rate)
+            .with_context(|| format!("cannot parse script {}", path.display()))
+    }
+
+    /// Parses the steps of the script into input events, to be sent one per tick of `tick_rate`
+    /// ms. Each key press and click is followed by a tick, so that it is drawn before the next
+    /// one, and a wait is rounded up to whole ticks.
+    pub fn parse(script: &str, tick_rate: u64) -> anyhow::Result<Self> {
+        let mut input_events = vec![];
+        for (i, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (command, arg) = match line.find(char::is_whitespace) {
+                Some(pos) => (&line[..pos], line[pos..].trim_start()),
+                None => (line, ""),
+            };
+            let step_events = match command {
+                "key" => vec![InputEvent::Key(
+                    parse_key(arg).with_context(|| format!("line {}: invalid key", i + 1))?,
+                )],
+                "type" => {
+                    anyhow::ensure!(!arg.is_empty(), "line {}: nothing to type", i + 1);
+                    arg.chars().map(|c| InputEvent::Key(char_key(c))).collect()
+                }
+                "click" => {
+                    let mut coords = arg.split_whitespace().map(|n| n.parse::<u16>());
+                    match (coords.next(), coords.next(), coords.next()) {
+                        (Some(Ok(x)), Some(Ok(y)), None) => vec![InputEvent::Mouse(
+                            MouseEvent::Up(MouseButton::Left, x, y, KeyModifiers::NONE),
+                        )],
+                        _ => anyhow::bail!("line {}: expected click <x> <y>", i + 1),
+                    }
+                }
+                "wait" => {
+                    let ms: u64 = arg
+                        .parse()
+                        .ok()
+                        .with_context(|| format!("line {}: expected wait <ms>", i + 1))?;
+                    let ticks = ms / tick_rate + (ms % tick_rate != 0) as u64;
+                    input_events.extend((0..ticks).map(|_| InputEvent::Tick));
+                    continue;
+                }
+                _ => anyhow::bail!("line {}: unknown step {:?}", i + 1, command),
+            };
+            for ev in step_events {
+                input_events.push(ev);
+                input_events.push(InputEvent::Tick);
+            }
+        }
+
+        Ok(Self { input_events })
+    }
+}
+
+/// The key press of a character, with shift for an uppercase one as sent by the terminal.
+fn char_key(c: char) -> KeyEvent {
+    let modifiers = if c.is_uppercase() {
+        KeyModifiers::SHIFT
+    } else {
+        KeyModifiers::NONE
+    };
+    KeyEvent::new(KeyCode::Char(c), modifiers)
+}
+
+/// Parses a key such as `q`, `enter`, `f1` or `ctrl+z`. The modifiers and named keys are
+/// lowercase, while a character key is taken as is.
+fn parse_key(s: &str) -> anyhow::Result<KeyEvent> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = s;
+    loop {
+        if let Some(stripped) = rest.strip_prefix("ctrl+") {
+            modifiers |= KeyModifiers::CONTROL;
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix("alt+") {
+            modifiers |= KeyModifiers::ALT;
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix("shift+") {
+            modifiers |= KeyModifiers::SHIFT;
+            rest = stripped;
+        } else {
+            break;
+        }
+    }
+
+    let mut chars = rest.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        let key_event = char_key(c);
+        return Ok(KeyEvent::new(
+            key_event.code,
+            key_event.modifiers | modifiers,
+        ));
+    }
+    let code = match rest {
+        "backspace" => KeyCode::Backspace,
+        "backtab" => KeyCode::BackTab,
+        "delete" => KeyCode::Delete,
+        "down" => KeyCode::Down,
+        "end" => KeyCode::End,
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "home" => KeyCode::Home,
+        "left" => KeyCode::Left,
+        "pagedown" => KeyCode::PageDown,
+        "pageup" => KeyCode::PageUp,
+        "right" => KeyCode::Right,
+        "space" => KeyCode::Char(' '),
+        "tab" => KeyCode::Tab,
+        "up" => KeyCode::Up,
+        _ => match rest.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+            Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+            _ => anyhow::bail!("unknown key {:?}", s),
+        },
+    };
+
+    Ok(KeyEvent::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_steps_into_input_events_paced_by_ticks() {
+        let script = Script::parse(
+            "# comment\n\nkey ctrl+z\ntype aB\nwait 250\nclick 3 4\n",
+            100,
+        )
+        .unwrap();
+
+        let events: Vec<_> = script
+            .input_events
+            .iter()
+            .map(|ev| format!("{:?}", ev))
+            .collect();
+        let expected: Vec<_> = vec![
+            InputEvent::Key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL)),
+            InputEvent::Tick,
+            InputEvent::Key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE)),
+            InputEvent::Tick,
+            InputEvent::Key(KeyEvent::new(KeyCode::Char('B'), KeyModifiers::SHIFT)),
+            InputEvent::Tick,
+            InputEvent::Tick,
+            InputEvent::Tick,
+            InputEvent::Tick,
+            InputEvent::Mouse(MouseEvent::Up(MouseButton::Left, 3, 4, KeyModifiers::NONE)),
+            InputEvent::Tick,
+        ]
+        .iter()
+        .map(|ev| format!("{:?}", ev))
+        .collect();
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn parses_named_keys() {
+        assert_eq!(
+            parse_key("enter").unwrap(),
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)
+        );
+        assert_eq!(
+            parse_key("alt+1").unwrap(),
+            KeyEvent::new(KeyCode::Char('1'), KeyModifiers::ALT)
+        );
+        assert_eq!(
+            parse_key("f12").unwrap(),
+            KeyEvent::new(KeyCode::F(12), KeyModifiers::NONE)
+        );
+        assert!(parse_key("f13").is_err());
+        assert!(parse_key("ctrl+").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_steps() {
+        assert!(Script::parse("press q", 100).is_err());
+        assert!(Script::parse("click 3", 100).is_err());
+        assert!(Script::parse("wait soon", 100).is_err());
+        assert!(Script::parse("type", 100).is_err());
+    }
+}
@@ -0,0 +1,460 @@
+use crate::{
+    app::{CrossoverPeriods, DateRange, PriceDirection},
+    stock::Stock,
+};
+use chrono::Duration;
+use ta::{
+    indicators::{ExponentialMovingAverage, SimpleMovingAverage},
+    Next,
+};
+use yahoo_finance::Timestamped;
+
+/// Statistics over the latest 52 weeks of the loaded bars.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StockStatistics {
+    pub average_volume: Option<f64>,
+    pub high: f64,
+    pub low: f64,
+    /// Whether the loaded bars cover the whole 52-week window. If not, the statistics only cover
+    /// the loaded range.
+    pub is_full_window: bool,
+    /// The standard deviation of daily returns.
+    pub volatility: Option<f64>,
+}
+
+impl StockStatistics {
+    pub fn from_stock(stock: &Stock) -> Option<Self> {
+        let last_bar = stock.bars.get_max()?;
+        let window_start = last_bar.datetime() - Duration::weeks(52);
+        let bars: Vec<_> = stock
+            .bars
+            .iter()
+            .filter(|bar| bar.datetime() >= window_start)
+            .collect();
+        let first_bar = bars.first()?;
+
+        let high = bars.iter().map(|bar| bar.high).fold(f64::NAN, f64::max);
+        let low = bars.iter().map(|bar| bar.low).fold(f64::NAN, f64::min);
+
+        let volumes: Vec<_> = bars.iter().filter_map(|bar| bar.volume).collect();
+        let average_volume = if volumes.is_empty() {
+            None
+        } else {
+            Some(volumes.iter().sum::<u64>() as f64 / volumes.len() as f64)
+        };
+
+        let closes: Vec<_> = bars.iter().map(|bar| bar.close).collect();
+        let volatility = return_volatility(&closes);
+
+        // allow for the window starting on a non-trading day
+        let is_full_window = first_bar.datetime() - window_start <= Duration::days(4);
+
+        Some(Self {
+            average_volume,
+            high,
+            low,
+            is_full_window,
+            volatility,
+        })
+    }
+}
+
+/// Returns the sample standard deviation of the returns between consecutive closes, if there are
+/// at least two returns.
+fn return_volatility(closes: &[f64]) -> Option<f64> {
+    let returns = returns(closes);
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance =
+        returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    Some(variance.sqrt())
+}
+
+/// Returns the returns between consecutive closes, as fractions.
+fn returns(closes: &[f64]) -> Vec<f64> {
+    closes
+        .windows(2)
+        .filter(|pair| pair[0] != 0f64)
+        .map(|pair| pair[1] / pair[0] - 1f64)
+        .collect()
+}
+
+/// The returns between consecutive closes counted in buckets of equal width, from the lowest return
+/// to the highest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReturnHistogram {
+    /// The return at the low end of the first bucket, in percent.
+    pub low: f64,
+    /// The width of the buckets, in percent.
+    pub bucket_width: f64,
+    pub counts: Vec<usize>,
+}
+
+impl ReturnHistogram {
+    /// Returns the histogram of the returns, in a single bucket if they are all the same.
+    pub fn from_closes(closes: &[f64], bucket_count: usize) -> Option<Self> {
+        let returns: Vec<_> = returns(closes).iter().map(|r| r * 100f64).collect();
+        if returns.is_empty() || bucket_count == 0 {
+            return None;
+        }
+
+        let low = returns.iter().copied().fold(f64::NAN, f64::min);
+        let high = returns.iter().copied().fold(f64::NAN, f64::max);
+        if high <= low {
+            return Some(Self {
+                low,
+                bucket_width: 0f64,
+                counts: vec![returns.len()],
+            });
+        }
+
+        let bucket_width = (high - low) / bucket_count as f64;
+        let mut counts = vec![0; bucket_count];
+        for r in returns {
+            // the highest return closes the last bucket
+            let i = ((r - low) / bucket_width) as usize;
+            counts[i.min(bucket_count - 1)] += 1;
+        }
+
+        Some(Self {
+            low,
+            bucket_width,
+            counts,
+        })
+    }
+
+    /// Returns the return at the low end of the bucket, in percent.
+    pub fn bucket_low(&self, i: usize) -> f64 {
+        self.low + i as f64 * self.bucket_width
+    }
+}
+
+/// The latest price and its change over a date range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PriceChange {
+    pub change: f64,
+    pub change_percent: f64,
+    pub last: f64,
+    pub volume: Option<u64>,
+}
+
+impl PriceChange {
+    pub fn from_stock(stock: &Stock, date_range: Option<&DateRange>) -> Option<Self> {
+        let mut bars = stock.bars.iter().filter(|bar| {
+            date_range.map_or(true, |date_range| date_range.contains(&bar.datetime()))
+        });
+        let first_bar = bars.next()?;
+        let last_bar = bars.last().unwrap_or(first_bar);
+
+        let change = last_bar.close - first_bar.close;
+        let change_percent = if first_bar.close != 0f64 {
+            change / first_bar.close * 100f64
+        } else {
+            0f64
+        };
+
+        Some(Self {
+            change,
+            change_percent,
+            last: last_bar.close,
+            volume: last_bar.volume,
+        })
+    }
+
+    /// Returns the change of the last close from the previous close, as shown by quote screens,
+    /// regardless of the date range of the bars.
+    pub fn daily(stock: &Stock) -> Option<Self> {
+        let mut bars = stock.bars.iter().rev();
+        let last_bar = bars.next()?;
+        let previous_bar = bars.next()?;
+
+        let change = last_bar.close - previous_bar.close;
+        let change_percent = if previous_bar.close != 0f64 {
+            change / previous_bar.close * 100f64
+        } else {
+            0f64
+        };
+
+        Some(Self {
+            change,
+            change_percent,
+            last: last_bar.close,
+            volume: last_bar.volume,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CrossoverKind {
+    /// The fast moving average crosses below the slow one.
+    Death,
+    /// The fast moving average crosses above the slow one.
+    Golden,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CrossoverSignal {
+    pub kind: CrossoverKind,
+    pub price: f64,
+    pub timestamp: f64,
+}
+
+/// The fast and slow simple moving averages of the loaded bars, aligned to start once the slow
+/// moving average has a full window, and the bars where they cross.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MovingAverageCrossovers {
+    pub fast_data: Vec<(f64, f64)>,
+    pub signals: Vec<CrossoverSignal>,
+    pub slow_data: Vec<(f64, f64)>,
+}
+
+impl MovingAverageCrossovers {
+    pub fn from_stock(stock: &Stock, periods: CrossoverPeriods) -> Self {
+        let mut fast_sma = SimpleMovingAverage::new(periods.fast as u32).unwrap();
+        let mut slow_sma = SimpleMovingAverage::new(periods.slow as u32).unwrap();
+
+        let mut crossovers = Self::default();
+        let mut last_difference: Option<f64> = None;
+        for (i, bar) in stock.bars.iter().enumerate() {
+            let fast = fast_sma.next(bar.close);
+            let slow = slow_sma.next(bar.close);
+            if i + 1 < periods.slow as usize {
+                continue;
+            }
+
+            let timestamp = bar.timestamp_seconds() as f64;
+            crossovers.fast_data.push((timestamp, fast));
+            crossovers.slow_data.push((timestamp, slow));
+
+            // a crossing is a sign change in the difference, skipping bars where the averages touch
+            let difference = fast - slow;
+            let kind = match last_difference {
+                Some(last_difference) if last_difference < 0f64 && difference > 0f64 => {
+                    Some(CrossoverKind::Golden)
+                }
+                Some(last_difference) if last_difference > 0f64 && difference < 0f64 => {
+                    Some(CrossoverKind::Death)
+                }
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                crossovers.signals.push(CrossoverSignal {
+                    kind,
+                    price: fast,
+                    timestamp,
+                });
+            }
+            if difference != 0f64 {
+                last_difference = Some(difference);
+            }
+        }
+
+        crossovers
+    }
+}
+
+/// The change between the closes of two bars, as measured with the measure tool.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PriceMeasurement {
+    pub change: f64,
+    pub change_percent: f64,
+    /// Number of bars from the start bar to the end bar.
+    pub bars: usize,
+    /// Number of calendar days from the start bar to the end bar.
+    pub days: i64,
+}
+
+impl PriceMeasurement {
+    /// Measures from the earlier to the later of the bars nearest to the timestamps, in seconds.
+    pub fn between(stock: &Stock, timestamps: (f64, f64)) -> Option<Self> {
+        let bars: Vec<_> = stock.bars.iter().collect();
+        let nearest = |timestamp: f64| {
+            (0..bars.len())
+                .min_by_key(|&i| (bars[i].timestamp_seconds() as i64 - timestamp as i64).abs())
+        };
+        let (i, j) = (nearest(timestamps.0)?, nearest(timestamps.1)?);
+        let (start_bar, end_bar) = (bars[i.min(j)], bars[i.max(j)]);
+        if start_bar.close == 0f64 {
+            return None;
+        }
+
+        Some(Self {
+            change: end_bar.close - start_bar.close,
+            change_percent: (end_bar.close / start_bar.close - 1f64) * 100f64,
+            bars: i.max(j) - i.min(j),
+            days: (end_bar.datetime() - start_bar.datetime()).num_days(),
+        })
+    }
+}
+
+/// A bar opening away from the close of the previous bar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PriceGap {
+    pub direction: PriceDirection,
+    pub open: f64,
+    pub previous_close: f64,
+    pub timestamp: f64,
+}
+
+impl PriceGap {
+    /// Returns the gaps of at least the threshold percent of the previous close between adjacent
+    /// bars.
+    pub fn from_stock(stock: &Stock, threshold_percent: f64) -> Vec<Self> {
+        let bars: Vec<_> = stock.bars.iter().collect();
+        bars.windows(2)
+            .filter(|pair| pair[0].close != 0f64)
+            .filter_map(|pair| {
+                let (previous_bar, bar) = (pair[0], pair[1]);
+                let gap_percent = (bar.open - previous_bar.close) / previous_bar.close * 100f64;
+                if gap_percent.abs() < threshold_percent {
+                    return None;
+                }
+
+                Some(Self {
+                    direction: if gap_percent > 0f64 {
+                        PriceDirection::Up
+                    } else {
+                        PriceDirection::Down
+                    },
+                    open: bar.open,
+                    previous_close: previous_bar.close,
+                    timestamp: bar.timestamp_seconds() as f64,
+                })
+            })
+            .collect()
+    }
+}
+
+/// An exponential moving average of the volume, smoothing out spikes of single bars.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SmoothedVolume {
+    pub timestamp: f64,
+    pub volume: f64,
+}
+
+impl SmoothedVolume {
+    /// Returns the average at every bar with a volume, once the period is filled.
+    pub fn from_stock(stock: &Stock, period: u16) -> Vec<Self> {
+        let mut ema = ExponentialMovingAverage::new(period as u32).unwrap();
+        stock
+            .bars
+            .iter()
+            .filter_map(|bar| Some((bar.timestamp_seconds() as f64, bar.volume? as f64)))
+            .enumerate()
+            .map(|(i, (timestamp, volume))| (i, timestamp, ema.next(volume)))
+            .filter(|&(i, _, _)| i + 1 >= period as usize)
+            .map(|(_, timestamp, volume)| Self { timestamp, volume })
+            .collect()
+    }
+}
+
+/// The range the price is expected to keep within from the last close, widening with the square
+/// root of the number of bars ahead at the volatility of the returns over a lookback.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VolatilityCone {
+    pub last_close: f64,
+    pub timestamp: f64,
+    /// The standard deviation of the returns per bar.
+    pub volatility: f64,
+}
+
+impl VolatilityCone {
+    pub fn from_stock(stock: &Stock, lookback: u16) -> Option<Self> {
+        let closes: Vec<_> = stock.bars.iter().map(|bar| bar.close).collect();
+        let closes = &closes[closes.len().saturating_sub(lookback as usize + 1)..];
+        let last_bar = stock.bars.get_max()?;
+
+        Some(Self {
+            last_close: last_bar.close,
+            timestamp: last_bar.timestamp_seconds() as f64,
+            volatility: return_volatility(closes)?,
+        })
+    }
+
+    /// Returns the lower and upper bounds of the given number of standard deviations, the given
+    /// number of bars ahead. The returns are taken as log-normal, so the lower bound stays above
+    /// zero.
+    pub fn bounds(&self, deviations: f64, bars_ahead: f64) -> (f64, f64) {
+        let spread = deviations * self.volatility * bars_ahead.sqrt();
+        (
+            self.last_close * (-spread).exp(),
+            self.last_close * spread.exp(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::stock_fixture;
+
+    #[test]
+    fn daily_change_is_from_the_previous_close() {
+        let price_change = PriceChange::daily(&stock_fixture()).unwrap();
+
+        assert_eq!(price_change.last, 116f64);
+        assert_eq!(price_change.change, 2f64);
+    }
+
+    #[test]
+    fn daily_change_needs_two_bars() {
+        let stock = Stock {
+            bars: stock_fixture().bars.into_iter().take(1).collect(),
+            ..stock_fixture()
+        };
+
+        assert!(PriceChange::daily(&stock).is_none());
+    }
+
+    #[test]
+    fn volatility_cone_widens_with_the_square_root_of_time() {
+        let cone = VolatilityCone {
+            last_close: 100f64,
+            timestamp: 0f64,
+            volatility: 0.01,
+        };
+
+        assert_eq!(cone.bounds(1f64, 0f64), (100f64, 100f64));
+        let (lower, upper) = cone.bounds(2f64, 4f64);
+        assert!((upper - 100f64 * 0.04f64.exp()).abs() < 1e-9);
+        assert!((lower - 100f64 * (-0.04f64).exp()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn measures_from_the_earlier_bar_to_the_later() {
+        let stock = stock_fixture();
+        let bars: Vec<_> = stock.bars.iter().collect();
+        // a little off the bars, which the anchors are snapped to
+        let timestamps = (
+            bars[5].timestamp_seconds() as f64 + 3_600f64,
+            bars[2].timestamp_seconds() as f64 - 3_600f64,
+        );
+        let measurement = PriceMeasurement::between(&stock, timestamps).unwrap();
+
+        assert_eq!(measurement.change, bars[5].close - bars[2].close);
+        assert_eq!(measurement.bars, 3);
+        assert_eq!(measurement.days, 3);
+    }
+
+    #[test]
+    fn return_histogram_spans_the_lowest_to_the_highest_return() {
+        let histogram = ReturnHistogram::from_closes(&[100f64, 110f64, 99f64, 108.9], 2).unwrap();
+
+        assert!((histogram.low + 10f64).abs() < 1e-9);
+        assert!((histogram.bucket_width - 10f64).abs() < 1e-9);
+        assert_eq!(histogram.counts, vec![1, 2]);
+
+        let histogram = ReturnHistogram::from_closes(&[100f64, 100f64, 100f64], 4).unwrap();
+        assert_eq!(histogram.counts, vec![2]);
+        assert!(ReturnHistogram::from_closes(&[100f64], 4).is_none());
+    }
+
+    #[test]
+    fn return_volatility_needs_two_returns() {
+        assert!(return_volatility(&[100f64, 101f64]).is_none());
+        assert_eq!(return_volatility(&[100f64, 110f64, 121f64]), Some(0f64));
+    }
+}
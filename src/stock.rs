@@ -1,46 +1,267 @@
 use crate::{
-    app::{Indicator, TimeFrame},
+    app::{CrossoverPeriods, DateRange, Indicator, TimeFrame},
+    data_source::{DataSource, RateLimitError},
+    indicator::ComputeIndicator,
+    market::Exchange,
     reactive::StreamExt,
 };
-use chrono::{DateTime, Duration, TimeZone, Utc};
-use futures::executor;
+use chrono::{Date, DateTime, Datelike, Duration, TimeZone, Utc};
 use gcollections::ops::{Bounded, Difference, Union};
 use im::{hashmap, ordset, HashMap, OrdSet};
 use interval::interval_set::{IntervalSet, ToIntervalSet};
+use itertools::Itertools;
+use log::warn;
 use reactive_rs::Stream;
 use std::{cell::RefCell, ops::Range, rc::Rc};
-use yahoo_finance::{history, Bar, Profile, Quote, Timestamped};
+use yahoo_finance::{Bar, Timestamped};
+
+/// Fetch error shown while the data source is rate limiting, set apart from the other failures.
+pub const RATE_LIMITED_ERROR: &str = "Rate limited by the data source, slow down";
 
 #[derive(Clone, Debug, Default)]
 pub struct Stock {
     pub bars: OrdSet<Bar>,
-    pub profile: Option<Profile>,
-    pub quote: Option<Quote>,
+    /// Dates of earnings reports, past and upcoming, oldest first.
+    pub earnings_dates: Vec<DateTime<Utc>>,
+    pub events: Vec<CorporateEvent>,
+    /// Error of the failed fetch of the profile or the bars, shown in place of the chart.
+    pub fetch_error: Option<String>,
+    pub profile: Option<StockProfile>,
+    pub quote: Option<StockQuote>,
     pub symbol: String,
 }
 
 impl Stock {
     pub fn name(&self) -> Option<&str> {
-        match &self.profile {
-            Some(Profile::Company(company)) => Some(company.name.as_str()),
-            Some(Profile::Fund(fund)) => Some(fund.name.as_str()),
-            None => None,
+        self.profile.as_ref().map(|profile| profile.name.as_str())
+    }
+
+    /// The exchange of the profile, or else the one resolved from the suffix of the symbol.
+    pub fn exchange(&self) -> Exchange {
+        self.profile
+            .as_ref()
+            .and_then(|profile| profile.exchange)
+            .unwrap_or_else(|| Exchange::from_symbol(&self.symbol))
+    }
+
+    /// Whether nothing has been fetched for the stock yet, not even an error.
+    pub fn is_loading(&self) -> bool {
+        self.bars.is_empty() && self.profile.is_none() && self.fetch_error.is_none()
+    }
+
+    /// Returns the stock with the prices before each dividend scaled down by the dividend yield, the
+    /// same way as Yahoo's adjusted close. The prices are assumed to be adjusted for splits already.
+    pub fn adjusted(&self) -> Self {
+        let dividend_factors: Vec<_> = self
+            .events
+            .iter()
+            .filter_map(|event| {
+                let amount = match event.kind {
+                    CorporateEventKind::Dividend { amount } => amount,
+                    CorporateEventKind::Split { .. } => return None,
+                };
+                let ex_date = event.datetime.date();
+                // the yield is relative to the close before the ex-date, which may not be loaded
+                let previous_bar = self
+                    .bars
+                    .iter()
+                    .rev()
+                    .find(|bar| bar.datetime().date() < ex_date)
+                    .filter(|bar| ex_date - bar.datetime().date() <= Duration::days(7))?;
+                if previous_bar.close == 0f64 {
+                    return None;
+                }
+                Some((ex_date, 1f64 - amount / previous_bar.close))
+            })
+            .collect();
+
+        let bars = self
+            .bars
+            .iter()
+            .map(|bar| {
+                let factor: f64 = dividend_factors
+                    .iter()
+                    .filter(|(ex_date, _)| bar.datetime().date() < *ex_date)
+                    .map(|(_, factor)| factor)
+                    .product();
+                Bar {
+                    open: bar.open * factor,
+                    high: bar.high * factor,
+                    low: bar.low * factor,
+                    close: bar.close * factor,
+                    ..bar.clone()
+                }
+            })
+            .collect();
+
+        Self {
+            bars,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the stock with the bars rolled up into weekly bars, or into monthly bars if there
+    /// would still be more than the maximum, so that long histories stay fast to draw.
+    pub fn downsampled(&self, max_bar_count: usize) -> Self {
+        if self.bars.len() <= max_bar_count {
+            return self.clone();
+        }
+
+        let weekly_bars = roll_up_bars(&self.bars, |date| {
+            let week = date.iso_week();
+            (week.year(), week.week())
+        });
+        let bars = if weekly_bars.len() <= max_bar_count {
+            weekly_bars
+        } else {
+            roll_up_bars(&self.bars, |date| (date.year(), date.month()))
+        };
+
+        Self {
+            bars,
+            ..self.clone()
         }
     }
 }
 
-pub fn to_stock_profiles<'a, S>(stock_symbols: S) -> ToStockProfiles<S>
+/// Rolls up the consecutive bars of the same period into a bar dated by the first of them.
+fn roll_up_bars<K, F>(bars: &OrdSet<Bar>, mut period_func: F) -> OrdSet<Bar>
+where
+    K: PartialEq,
+    F: FnMut(Date<Utc>) -> K,
+{
+    bars.iter()
+        .group_by(|bar| period_func(bar.datetime().date()))
+        .into_iter()
+        .map(|(_, period_bars)| {
+            let period_bars: Vec<_> = period_bars.collect();
+            let first_bar = period_bars[0];
+            let last_bar = period_bars[period_bars.len() - 1];
+
+            Bar {
+                timestamp: first_bar.timestamp,
+                open: first_bar.open,
+                high: period_bars
+                    .iter()
+                    .map(|bar| bar.high)
+                    .fold(f64::NEG_INFINITY, f64::max),
+                low: period_bars
+                    .iter()
+                    .map(|bar| bar.low)
+                    .fold(f64::INFINITY, f64::min),
+                close: last_bar.close,
+                volume: period_bars
+                    .iter()
+                    .filter_map(|bar| bar.volume)
+                    .fold(None, |acc, volume| Some(acc.unwrap_or(0) + volume)),
+            }
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct StockProfile {
+    /// Exchange the stock is listed on, if the data source tells it.
+    pub exchange: Option<Exchange>,
+    pub name: String,
+}
+
+/// Latest quote of a stock, with only the fields that the data source provides.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StockQuote {
+    pub ask: Option<f64>,
+    pub bid: Option<f64>,
+    pub day_high: Option<f64>,
+    pub day_low: Option<f64>,
+}
+
+/// Dividend or split, dated by its ex-date.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CorporateEvent {
+    pub datetime: DateTime<Utc>,
+    pub kind: CorporateEventKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CorporateEventKind {
+    Dividend { amount: f64 },
+    Split { denominator: f64, numerator: f64 },
+}
+
+impl CorporateEvent {
+    /// Returns the letter marking the event on the chart.
+    pub fn marker(&self) -> char {
+        match self.kind {
+            CorporateEventKind::Dividend { .. } => 'D',
+            CorporateEventKind::Split { .. } => 'S',
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NewsHeadline {
+    pub link: Option<String>,
+    pub published: DateTime<Utc>,
+    pub publisher: String,
+    pub title: String,
+}
+
+/// Returns the number of days before the start of a date range needed to compute the indicator and
+/// the crossover moving averages from the start of the date range.
+fn lookback_days(indicator: Option<Indicator>, crossover_periods: Option<CrossoverPeriods>) -> i64 {
+    let indicator_lookback_days = indicator.map_or(0, |indicator| indicator.lookback() as i64 - 1);
+    // the crossings are only marked once the slow moving average has a full window of bars, so
+    // convert its period from trading days to calendar days, allowing a week for holidays
+    let crossover_lookback_days = crossover_periods.map_or(0, |crossover_periods| {
+        (crossover_periods.slow as i64 - 1) * 7 / 5 + 7
+    });
+
+    indicator_lookback_days.max(crossover_lookback_days)
+}
+
+/// Fetches the profile and the bars within the date range of a stock, including the bars needed to
+/// compute the indicator and the crossover moving averages from the start of the date range.
+pub fn fetch_stock(
+    data_source: &dyn DataSource,
+    symbol: &str,
+    date_range: Option<&DateRange>,
+    indicator: Option<Indicator>,
+    crossover_periods: Option<CrossoverPeriods>,
+) -> anyhow::Result<Stock> {
+    let profile = data_source.fetch_profile(symbol)?;
+
+    let lookback_days = lookback_days(indicator, crossover_periods);
+    let fetch_date_range = date_range
+        .map(|date_range| (date_range.start - Duration::days(lookback_days))..date_range.end);
+    let bars = data_source.fetch_bars(symbol, fetch_date_range.as_ref())?;
+
+    Ok(Stock {
+        bars: OrdSet::from(bars),
+        profile: Some(profile),
+        symbol: symbol.to_owned(),
+        ..Stock::default()
+    })
+}
+
+pub fn to_stock_profiles<'a, S>(
+    stock_symbols: S,
+    data_source: Rc<dyn DataSource>,
+) -> ToStockProfiles<S>
 where
     S: Stream<'a, Item = String>,
 {
     ToStockProfiles {
+        data_source,
         stock_profile_map: Rc::new(RefCell::new(hashmap! {})),
         stock_symbols,
     }
 }
 
 pub struct ToStockProfiles<S> {
-    stock_profile_map: Rc<RefCell<HashMap<String, Profile>>>,
+    data_source: Rc<dyn DataSource>,
+    /// The profiles fetched by symbol, kept until the app quits without expiring, so that a symbol
+    /// is only fetched again after a failure.
+    stock_profile_map: Rc<RefCell<HashMap<String, StockProfile>>>,
     stock_symbols: S,
 }
 
@@ -49,12 +270,15 @@ where
     S: Stream<'a, Item = String>,
 {
     type Context = S::Context;
-    type Item = Profile;
+    /// The profile, or the error of the failed fetch, which is retried on the next change of the
+    /// symbol.
+    type Item = Result<StockProfile, String>;
 
     fn subscribe_ctx<O>(self, mut observer: O)
     where
         O: 'a + FnMut(&Self::Context, &Self::Item),
     {
+        let data_source = self.data_source.clone();
         let stock_profile_map = self.stock_profile_map.clone();
         self.stock_symbols
             .distinct_until_changed()
@@ -63,24 +287,251 @@ where
                     let stock_profile_map = stock_profile_map.borrow();
                     stock_profile_map.get(stock_symbol).cloned()
                 };
-                let profile = profile.unwrap_or_else(|| {
-                    let profile = executor::block_on(Profile::load(stock_symbol.as_str()))
-                        .expect("profile load failed");
-                    let mut stock_profile_map = stock_profile_map.borrow_mut();
-                    stock_profile_map.insert(stock_symbol.clone(), profile.clone());
-                    profile
-                });
+                let profile = profile.map_or_else(
+                    || {
+                        let profile = data_source.fetch_profile(stock_symbol).map_err(|err| {
+                            warn!("profile retrieval failed for {}: {}", stock_symbol, err);
+                            if err.is::<RateLimitError>() {
+                                RATE_LIMITED_ERROR.to_owned()
+                            } else {
+                                format!("Profile retrieval failed: {}", err)
+                            }
+                        })?;
+                        let mut stock_profile_map = stock_profile_map.borrow_mut();
+                        stock_profile_map.insert(stock_symbol.clone(), profile.clone());
+                        Ok(profile)
+                    },
+                    Ok,
+                );
 
                 observer(ctx, &profile);
             });
     }
 }
 
+pub fn to_stock_earnings_dates<'a, S>(
+    stock_symbols: S,
+    data_source: Rc<dyn DataSource>,
+) -> ToStockEarningsDates<S>
+where
+    S: Stream<'a, Item = String>,
+{
+    ToStockEarningsDates {
+        data_source,
+        stock_earnings_dates_map: Rc::new(RefCell::new(hashmap! {})),
+        stock_symbols,
+    }
+}
+
+pub struct ToStockEarningsDates<S> {
+    data_source: Rc<dyn DataSource>,
+    stock_earnings_dates_map: Rc<RefCell<HashMap<String, Vec<DateTime<Utc>>>>>,
+    stock_symbols: S,
+}
+
+impl<'a, S> Stream<'a> for ToStockEarningsDates<S>
+where
+    S: Stream<'a, Item = String>,
+{
+    type Context = S::Context;
+    type Item = Vec<DateTime<Utc>>;
+
+    fn subscribe_ctx<O>(self, mut observer: O)
+    where
+        O: 'a + FnMut(&Self::Context, &Self::Item),
+    {
+        let data_source = self.data_source.clone();
+        let stock_earnings_dates_map = self.stock_earnings_dates_map.clone();
+        self.stock_symbols
+            .distinct_until_changed()
+            .subscribe_ctx(move |ctx, stock_symbol| {
+                let earnings_dates = {
+                    let stock_earnings_dates_map = stock_earnings_dates_map.borrow();
+                    stock_earnings_dates_map.get(stock_symbol).cloned()
+                };
+                let earnings_dates = earnings_dates.unwrap_or_else(|| {
+                    // like the events, the earnings dates are not essential
+                    let earnings_dates = data_source
+                        .fetch_earnings_dates(stock_symbol)
+                        .unwrap_or_else(|err| {
+                            warn!(
+                                "earnings dates retrieval failed for {}: {}",
+                                stock_symbol, err
+                            );
+                            vec![]
+                        });
+                    let mut stock_earnings_dates_map = stock_earnings_dates_map.borrow_mut();
+                    stock_earnings_dates_map.insert(stock_symbol.clone(), earnings_dates.clone());
+                    earnings_dates
+                });
+
+                observer(ctx, &earnings_dates);
+            });
+    }
+}
+
+pub fn to_stock_events<'a, S>(stock_symbols: S, data_source: Rc<dyn DataSource>) -> ToStockEvents<S>
+where
+    S: Stream<'a, Item = String>,
+{
+    ToStockEvents {
+        data_source,
+        stock_events_map: Rc::new(RefCell::new(hashmap! {})),
+        stock_symbols,
+    }
+}
+
+pub struct ToStockEvents<S> {
+    data_source: Rc<dyn DataSource>,
+    stock_events_map: Rc<RefCell<HashMap<String, Vec<CorporateEvent>>>>,
+    stock_symbols: S,
+}
+
+impl<'a, S> Stream<'a> for ToStockEvents<S>
+where
+    S: Stream<'a, Item = String>,
+{
+    type Context = S::Context;
+    type Item = Vec<CorporateEvent>;
+
+    fn subscribe_ctx<O>(self, mut observer: O)
+    where
+        O: 'a + FnMut(&Self::Context, &Self::Item),
+    {
+        let data_source = self.data_source.clone();
+        let stock_events_map = self.stock_events_map.clone();
+        self.stock_symbols
+            .distinct_until_changed()
+            .subscribe_ctx(move |ctx, stock_symbol| {
+                let events = {
+                    let stock_events_map = stock_events_map.borrow();
+                    stock_events_map.get(stock_symbol).cloned()
+                };
+                let events = events.unwrap_or_else(|| {
+                    // the events are not essential, so a failed fetch only leaves them empty
+                    let events = data_source
+                        .fetch_events(stock_symbol)
+                        .unwrap_or_else(|err| {
+                            warn!("events retrieval failed for {}: {}", stock_symbol, err);
+                            vec![]
+                        });
+                    let mut stock_events_map = stock_events_map.borrow_mut();
+                    stock_events_map.insert(stock_symbol.clone(), events.clone());
+                    events
+                });
+
+                observer(ctx, &events);
+            });
+    }
+}
+
+pub fn to_stock_news<'a, S>(stock_symbols: S, data_source: Rc<dyn DataSource>) -> ToStockNews<S>
+where
+    S: Stream<'a, Item = String>,
+{
+    ToStockNews {
+        data_source,
+        stock_news_map: Rc::new(RefCell::new(hashmap! {})),
+        stock_symbols,
+    }
+}
+
+pub struct ToStockNews<S> {
+    data_source: Rc<dyn DataSource>,
+    stock_news_map: Rc<RefCell<HashMap<String, Vec<NewsHeadline>>>>,
+    stock_symbols: S,
+}
+
+impl<'a, S> Stream<'a> for ToStockNews<S>
+where
+    S: Stream<'a, Item = String>,
+{
+    type Context = S::Context;
+    type Item = Vec<NewsHeadline>;
+
+    fn subscribe_ctx<O>(self, mut observer: O)
+    where
+        O: 'a + FnMut(&Self::Context, &Self::Item),
+    {
+        let data_source = self.data_source.clone();
+        let stock_news_map = self.stock_news_map.clone();
+        self.stock_symbols
+            .distinct_until_changed()
+            .subscribe_ctx(move |ctx, stock_symbol| {
+                let news = {
+                    let stock_news_map = stock_news_map.borrow();
+                    stock_news_map.get(stock_symbol).cloned()
+                };
+                let news = news.unwrap_or_else(|| {
+                    match data_source.fetch_news(stock_symbol) {
+                        Ok(news) => {
+                            let mut stock_news_map = stock_news_map.borrow_mut();
+                            stock_news_map.insert(stock_symbol.clone(), news.clone());
+                            news
+                        }
+                        // The headlines are not essential, so a failed fetch only leaves them
+                        // empty, to be fetched again next time.
+                        Err(err) => {
+                            warn!("news retrieval failed for {}: {}", stock_symbol, err);
+                            vec![]
+                        }
+                    }
+                });
+
+                observer(ctx, &news);
+            });
+    }
+}
+
+pub fn to_stock_quotes<'a, S>(stock_symbols: S, data_source: Rc<dyn DataSource>) -> ToStockQuotes<S>
+where
+    S: Stream<'a, Item = String>,
+{
+    ToStockQuotes {
+        data_source,
+        stock_symbols,
+    }
+}
+
+pub struct ToStockQuotes<S> {
+    data_source: Rc<dyn DataSource>,
+    stock_symbols: S,
+}
+
+impl<'a, S> Stream<'a> for ToStockQuotes<S>
+where
+    S: Stream<'a, Item = String>,
+{
+    type Context = S::Context;
+    type Item = Option<StockQuote>;
+
+    fn subscribe_ctx<O>(self, mut observer: O)
+    where
+        O: 'a + FnMut(&Self::Context, &Self::Item),
+    {
+        let data_source = self.data_source.clone();
+        // quotes go stale, so unlike the profile they are fetched again on every change of symbol
+        self.stock_symbols
+            .distinct_until_changed()
+            .subscribe_ctx(move |ctx, stock_symbol| {
+                // the quote is not essential, so a failed fetch only leaves it out
+                let quote = data_source.fetch_quote(stock_symbol).unwrap_or_else(|err| {
+                    warn!("quote retrieval failed for {}: {}", stock_symbol, err);
+                    None
+                });
+
+                observer(ctx, &quote);
+            });
+    }
+}
+
 pub fn to_stock_bar_sets<'a, S, U, R, V>(
     stock_symbols: S,
     time_frames: U,
     date_ranges: R,
     indicators: V,
+    crossover_periods: Option<CrossoverPeriods>,
+    data_source: Rc<dyn DataSource>,
 ) -> ToStockBarSets<S, U, R, V>
 where
     S: Stream<'a, Item = String>,
@@ -89,6 +540,8 @@ where
     V: Stream<'a, Item = Option<Indicator>>,
 {
     ToStockBarSets {
+        crossover_periods,
+        data_source,
         date_ranges,
         indicators,
         stock_bars_map: Rc::new(RefCell::new(hashmap! {})),
@@ -101,8 +554,12 @@ type DateRangeIntervalSet = IntervalSet<i64>;
 type BarCoverageHashMap = HashMap<String, (OrdSet<Bar>, DateRangeIntervalSet)>;
 
 pub struct ToStockBarSets<S, U, R, V> {
+    crossover_periods: Option<CrossoverPeriods>,
+    data_source: Rc<dyn DataSource>,
     date_ranges: R,
     indicators: V,
+    /// The bars fetched by symbol with the date ranges they cover, kept until the app quits, so
+    /// that only the uncovered part of a date range is fetched.
     stock_bars_map: Rc<RefCell<BarCoverageHashMap>>,
     stock_symbols: S,
     time_frames: U,
@@ -117,12 +574,15 @@ where
     C: 'a + Clone + Sized,
 {
     type Context = C;
-    type Item = OrdSet<Bar>;
+    /// The bars, or the error of the failed fetch, leaving the date range to be fetched again.
+    type Item = Result<OrdSet<Bar>, String>;
 
     fn subscribe_ctx<O>(self, mut observer: O)
     where
         O: 'a + FnMut(&Self::Context, &Self::Item),
     {
+        let crossover_periods = self.crossover_periods;
+        let data_source = self.data_source.clone();
         let stock_bars_map = self.stock_bars_map.clone();
         self.stock_symbols
             .distinct_until_changed()
@@ -147,78 +607,70 @@ where
                     )
                 },
             )
-            .subscribe_ctx(
-                move |ctx, (stock_symbol, time_frame, date_range, indicator)| {
-                    let (stock_bar_set, covered_date_ranges) = {
-                        let stock_bars_map = stock_bars_map.borrow();
-                        stock_bars_map
-                            .get(stock_symbol)
-                            .cloned()
-                            .unwrap_or((ordset![], vec![].to_interval_set()))
-                    };
+            .subscribe_ctx(move |ctx, (stock_symbol, _, date_range, indicator)| {
+                let (stock_bar_set, covered_date_ranges) = {
+                    let stock_bars_map = stock_bars_map.borrow();
+                    stock_bars_map
+                        .get(stock_symbol)
+                        .cloned()
+                        .unwrap_or((ordset![], vec![].to_interval_set()))
+                };
 
-                    let (stock_bar_set, covered_date_ranges) = if let Some(date_range) = date_range
-                    {
-                        let uncovered_date_ranges = (
-                            date_range.start.timestamp(),
-                            (date_range.end - Duration::seconds(1)).timestamp(),
+                let (stock_bar_set, covered_date_ranges) = if let Some(date_range) = date_range {
+                    let uncovered_date_ranges = (
+                        date_range.start.timestamp(),
+                        (date_range.end - Duration::seconds(1)).timestamp(),
+                    )
+                        .to_interval_set();
+                    let lookback_days = lookback_days(*indicator, crossover_periods);
+                    let uncovered_date_ranges = if lookback_days > 0 {
+                        uncovered_date_ranges.union(
+                            &(
+                                (date_range.start - Duration::days(lookback_days)).timestamp(),
+                                (date_range.start - Duration::seconds(1)).timestamp(),
+                            )
+                                .to_interval_set(),
                         )
-                            .to_interval_set();
-                        let uncovered_date_ranges = match indicator {
-                            Some(Indicator::BollingerBands(n, _)) => uncovered_date_ranges.union(
-                                &(
-                                    (date_range.start - Duration::days(**n as i64 - 1)).timestamp(),
-                                    (date_range.start - Duration::seconds(1)).timestamp(),
-                                )
-                                    .to_interval_set(),
-                            ),
-                            Some(Indicator::ExponentialMovingAverage(n)) => uncovered_date_ranges
-                                .union(
-                                    &(
-                                        (date_range.start - Duration::days(**n as i64 - 1))
-                                            .timestamp(),
-                                        (date_range.start - Duration::seconds(1)).timestamp(),
-                                    )
-                                        .to_interval_set(),
-                                ),
-                            Some(Indicator::SimpleMovingAverage(n)) => uncovered_date_ranges.union(
-                                &(
-                                    (date_range.start - Duration::days(**n as i64 - 1)).timestamp(),
-                                    (date_range.start - Duration::seconds(1)).timestamp(),
-                                )
-                                    .to_interval_set(),
+                    } else {
+                        uncovered_date_ranges
+                    };
+                    let uncovered_date_ranges =
+                        uncovered_date_ranges.difference(&covered_date_ranges);
+
+                    let mut covered_date_ranges = covered_date_ranges;
+                    let mut stock_bar_set = stock_bar_set;
+                    for uncovered_date_range in uncovered_date_ranges {
+                        let bars = match data_source.fetch_bars(
+                            stock_symbol,
+                            Some(
+                                &(Utc.timestamp(uncovered_date_range.lower(), 0)
+                                    ..Utc.timestamp(uncovered_date_range.upper(), 0)),
                             ),
-                            None => uncovered_date_ranges,
+                        ) {
+                            Ok(bars) => bars,
+                            Err(err) => {
+                                observer(ctx, &Err(fetch_bars_error(stock_symbol, err)));
+                                return;
+                            }
                         };
-                        let uncovered_date_ranges =
-                            uncovered_date_ranges.difference(&covered_date_ranges);
-
-                        let mut covered_date_ranges = covered_date_ranges;
-                        let mut stock_bar_set = stock_bar_set;
-                        for uncovered_date_range in uncovered_date_ranges {
-                            let bars = executor::block_on(history::retrieve_range(
-                                stock_symbol.as_str(),
-                                Utc.timestamp(uncovered_date_range.lower(), 0),
-                                Some(Utc.timestamp(uncovered_date_range.upper(), 0)),
-                            ))
-                            .expect("historical prices retrieval failed");
-                            covered_date_ranges = covered_date_ranges.union(
-                                &(uncovered_date_range.lower(), uncovered_date_range.upper())
-                                    .to_interval_set(),
-                            );
-                            stock_bar_set = stock_bar_set + OrdSet::from(bars);
-                        }
+                        covered_date_ranges = covered_date_ranges.union(
+                            &(uncovered_date_range.lower(), uncovered_date_range.upper())
+                                .to_interval_set(),
+                        );
+                        stock_bar_set = stock_bar_set + OrdSet::from(bars);
+                    }
 
-                        (stock_bar_set, covered_date_ranges)
-                    } else {
-                        let bars = executor::block_on(history::retrieve_interval(
-                            stock_symbol.as_str(),
-                            time_frame.interval(),
-                        ))
-                        .expect("historical prices retrieval failed");
-                        let covered_date_ranges = if let (Some(first_bar), Some(last_bar)) =
-                            (bars.first(), bars.last())
-                        {
+                    (stock_bar_set, covered_date_ranges)
+                } else {
+                    let bars = match data_source.fetch_bars(stock_symbol, None) {
+                        Ok(bars) => bars,
+                        Err(err) => {
+                            observer(ctx, &Err(fetch_bars_error(stock_symbol, err)));
+                            return;
+                        }
+                    };
+                    let covered_date_ranges =
+                        if let (Some(first_bar), Some(last_bar)) = (bars.first(), bars.last()) {
                             covered_date_ranges.union(
                                 &vec![(
                                     first_bar.timestamp_seconds() as i64,
@@ -229,16 +681,82 @@ where
                         } else {
                             covered_date_ranges
                         };
-                        let stock_bar_set = stock_bar_set + OrdSet::from(bars);
-                        (stock_bar_set, covered_date_ranges)
-                    };
+                    let stock_bar_set = stock_bar_set + OrdSet::from(bars);
+                    (stock_bar_set, covered_date_ranges)
+                };
 
-                    observer(ctx, &stock_bar_set);
+                observer(ctx, &Ok(stock_bar_set.clone()));
 
-                    let mut stock_bars_map = stock_bars_map.borrow_mut();
-                    stock_bars_map
-                        .insert(stock_symbol.clone(), (stock_bar_set, covered_date_ranges));
-                },
-            );
+                let mut stock_bars_map = stock_bars_map.borrow_mut();
+                stock_bars_map.insert(stock_symbol.clone(), (stock_bar_set, covered_date_ranges));
+            });
+    }
+}
+
+fn fetch_bars_error(stock_symbol: &str, err: anyhow::Error) -> String {
+    warn!(
+        "historical prices retrieval failed for {}: {}",
+        stock_symbol, err
+    );
+    if err.is::<RateLimitError>() {
+        RATE_LIMITED_ERROR.to_owned()
+    } else {
+        format!("Historical prices retrieval failed: {}", err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::stock_fixture;
+
+    #[test]
+    fn prefers_the_exchange_of_the_profile_to_the_symbol_suffix() {
+        let stock = Stock {
+            symbol: "SAP.DE".to_owned(),
+            ..Stock::default()
+        };
+        assert_eq!(stock.exchange(), Exchange::Xetra);
+
+        let stock = Stock {
+            profile: Some(StockProfile {
+                exchange: Exchange::from_code("NYQ"),
+                name: "SAP SE".to_owned(),
+            }),
+            ..stock
+        };
+        assert_eq!(stock.exchange(), Exchange::UnitedStates);
+    }
+
+    #[test]
+    fn keeps_the_bars_within_the_maximum() {
+        let stock = stock_fixture();
+
+        assert_eq!(stock.downsampled(30).bars, stock.bars);
+    }
+
+    #[test]
+    fn rolls_up_into_weekly_bars() {
+        let bars = stock_fixture().downsampled(10).bars;
+        let first_bar = bars.get_min().unwrap();
+
+        assert_eq!(bars.len(), 5);
+        assert_eq!(
+            (
+                first_bar.open,
+                first_bar.high,
+                first_bar.low,
+                first_bar.close
+            ),
+            (99f64, 114f64, 97f64, 112f64)
+        );
+        assert_eq!(first_bar.volume, Some(7_210_000));
+    }
+
+    #[test]
+    fn rolls_up_into_monthly_bars_if_weekly_bars_are_too_many() {
+        let bars = stock_fixture().downsampled(3).bars;
+
+        assert_eq!(bars.len(), 1);
     }
 }
@@ -0,0 +1,181 @@
+//! Fixtures for driving the reactive streams with synthetic input events, without a terminal.
+
+use crate::{
+    app::{DateRange, UiTarget},
+    event::{InputEvent, OverlayState},
+    stock::Stock,
+};
+use chrono::{Duration, TimeZone, Utc};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent};
+use reactive_rs::{Broadcast, Stream};
+use std::{cell::RefCell, env, fs, path::PathBuf, rc::Rc};
+use tui::{buffer::Buffer, layout::Rect};
+use yahoo_finance::Bar;
+
+/// Number of daily bars in the stock fixture.
+const FIXTURE_BAR_COUNT: i64 = 30;
+
+pub fn key(code: KeyCode) -> InputEvent {
+    InputEvent::Key(KeyEvent {
+        code,
+        modifiers: KeyModifiers::empty(),
+    })
+}
+
+/// Key presses typing out the string.
+pub fn type_str(s: &str) -> Vec<InputEvent> {
+    s.chars().map(|c| key(KeyCode::Char(c))).collect()
+}
+
+/// A left click at the point.
+pub fn click(x: u16, y: u16) -> InputEvent {
+    InputEvent::Mouse(MouseEvent::Up(
+        MouseButton::Left,
+        x,
+        y,
+        KeyModifiers::empty(),
+    ))
+}
+
+/// Records the items emitted by a stream, to be taken out for assertions.
+pub struct Recorder<T> {
+    items: Rc<RefCell<Vec<T>>>,
+}
+
+impl<T> Recorder<T>
+where
+    T: 'static + Clone,
+{
+    pub fn subscribe<S>(stream: S) -> Self
+    where
+        S: Stream<'static, Item = T>,
+    {
+        let items = Rc::new(RefCell::new(vec![]));
+        stream.subscribe({
+            let items = items.clone();
+            move |item| items.borrow_mut().push(item.clone())
+        });
+        Self { items }
+    }
+
+    /// Takes out the items emitted since the last call.
+    pub fn take(&self) -> Vec<T> {
+        self.items.borrow_mut().drain(..).collect()
+    }
+}
+
+/// The inputs of an overlay, i.e. a text field or a select menu, as wired up in `main`.
+pub struct OverlayInputs {
+    pub input_events: Broadcast<'static, (), InputEvent>,
+    pub overlay_states: Broadcast<'static, (), OverlayState>,
+    pub ui_target_areas: Broadcast<'static, (), (UiTarget, Option<Rect>)>,
+}
+
+impl Default for OverlayInputs {
+    fn default() -> Self {
+        Self {
+            input_events: Broadcast::new(),
+            overlay_states: Broadcast::new(),
+            ui_target_areas: Broadcast::new(),
+        }
+    }
+}
+
+impl OverlayInputs {
+    /// Sends the initial overlay state and the areas of the first draw, which the overlay streams
+    /// wait for before handling any input.
+    pub fn init(&self, ui_target_areas: &[(UiTarget, Option<Rect>)]) {
+        self.overlay_states.send(OverlayState::Inactive);
+        for ui_target_area in ui_target_areas {
+            self.ui_target_areas.send(*ui_target_area);
+        }
+    }
+
+    pub fn send_all<I>(&self, input_events: I)
+    where
+        I: IntoIterator<Item = InputEvent>,
+    {
+        for ev in input_events {
+            self.input_events.send(ev);
+        }
+    }
+}
+
+/// The date range covered by the bars of the stock fixture.
+pub fn fixture_date_range() -> DateRange {
+    let start_date = Utc.ymd(2020, 6, 1).and_hms(0, 0, 0);
+    start_date..(start_date + Duration::days(FIXTURE_BAR_COUNT))
+}
+
+/// A crypto stock, so that the market is always open, with a bar for each day of the fixture
+/// date range and prices that zigzag upwards.
+pub fn stock_fixture() -> Stock {
+    let start_date = fixture_date_range().start;
+    let bars = (0..FIXTURE_BAR_COUNT)
+        .map(|i| {
+            let close = 100f64 + (i % 7) as f64 * 1.5 + i as f64 * 0.5;
+            Bar {
+                timestamp: (start_date + Duration::days(i)).timestamp_millis(),
+                open: close - 1f64,
+                high: close + 2f64,
+                low: close - 3f64,
+                close,
+                volume: Some(1_000_000 + i as u64 * 10_000),
+            }
+        })
+        .collect();
+
+    Stock {
+        bars,
+        symbol: "BTC-USD".to_owned(),
+        ..Stock::default()
+    }
+}
+
+/// Returns the symbols of the cells in the buffer, a line per row, ignoring styles.
+pub fn buffer_lines(buffer: &Buffer) -> Vec<String> {
+    let area = buffer.area();
+    (area.top()..area.bottom())
+        .map(|y| {
+            (area.left()..area.right())
+                .map(|x| buffer.get(x, y).symbol.as_str())
+                .collect()
+        })
+        .collect()
+}
+
+/// Asserts that the lines match the snapshot of the name in `src/snapshots`.
+///
+/// The snapshot is recorded instead if `UPDATE_SNAPSHOTS` is set, while a missing one fails, so
+/// that a snapshot left uncommitted is not silently recorded on the next run.
+pub fn assert_snapshot(name: &str, lines: &[String]) {
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "src",
+        "snapshots",
+        &format!("{}.txt", name),
+    ]
+    .iter()
+    .collect();
+    let actual = lines.join("\n") + "\n";
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "snapshot {} is missing, run with UPDATE_SNAPSHOTS=1 to record it\n\nactual:\n{}",
+            name, actual
+        )
+    });
+    assert!(
+        actual == expected,
+        "snapshot {} differs, rerun with UPDATE_SNAPSHOTS=1 to record it\n\nexpected:\n{}\nactual:\n{}",
+        name,
+        expected,
+        actual
+    );
+}
@@ -0,0 +1,186 @@
+use crate::{app::IndicatorKind, config::ColorsConfig};
+use derivative::Derivative;
+use im::HashMap;
+use serde::Deserialize;
+use std::{convert::TryFrom, str::FromStr};
+use thiserror::Error;
+use tui::style::Color;
+
+/// Colors of the chart lines.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorScheme {
+    /// Color of falling prices, e.g. of the price line, the change and the volume of down days.
+    pub down: Color,
+    pub gridlines: Color,
+    pub indicators: HashMap<IndicatorKind, Color>,
+    /// Color of the marker on the latest bar, and of the arrow pointing to it when out of view.
+    pub latest_bar: Color,
+    /// Background color of the area between the price and the moving average where the price is
+    /// above it, dark enough for the price line to show over it.
+    pub ma_shading_above: Color,
+    /// Background color of the area where the price is below the moving average.
+    pub ma_shading_below: Color,
+    /// Color of rising prices.
+    pub up: Color,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        Self {
+            down: Color::Red,
+            gridlines: Color::DarkGray,
+            indicators: HashMap::new(),
+            latest_bar: Color::White,
+            ma_shading_above: Color::Rgb(0x12, 0x3b, 0x1e),
+            ma_shading_below: Color::Rgb(0x4a, 0x14, 0x14),
+            up: Color::Green,
+        }
+    }
+}
+
+impl ColorScheme {
+    /// Returns the color scheme with the colors set in the config file taking precedence.
+    pub fn merge(self, colors_config: &ColorsConfig) -> Self {
+        let indicators: HashMap<_, _> = colors_config
+            .indicators
+            .iter()
+            .map(|(&kind, &ThemeColor(color))| (kind, color))
+            .collect();
+
+        Self {
+            down: colors_config
+                .down
+                .map_or(self.down, |ThemeColor(color)| color),
+            gridlines: colors_config
+                .gridlines
+                .map_or(self.gridlines, |ThemeColor(color)| color),
+            indicators: indicators.union(self.indicators),
+            latest_bar: colors_config
+                .latest_bar
+                .map_or(self.latest_bar, |ThemeColor(color)| color),
+            ma_shading_above: colors_config
+                .ma_shading_above
+                .map_or(self.ma_shading_above, |ThemeColor(color)| color),
+            ma_shading_below: colors_config
+                .ma_shading_below
+                .map_or(self.ma_shading_below, |ThemeColor(color)| color),
+            up: colors_config.up.map_or(self.up, |ThemeColor(color)| color),
+        }
+    }
+
+    pub fn indicator(&self, kind: IndicatorKind) -> Color {
+        self.indicators.get(&kind).copied().unwrap_or(Color::Cyan)
+    }
+
+    /// Returns the color of rising prices if up, else of falling prices.
+    pub fn up_down(&self, up: bool) -> Color {
+        if up {
+            self.up
+        } else {
+            self.down
+        }
+    }
+}
+
+/// Preset of colors, which those set in the config file take precedence over.
+#[derive(Clone, Copy, Debug, Derivative, Eq, PartialEq)]
+#[derivative(Default)]
+pub enum Theme {
+    /// Blue for rising and orange for falling prices, which can be told apart with red-green color
+    /// blindness.
+    Colorblind,
+    #[derivative(Default)]
+    Default,
+}
+
+impl Theme {
+    pub fn color_scheme(self) -> ColorScheme {
+        match self {
+            Self::Colorblind => ColorScheme {
+                down: Color::Rgb(0xe6, 0x9f, 0x00),
+                ma_shading_above: Color::Rgb(0x12, 0x33, 0x4d),
+                ma_shading_below: Color::Rgb(0x4d, 0x35, 0x00),
+                up: Color::Rgb(0x56, 0xb4, 0xe9),
+                ..ColorScheme::default()
+            },
+            Self::Default => ColorScheme::default(),
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = ParseThemeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "colorblind" => Ok(Self::Colorblind),
+            "default" => Ok(Self::Default),
+            "" => Err(ParseThemeError::Empty),
+            _ => Err(ParseThemeError::Invalid),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseThemeError {
+    #[error("cannot parse theme from empty string")]
+    Empty,
+    #[error("invalid theme literal")]
+    Invalid,
+}
+
+/// Color as written in the config file, either a name such as `light_blue` or a hex RGB value such
+/// as `#ff8800`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "String")]
+pub struct ThemeColor(pub Color);
+
+impl FromStr for ThemeColor {
+    type Err = ParseThemeColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let color = match s.to_lowercase().replace('-', "_").as_str() {
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "gray" | "grey" => Color::Gray,
+            "dark_gray" | "dark_grey" => Color::DarkGray,
+            "light_red" => Color::LightRed,
+            "light_green" => Color::LightGreen,
+            "light_yellow" => Color::LightYellow,
+            "light_blue" => Color::LightBlue,
+            "light_magenta" => Color::LightMagenta,
+            "light_cyan" => Color::LightCyan,
+            "white" => Color::White,
+            "" => return Err(ParseThemeColorError::Empty),
+            hex if hex.starts_with('#') && hex.len() == 7 && hex.is_ascii() => {
+                let rgb = u32::from_str_radix(&hex[1..], 16)
+                    .map_err(|_| ParseThemeColorError::Invalid)?;
+                Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+            }
+            _ => return Err(ParseThemeColorError::Invalid),
+        };
+
+        Ok(Self(color))
+    }
+}
+
+impl TryFrom<String> for ThemeColor {
+    type Error = ParseThemeColorError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseThemeColorError {
+    #[error("cannot parse color from empty string")]
+    Empty,
+    #[error("invalid color, expected a color name or #rrggbb")]
+    Invalid,
+}
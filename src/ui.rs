@@ -1,33 +1,102 @@
 use crate::{
-    app::{App, Indicator, TimeFrame, UiState, UiTarget},
-    widgets::{SelectMenuBox, SelectMenuList, TextField},
+    app::{
+        self, App, ChartAnchor, ChartSeries, ChartTool, ChartToolState, ChartViewport, DateRange,
+        LineStyle, PriceDirection, PriceFlash, TimeFrame, TradingDayAxis, UiState, UiTarget,
+    },
+    format::{self, DateFormat},
+    indicator::{ComputeIndicator, IndicatorLineRole, INDICATOR_REGISTRY},
+    market::MarketStatus,
+    stats::{
+        CrossoverKind, MovingAverageCrossovers, PriceChange, PriceGap, PriceMeasurement,
+        ReturnHistogram, SmoothedVolume, StockStatistics, VolatilityCone,
+    },
+    stock::{self, CorporateEvent, CorporateEventKind, Stock, StockQuote},
+    theme::ColorScheme,
+    widgets::{SelectMenuBox, SelectMenuList, Shading, TextField},
 };
-use chrono::{Duration, TimeZone, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use im::{hashmap, HashMap};
 use itertools::Itertools;
 use itertools::MinMaxResult::{MinMax, NoElements, OneElement};
 use math::round;
-use std::{cmp, iter, ops::Range};
+use reactive_rs::Broadcast;
+use std::cmp::{self, Ordering};
 use strum::IntoEnumIterator;
 use ta::indicators;
-use ta::{DataItem, Next};
+use ta::Next;
 use tui::{
     backend::Backend,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
-    symbols::Marker,
-    widgets::{Axis, Block, Borders, Chart, Clear, Dataset, GraphType, Paragraph, Text},
+    widgets::{Axis, Block, Borders, Chart, Clear, Dataset, GraphType, Paragraph, Sparkline, Text},
     Frame,
 };
-use yahoo_finance::Timestamped;
+use yahoo_finance::{Bar, Timestamped};
+
+const COMPACT_HEADER_WIDTH: u16 = 80;
+/// Periods of the EMA ribbon, from the fastest to the slowest.
+const EMA_RIBBON_PERIODS: [u32; 5] = [8, 13, 21, 34, 55];
+const MIN_TERMINAL_HEIGHT: u16 = 12;
+const MIN_TERMINAL_WIDTH: u16 = 60;
+/// Most room kept ahead of the last bar for the next earnings date, as a share of the bars in view.
+const EARNINGS_AHEAD_SHARE: f64 = 0.25;
+/// Height of the minimap, including its borders.
+const MINIMAP_HEIGHT: u16 = 4;
+/// The fewest full dates on the x-axis before they are shortened to the month.
+const MIN_FULL_X_LABELS: usize = 3;
+/// Height of the smallest sub-pane that is drawn, including its borders.
+const MIN_SUB_PANE_HEIGHT: u16 = 5;
+const NEWS_PANEL_WIDTH: u16 = 50;
+const RETURN_HISTOGRAM_MAX_BUCKETS: usize = 15;
+const RETURN_HISTOGRAM_WIDTH: u16 = 30;
+/// Format of the x-axis labels shortened to the month, on narrow charts.
+const SHORT_X_LABEL_FORMAT: &str = "%b %y";
+const SMOOTH_LINE_MAX_STEPS: usize = 8;
+const STATS_PANEL_WIDTH: u16 = 24;
+/// Standard deviations of the bands of the volatility cone, with the chance of the price keeping
+/// within each.
+const VOLATILITY_CONE_DEVIATIONS: [(f64, &str); 2] = [(1f64, "68%"), (2f64, "95%")];
+/// Room kept ahead of the last bar for the volatility cone, as a share of the bars in view.
+const VOLATILITY_CONE_SHARE: f64 = 0.2;
+const X_AXIS_LABEL_PADDING: u8 = 4;
+const Y_AXIS_LABEL_HEIGHT: u8 = 1;
+const Y_AXIS_LABEL_PADDING: u8 = 2;
 
 pub fn draw<B: Backend>(f: &mut Frame<B>, app: &App) -> anyhow::Result<()> {
+    let size = f.size();
+    if size.width < MIN_TERMINAL_WIDTH || size.height < MIN_TERMINAL_HEIGHT {
+        draw_too_small(f, size);
+        // nothing can be clicked, but every area is still expected once per frame
+        for ui_target in UiTarget::iter() {
+            app.ui_state.ui_target_areas.send((ui_target, None));
+        }
+        app.ui_state.minimap_viewports.send(None);
+
+        return Ok(());
+    }
+
+    if app.ui_state.splash {
+        draw_splash(f, &app.stock.symbol, size);
+        for ui_target in UiTarget::iter() {
+            app.ui_state.ui_target_areas.send((ui_target, None));
+        }
+        app.ui_state.minimap_viewports.send(None);
+
+        return Ok(());
+    }
+
+    let (header_height, footer_height) = if app.ui_state.fullscreen_chart {
+        (0, 0)
+    } else {
+        (2, 2)
+    };
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(vec![
-            Constraint::Length(2),
+            Constraint::Length(header_height),
             Constraint::Min(5),
-            Constraint::Length(2),
+            Constraint::Length(footer_height),
         ])
         .split(f.size());
     let header_area = chunks[0];
@@ -35,6 +104,7 @@ pub fn draw<B: Backend>(f: &mut Frame<B>, app: &App) -> anyhow::Result<()> {
     let footer_area = chunks[2];
 
     draw_header(f, app, header_area)?;
+    draw_favorites_bar(f, app, header_area)?;
     draw_body(f, app, body_area)?;
     draw_footer(f, app, footer_area)?;
     draw_overlay(f, app)?;
@@ -45,7 +115,60 @@ pub fn draw<B: Backend>(f: &mut Frame<B>, app: &App) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn draw_header<B: Backend>(
+fn draw_splash<B: Backend>(f: &mut Frame<B>, symbol: &str, area: Rect) {
+    let splash_texts = vec![
+        Text::styled(
+            format!(
+                "{} {}\n\n",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            ),
+            Style::default().modifier(Modifier::BOLD),
+        ),
+        Text::styled(
+            format!("Loading {}…", symbol),
+            Style::default().fg(Color::Gray),
+        ),
+    ];
+    let splash_paragraph = Paragraph::new(splash_texts.iter())
+        .alignment(Alignment::Center)
+        .wrap(true);
+    let splash_area = Rect::new(
+        area.x,
+        area.y + area.height.saturating_sub(3) / 2,
+        area.width,
+        cmp::min(area.height, 3),
+    );
+    f.render_widget(splash_paragraph, splash_area);
+}
+
+fn draw_too_small<B: Backend>(f: &mut Frame<B>, area: Rect) {
+    let message = format!(
+        "Terminal too small (need at least {}×{})",
+        MIN_TERMINAL_WIDTH, MIN_TERMINAL_HEIGHT
+    );
+    let message_texts = vec![Text::raw(message)];
+    let message_paragraph = Paragraph::new(message_texts.iter())
+        .alignment(Alignment::Center)
+        .wrap(true);
+    let message_area = Rect::new(
+        area.x,
+        area.y + area.height / 2,
+        area.width,
+        cmp::min(area.height - area.height / 2, 3),
+    );
+    f.render_widget(message_paragraph, message_area);
+}
+
+fn draw_header<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) -> anyhow::Result<()> {
+    if app.ui_state.compact_header || area.width < COMPACT_HEADER_WIDTH {
+        draw_compact_header(f, app, area)
+    } else {
+        draw_full_header(f, app, area)
+    }
+}
+
+fn draw_full_header<B: Backend>(
     f: &mut Frame<B>,
     App {
         stock, ui_state, ..
@@ -53,18 +176,53 @@ fn draw_header<B: Backend>(
     area: Rect,
 ) -> anyhow::Result<()> {
     let stock_name = stock.name().unwrap_or("");
+    let stock_name_width = cmp::max(stock_name.chars().count() as u16, 20);
+
+    let latest_bar = stock.bars.get_max();
+
+    let entry_price_pl = latest_bar.and_then(|bar| entry_price_pl(bar.close, ui_state));
+    let entry_price_pl_width = entry_price_pl
+        .as_ref()
+        .map_or(0, |(text, _)| text.chars().count() as u16 + 2);
+
+    // the quote is left out where it would crowd out the market status
+    let stock_quote_text = stock
+        .quote
+        .as_ref()
+        .map(|quote| quote_text(quote, ui_state))
+        .filter(|text| {
+            let width = 2
+                + 10
+                + stock_name_width
+                + 14
+                + 14
+                + entry_price_pl_width
+                + text.chars().count() as u16
+                + 8;
+            !text.is_empty() && width <= area.width
+        })
+        .unwrap_or_default();
 
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .horizontal_margin(1)
         .constraints(vec![
             Constraint::Length(10),
-            Constraint::Length(cmp::max(stock_name.chars().count() as u16, 20)),
+            Constraint::Length(stock_name_width),
+            Constraint::Length(14),
+            Constraint::Length(14),
+            Constraint::Length(entry_price_pl_width),
+            Constraint::Length(stock_quote_text.chars().count() as u16),
             Constraint::Min(0),
         ])
         .split(area);
     let stock_symbol_area = chunks[0];
     let stock_name_area = chunks[1];
+    let stock_price_area = chunks[2];
+    let stock_volume_area = chunks[3];
+    let entry_price_pl_area = chunks[4];
+    let stock_quote_area = chunks[5];
+    let market_status_area = chunks[6];
 
     let header_base_style = Style::default().fg(Color::White).bg(Color::DarkGray);
 
@@ -91,252 +249,2395 @@ fn draw_header<B: Backend>(
         .ui_target_areas
         .send((UiTarget::StockNameButton, Some(stock_name_area)));
 
-    Ok(())
+    let stock_price_style = price_flash_style(
+        ui_state.price_flash,
+        &ui_state.color_scheme,
+        header_base_style,
+    );
+    let stock_price_texts = vec![Text::raw(latest_bar.map_or_else(String::new, |bar| {
+        ui_state.number_format.format_price(bar.close)
+    }))];
+    let stock_price_paragraph = Paragraph::new(stock_price_texts.iter())
+        .block(Block::default().style(header_base_style))
+        .style(stock_price_style.modifier(Modifier::BOLD));
+    f.render_widget(stock_price_paragraph, stock_price_area);
+
+    let stock_volume_texts = vec![Text::raw(
+        latest_bar
+            .and_then(|bar| bar.volume)
+            .map_or_else(String::new, |volume| {
+                format!("Vol: {}", ui_state.number_format.format_si(volume as f64))
+            }),
+    )];
+    let stock_volume_paragraph = Paragraph::new(stock_volume_texts.iter())
+        .block(Block::default().style(header_base_style))
+        .style(header_base_style);
+    f.render_widget(stock_volume_paragraph, stock_volume_area);
+
+    if let Some((entry_price_pl_text, pl_percent)) = entry_price_pl {
+        let entry_price_pl_texts = vec![Text::raw(entry_price_pl_text)];
+        let entry_price_pl_paragraph = Paragraph::new(entry_price_pl_texts.iter())
+            .block(Block::default().style(header_base_style))
+            .style(header_base_style.fg(ui_state.color_scheme.up_down(pl_percent >= 0f64)));
+        f.render_widget(entry_price_pl_paragraph, entry_price_pl_area);
+    }
+
+    let stock_quote_texts = vec![Text::raw(stock_quote_text)];
+    let stock_quote_paragraph = Paragraph::new(stock_quote_texts.iter())
+        .block(Block::default().style(header_base_style))
+        .style(header_base_style);
+    f.render_widget(stock_quote_paragraph, stock_quote_area);
+
+    let exchange = stock.exchange();
+    let market_status = exchange.market_status(Utc::now());
+    // markets that never close have no use for the badge
+    let market_status_texts = if exchange.is_always_open() {
+        vec![]
+    } else {
+        vec![Text::styled(
+            market_status.to_string(),
+            header_base_style
+                .fg(match market_status {
+                    MarketStatus::Open => Color::Green,
+                    MarketStatus::Closed => Color::Red,
+                })
+                .modifier(Modifier::BOLD),
+        )]
+    };
+    let market_status_paragraph = Paragraph::new(market_status_texts.iter())
+        .block(Block::default().style(header_base_style))
+        .style(header_base_style)
+        .alignment(Alignment::Right);
+    f.render_widget(market_status_paragraph, market_status_area);
+
+    Ok(())
+}
+
+/// Returns the unrealized P/L text of the entry price at the given price, and the P/L in percent.
+fn entry_price_pl(price: f64, ui_state: &UiState) -> Option<(String, f64)> {
+    let entry_price = ui_state.entry_price?;
+    let pl_percent = entry_price_pl_percent(entry_price, price);
+    Some((
+        format!(
+            "P/L: {}{}%",
+            if pl_percent > 0f64 { "+" } else { "" },
+            ui_state.number_format.format_decimal(pl_percent, 2)
+        ),
+        pl_percent,
+    ))
+}
+
+fn entry_price_pl_percent(entry_price: f64, price: f64) -> f64 {
+    (price / entry_price - 1f64) * 100f64
+}
+
+/// Returns the bid / ask and the day range of the quote, leaving out those not provided.
+fn quote_text(quote: &StockQuote, ui_state: &UiState) -> String {
+    let number_format = &ui_state.number_format;
+    let bid_ask = match (quote.bid, quote.ask) {
+        (Some(bid), Some(ask)) => Some(format!(
+            "Bid/Ask: {}/{}",
+            number_format.format_price(bid),
+            number_format.format_price(ask)
+        )),
+        _ => None,
+    };
+    let day_range = match (quote.day_low, quote.day_high) {
+        (Some(low), Some(high)) => Some(format!(
+            "Day: {} - {}",
+            number_format.format_price(low),
+            number_format.format_price(high)
+        )),
+        _ => None,
+    };
+
+    bid_ask.into_iter().chain(day_range).join("  ")
+}
+
+/// Draws the symbol, the latest price and its change over the date range on a single line.
+fn draw_compact_header<B: Backend>(
+    f: &mut Frame<B>,
+    App {
+        stock, ui_state, ..
+    }: &App,
+    area: Rect,
+) -> anyhow::Result<()> {
+    let header_base_style = Style::default().fg(Color::White).bg(Color::DarkGray);
+
+    let header_block = Block::default().style(header_base_style);
+    f.render_widget(header_block, area);
+
+    let area = area.inner(&Margin {
+        horizontal: 1,
+        vertical: 0,
+    });
+
+    let mut header_texts = vec![Text::styled(
+        format!("{} ", stock.symbol),
+        header_base_style.modifier(Modifier::BOLD),
+    )];
+    if let Some(price_change) = PriceChange::from_stock(stock, ui_state.date_range.as_ref()) {
+        header_texts.push(Text::styled(
+            ui_state.number_format.format_price(price_change.last),
+            price_flash_style(
+                ui_state.price_flash,
+                &ui_state.color_scheme,
+                header_base_style,
+            )
+            .modifier(Modifier::BOLD),
+        ));
+        header_texts.push(Text::styled(
+            format!(
+                " {}{}%",
+                if price_change.change_percent > 0f64 {
+                    "+"
+                } else {
+                    ""
+                },
+                ui_state
+                    .number_format
+                    .format_decimal(price_change.change_percent, 2)
+            ),
+            header_base_style.fg(ui_state
+                .color_scheme
+                .up_down(price_change.change_percent >= 0f64)),
+        ));
+        if let Some((entry_price_pl_text, pl_percent)) = entry_price_pl(price_change.last, ui_state)
+        {
+            header_texts.push(Text::styled(
+                format!("  {}", entry_price_pl_text),
+                header_base_style.fg(ui_state.color_scheme.up_down(pl_percent >= 0f64)),
+            ));
+        }
+    }
+    let header_paragraph = Paragraph::new(header_texts.iter()).style(header_base_style);
+    f.render_widget(header_paragraph, area);
+
+    // the name is left out, so only the symbol opens the symbol field
+    let stock_symbol_area = Rect::new(
+        area.x,
+        area.y,
+        cmp::min(stock.symbol.chars().count() as u16, area.width),
+        cmp::min(1, area.height),
+    );
+    ui_state
+        .ui_target_areas
+        .send((UiTarget::StockSymbolButton, Some(stock_symbol_area)));
+    ui_state
+        .ui_target_areas
+        .send((UiTarget::StockNameButton, None));
+
+    Ok(())
+}
+
+/// Draws the favorites on the second line of the header, highlighting the shown one.
+fn draw_favorites_bar<B: Backend>(
+    f: &mut Frame<B>,
+    App {
+        stock, ui_state, ..
+    }: &App,
+    area: Rect,
+) -> anyhow::Result<()> {
+    if ui_state.favorites.is_empty() || area.height < 2 {
+        ui_state
+            .ui_target_areas
+            .send((UiTarget::FavoritesBar, None));
+        return Ok(());
+    }
+
+    let area = Rect::new(area.x, area.y + 1, area.width, 1).inner(&Margin {
+        horizontal: 1,
+        vertical: 0,
+    });
+
+    let header_base_style = Style::default().fg(Color::White).bg(Color::DarkGray);
+
+    let slot_areas = app::favorite_slot_areas(&ui_state.favorites, area);
+    for (i, (symbol, slot_area)) in ui_state.favorites.iter().zip(slot_areas).enumerate() {
+        let slot_style = if symbol.eq_ignore_ascii_case(&stock.symbol) {
+            header_base_style.fg(Color::Black).bg(Color::Gray)
+        } else {
+            header_base_style
+        };
+        let slot_texts = vec![Text::styled(app::favorite_label(i, symbol), slot_style)];
+        let slot_paragraph = Paragraph::new(slot_texts.iter()).style(slot_style);
+        f.render_widget(slot_paragraph, slot_area);
+    }
+
+    ui_state
+        .ui_target_areas
+        .send((UiTarget::FavoritesBar, Some(area)));
+
+    Ok(())
+}
+
+/// Returns the style of the latest price, highlighted while it flashes.
+fn price_flash_style(
+    price_flash: Option<PriceFlash>,
+    color_scheme: &ColorScheme,
+    base_style: Style,
+) -> Style {
+    match price_flash {
+        Some(price_flash) => {
+            let color = match price_flash.direction {
+                PriceDirection::Down => color_scheme.down,
+                PriceDirection::Up => color_scheme.up,
+            };
+            if price_flash.is_fading() {
+                base_style.fg(color)
+            } else {
+                base_style.fg(Color::Black).bg(color)
+            }
+        }
+        None => base_style,
+    }
+}
+
+fn draw_body<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) -> anyhow::Result<()> {
+    if app.ui_state.summary_view {
+        app.ui_state.minimap_viewports.send(None);
+        return draw_summary(f, app, area);
+    }
+
+    let (area, minimap_area) = if app.ui_state.minimap {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Min(0), Constraint::Length(MINIMAP_HEIGHT)])
+            .split(area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (area, None)
+    };
+
+    let (area, stats_area) = if app.ui_state.stats_panel {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Min(0),
+                Constraint::Length(STATS_PANEL_WIDTH),
+            ])
+            .split(area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (area, None)
+    };
+
+    let (area, return_histogram_area) = if app.ui_state.return_histogram {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Min(0),
+                Constraint::Length(RETURN_HISTOGRAM_WIDTH),
+            ])
+            .split(area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (area, None)
+    };
+
+    let shown_sub_pane_count =
+        app.relative_strength_stock.is_some() as usize + app.ui_state.volume_pane as usize;
+    let (chart_area, mut sub_pane_areas) = if shown_sub_pane_count > 0 {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Percentage(app.ui_state.pane_split.price_percent()),
+                Constraint::Percentage(app.ui_state.pane_split.sub_pane_percent()),
+            ])
+            .split(area);
+        // the sub-panes share their part of the body evenly, leaving out the later ones that would
+        // be too short, and the chart takes the whole body if none fits
+        let sub_pane_count = cmp::min(
+            shown_sub_pane_count,
+            (chunks[1].height / MIN_SUB_PANE_HEIGHT) as usize,
+        );
+        if sub_pane_count > 0 {
+            let sub_pane_areas = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(vec![
+                    Constraint::Ratio(1, sub_pane_count as u32);
+                    sub_pane_count
+                ])
+                .split(chunks[1]);
+            (chunks[0], sub_pane_areas.into_iter())
+        } else {
+            (area, vec![].into_iter())
+        }
+    } else {
+        (area, vec![].into_iter())
+    };
+    let relative_strength_area = app
+        .relative_strength_stock
+        .as_ref()
+        .and_then(|_| sub_pane_areas.next());
+    let volume_area = if app.ui_state.volume_pane {
+        sub_pane_areas.next()
+    } else {
+        None
+    };
+
+    let (chart_area, split_chart_area) = if app.split_stock.is_some() {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(chart_area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (chart_area, None)
+    };
+
+    draw_price_chart(f, app, chart_area)?;
+    if let (Some(split_stock), Some(split_chart_area)) = (&app.split_stock, split_chart_area) {
+        // The split chart is not interactive, so its areas and viewport go to broadcasts nobody
+        // listens to, and the chart tools stay on the main chart.
+        let split_app = App {
+            split_stock: None,
+            stock: split_stock.clone(),
+            ui_state: UiState {
+                chart_tool_state: ChartToolState::default(),
+                chart_viewports: Broadcast::new(),
+                date_range: app.ui_state.split_time_frame.now_date_range(),
+                time_frame: app.ui_state.split_time_frame,
+                ui_target_areas: Broadcast::new(),
+                ..app.ui_state.clone()
+            },
+            ..app.clone()
+        };
+        draw_price_chart(f, &split_app, split_chart_area)?;
+    }
+    if let Some(relative_strength_area) = relative_strength_area {
+        draw_relative_strength_chart(f, app, relative_strength_area)?;
+    }
+    if let Some(volume_area) = volume_area {
+        draw_volume_chart(f, app, volume_area)?;
+    }
+    if let Some(stats_area) = stats_area {
+        draw_stats_panel(f, app, stats_area)?;
+    }
+    if let Some(return_histogram_area) = return_histogram_area {
+        draw_return_histogram(f, app, return_histogram_area)?;
+    }
+    match minimap_area {
+        Some(minimap_area) => draw_minimap(f, app, minimap_area),
+        None => app.ui_state.minimap_viewports.send(None),
+    }
+
+    Ok(())
+}
+
+fn draw_price_chart<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) -> anyhow::Result<()> {
+    if app.ui_state.is_adjusted_close() {
+        let adjusted_app = App {
+            stock: app.stock.adjusted(),
+            ..app.clone()
+        };
+        draw_historical_prices_chart(f, &adjusted_app, area)
+    } else {
+        draw_historical_prices_chart(f, app, area)
+    }
+}
+
+/// Draws the statistics above a sparkline of the closes, in place of the charts.
+fn draw_summary<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) -> anyhow::Result<()> {
+    let App {
+        stock, ui_state, ..
+    } = app;
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(6), Constraint::Min(0)])
+        .split(area);
+    let stats_area = chunks[0];
+    let sparkline_area = chunks[1];
+
+    draw_stats_panel(f, app, stats_area)?;
+
+    let closes: Vec<_> = stock
+        .bars
+        .iter()
+        .filter(|&bar| {
+            ui_state
+                .date_range
+                .as_ref()
+                .map_or(true, |date_range| date_range.contains(&bar.datetime()))
+        })
+        .map(|bar| bar.close)
+        .collect();
+    // the sparkline only has room for a bar per column, so the latest closes are kept
+    let closes = &closes[closes
+        .len()
+        .saturating_sub(sparkline_area.width.saturating_sub(2) as usize)..];
+    // and it starts its bars from zero, so the closes are raised from their low to show the moves
+    let sparkline_data: Vec<_> = match closes.iter().minmax() {
+        MinMax(&min, &max) if max > min => closes
+            .iter()
+            .map(|close| ((close - min) / (max - min) * 100f64).round() as u64 + 1)
+            .collect(),
+        _ => vec![1; closes.len()],
+    };
+
+    let title = format!("{} - press Enter for the chart", ui_state.time_frame);
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .title(&title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Gray)),
+        )
+        .style(Style::default().fg(Color::Cyan))
+        .data(&sparkline_data);
+    f.render_widget(sparkline, sparkline_area);
+
+    ui_state.ui_target_areas.send((UiTarget::Chart, None));
+
+    Ok(())
+}
+
+fn draw_stats_panel<B: Backend>(
+    f: &mut Frame<B>,
+    App {
+        stock, ui_state, ..
+    }: &App,
+    area: Rect,
+) -> anyhow::Result<()> {
+    let number_format = &ui_state.number_format;
+    let stats_texts = if let Some(stats) = StockStatistics::from_stock(stock) {
+        let window_label = if stats.is_full_window { "52W" } else { "Range" };
+        vec![
+            Text::raw(format!(
+                "{} high: {}\n",
+                window_label,
+                number_format.format_price(stats.high)
+            )),
+            Text::raw(format!(
+                "{} low: {}\n",
+                window_label,
+                number_format.format_price(stats.low)
+            )),
+            Text::raw(format!(
+                "Avg volume: {}\n",
+                stats
+                    .average_volume
+                    .map_or_else(|| "-".to_owned(), |v| number_format.format_si(v))
+            )),
+            Text::raw(format!(
+                "Volatility: {}\n",
+                stats.volatility.map_or_else(
+                    || "-".to_owned(),
+                    |v| format!("{}%", number_format.format_decimal(v * 100f64, 2))
+                )
+            )),
+        ]
+    } else {
+        vec![]
+    };
+
+    let stats_paragraph = Paragraph::new(stats_texts.iter()).block(
+        Block::default()
+            .title("Statistics")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Gray)),
+    );
+    f.render_widget(stats_paragraph, area);
+
+    Ok(())
+}
+
+/// Draws the returns over the date range as a histogram, a row per bucket with the highest returns
+/// on top, and the bars growing to the right.
+fn draw_return_histogram<B: Backend>(
+    f: &mut Frame<B>,
+    App {
+        stock, ui_state, ..
+    }: &App,
+    area: Rect,
+) -> anyhow::Result<()> {
+    let closes: Vec<_> = stock
+        .bars
+        .iter()
+        .filter(|&bar| {
+            ui_state
+                .date_range
+                .as_ref()
+                .map_or(true, |date_range| date_range.contains(&bar.datetime()))
+        })
+        .map(|bar| bar.close)
+        .collect();
+    let bucket_count = cmp::min(
+        area.height.saturating_sub(2) as usize,
+        RETURN_HISTOGRAM_MAX_BUCKETS,
+    );
+
+    let histogram_texts: Vec<_> = match ReturnHistogram::from_closes(&closes, bucket_count) {
+        Some(histogram) => {
+            let labels: Vec<_> = (0..histogram.counts.len())
+                .map(|i| {
+                    let low = histogram.bucket_low(i);
+                    format!(
+                        "{}{}%",
+                        if low > 0f64 { "+" } else { "" },
+                        ui_state.number_format.format_decimal(low, 1)
+                    )
+                })
+                .collect();
+            let label_width = labels
+                .iter()
+                .map(|label| label.chars().count())
+                .max()
+                .unwrap_or(0);
+            let bar_width = (area.width.saturating_sub(2) as usize).saturating_sub(label_width + 1);
+            let max_count = histogram.counts.iter().copied().max().unwrap_or(0);
+
+            histogram
+                .counts
+                .iter()
+                .zip(labels)
+                .enumerate()
+                .rev()
+                .map(|(i, (&count, label))| {
+                    // rounded up, so that a single return still shows
+                    let length = if max_count > 0 {
+                        (count * bar_width + max_count - 1) / max_count
+                    } else {
+                        0
+                    };
+                    let middle = histogram.bucket_low(i) + histogram.bucket_width / 2f64;
+                    Text::styled(
+                        format!(
+                            "{:>width$} {}\n",
+                            label,
+                            "█".repeat(length),
+                            width = label_width
+                        ),
+                        Style::default().fg(ui_state.color_scheme.up_down(middle >= 0f64)),
+                    )
+                })
+                .collect()
+        }
+        None => vec![],
+    };
+
+    let histogram_paragraph = Paragraph::new(histogram_texts.iter()).block(
+        Block::default()
+            .title("Returns")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Gray)),
+    );
+    f.render_widget(histogram_paragraph, area);
+
+    Ok(())
+}
+
+fn draw_historical_prices_chart<B: Backend>(
+    f: &mut Frame<B>,
+    App {
+        stock, ui_state, ..
+    }: &App,
+    area: Rect,
+) -> anyhow::Result<()> {
+    // The data is plotted against the trading day axis, while the chart tools keep timestamps, and
+    // against negated prices on an inverted axis, while the chart tools keep prices.
+    let x_axis = trading_day_axis(stock, ui_state.date_range.as_ref());
+    let to_y = |price: f64| {
+        if ui_state.inverted_axis {
+            -price
+        } else {
+            price
+        }
+    };
+
+    let mut historical_prices_data: HashMap<String, Vec<_>> = hashmap! {};
+    let stock_data: Vec<_> = stock
+        .bars
+        .iter()
+        .filter(|&bar| {
+            ui_state
+                .date_range
+                .as_ref()
+                .map_or(true, |date_range| date_range.contains(&bar.datetime()))
+        })
+        .map(|bar| {
+            (
+                x_axis.to_x(bar.timestamp_seconds() as f64),
+                to_y(round::half_to_even(
+                    bar.close,
+                    format::price_precision(bar.close) as i8,
+                )),
+            )
+        })
+        .collect();
+
+    if stock_data.is_empty() {
+        // the profile is loaded before the bars, so its absence means we are still loading
+        let message = if let Some(fetch_error) = &stock.fetch_error {
+            fetch_error.as_str()
+        } else if stock.profile.is_some() {
+            "No data for this range"
+        } else {
+            "Loading..."
+        };
+        draw_chart_message(f, "Historical Prices", message, area);
+        ui_state.ui_target_areas.send((UiTarget::Chart, Some(area)));
+
+        return Ok(());
+    }
+
+    historical_prices_data.insert(stock.symbol.clone(), stock_data);
+
+    let (area, ribbon_area) = if ui_state.up_down_ribbon && area.height > 1 {
+        (
+            Rect::new(area.x, area.y, area.width, area.height - 1),
+            Some(Rect::new(area.x, area.bottom() - 1, area.width, 1)),
+        )
+    } else {
+        (area, None)
+    };
+
+    let crossovers = ui_state.crossover_periods.map(|crossover_periods| {
+        let crossovers = MovingAverageCrossovers::from_stock(stock, crossover_periods);
+        let in_date_range = |timestamp: f64| {
+            ui_state.date_range.as_ref().map_or(true, |date_range| {
+                date_range.contains(&Utc.timestamp(timestamp as i64, 0))
+            })
+        };
+        historical_prices_data.insert(
+            crossover_data_name(crossover_periods.fast),
+            crossovers
+                .fast_data
+                .iter()
+                .filter(|&&(timestamp, _)| in_date_range(timestamp))
+                .map(|&(timestamp, price)| (x_axis.to_x(timestamp), to_y(price)))
+                .collect(),
+        );
+        historical_prices_data.insert(
+            crossover_data_name(crossover_periods.slow),
+            crossovers
+                .slow_data
+                .iter()
+                .filter(|&&(timestamp, _)| in_date_range(timestamp))
+                .map(|&(timestamp, price)| (x_axis.to_x(timestamp), to_y(price)))
+                .collect(),
+        );
+        let signals: Vec<_> = crossovers
+            .signals
+            .into_iter()
+            .filter(|signal| in_date_range(signal.timestamp))
+            .collect();
+        (crossover_periods, signals)
+    });
+
+    let ema_ribbon_names: Vec<_> = if ui_state.ema_ribbon {
+        EMA_RIBBON_PERIODS
+            .iter()
+            .map(|&n| {
+                let name = format!("EMA({})", n);
+                let mut ema = indicators::ExponentialMovingAverage::new(n).unwrap();
+                let ema_data = stock
+                    .bars
+                    .iter()
+                    .map(|bar| (bar.timestamp_seconds() as f64, ema.next(bar.close)))
+                    .filter(|&(timestamp, _)| {
+                        ui_state.date_range.as_ref().map_or(true, |date_range| {
+                            date_range.contains(&Utc.timestamp(timestamp as i64, 0))
+                        })
+                    })
+                    .map(|(timestamp, price)| (x_axis.to_x(timestamp), to_y(price)))
+                    .collect();
+                historical_prices_data.insert(name.clone(), ema_data);
+                name
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+
+    let mut historical_prices_datasets = vec![];
+    let is_shown = |series: ChartSeries| !ui_state.hidden_series.contains(&series);
+    // the series in the legend, by their number, with the color they are drawn in
+    let mut legend_entries = vec![];
+    let mut indicator_main_line = None;
+
+    if let Some(indicator) = ui_state.indicator {
+        // the indicator starts from its lookback before the date range, as fetched
+        let indicator_bars: Vec<_> = stock
+            .bars
+            .iter()
+            .filter(|bar| {
+                ui_state.date_range.as_ref().map_or(true, |date_range| {
+                    let date_range = (date_range.start
+                        - Duration::days(indicator.lookback() as i64 - 1))
+                        ..date_range.end;
+                    date_range.contains(&bar.datetime())
+                })
+            })
+            .cloned()
+            .collect();
+        let indicator_output = indicator.compute(&indicator_bars);
+        indicator_main_line = indicator_output
+            .lines
+            .iter()
+            .find(|line| line.role == IndicatorLineRole::Main)
+            .map(|line| line.name.clone());
+        for line in &indicator_output.lines {
+            historical_prices_data.insert(
+                line.name.clone(),
+                line.data
+                    .iter()
+                    .map(|&(timestamp, value)| (x_axis.to_x(timestamp), to_y(value)))
+                    .collect(),
+            );
+        }
+
+        let indicator_color = ui_state.color_scheme.indicator(indicator.kind());
+        legend_entries.push((
+            ChartSeries::Indicator,
+            indicator.to_string(),
+            indicator_color,
+        ));
+        for line in indicator_output
+            .lines
+            .iter()
+            .filter(|_| is_shown(ChartSeries::Indicator))
+        {
+            historical_prices_datasets.push(
+                Dataset::default()
+                    .marker(ui_state.chart_style.marker())
+                    .style(Style::default().fg(match line.role {
+                        IndicatorLineRole::Band => Color::DarkGray,
+                        IndicatorLineRole::Main => indicator_color,
+                    }))
+                    .graph_type(GraphType::Line)
+                    .data(historical_prices_data.get(&line.name).unwrap()),
+            );
+        }
+    }
+
+    if let Some((crossover_periods, _)) = &crossovers {
+        legend_entries.push((
+            ChartSeries::Crossovers,
+            crossover_periods.to_string(),
+            Color::Yellow,
+        ));
+    }
+    if let Some((crossover_periods, _)) = crossovers
+        .as_ref()
+        .filter(|_| is_shown(ChartSeries::Crossovers))
+    {
+        let fast_data = historical_prices_data
+            .get(&crossover_data_name(crossover_periods.fast))
+            .unwrap();
+        let slow_data = historical_prices_data
+            .get(&crossover_data_name(crossover_periods.slow))
+            .unwrap();
+
+        historical_prices_datasets.push(
+            Dataset::default()
+                .marker(ui_state.chart_style.marker())
+                .style(Style::default().fg(Color::Yellow))
+                .graph_type(GraphType::Line)
+                .data(fast_data),
+        );
+        historical_prices_datasets.push(
+            Dataset::default()
+                .marker(ui_state.chart_style.marker())
+                .style(Style::default().fg(Color::Blue))
+                .graph_type(GraphType::Line)
+                .data(slow_data),
+        );
+    }
+
+    if ui_state.ema_ribbon {
+        legend_entries.push((
+            ChartSeries::EmaRibbon,
+            "EMA ribbon".to_owned(),
+            ema_ribbon_color(0),
+        ));
+    }
+    for (i, name) in ema_ribbon_names
+        .iter()
+        .enumerate()
+        .filter(|_| is_shown(ChartSeries::EmaRibbon))
+    {
+        historical_prices_datasets.push(
+            Dataset::default()
+                .marker(ui_state.chart_style.marker())
+                .style(Style::default().fg(ema_ribbon_color(i)))
+                .graph_type(GraphType::Line)
+                .data(historical_prices_data.get(name).unwrap()),
+        );
+    }
+
+    let stock_data = historical_prices_data.get(&stock.symbol).unwrap();
+    let stock_prices: Vec<_> = stock_data.iter().map(|&(_, y)| to_y(y)).collect();
+
+    // smoothing only adds points where the bars are sparse, at most a step per braille dot
+    let smooth_stock_data = if ui_state.line_style == LineStyle::Smooth {
+        let steps = cmp::min(
+            area.width as usize * 2 / stock_data.len(),
+            SMOOTH_LINE_MAX_STEPS,
+        );
+        Some(smooth_line(stock_data, cmp::max(steps, 1)))
+    } else {
+        None
+    };
+
+    let stock_color = {
+        let first_price = stock_prices.first().unwrap_or(&0f64);
+        let last_price = stock_prices.last().unwrap_or(&0f64);
+        ui_state.color_scheme.up_down(last_price >= first_price)
+    };
+    legend_entries.insert(0, (ChartSeries::Price, stock.symbol.clone(), stock_color));
+    if is_shown(ChartSeries::Price) {
+        let historical_prices_dataset = Dataset::default()
+            .marker(ui_state.chart_style.marker())
+            .style(Style::default().fg(stock_color))
+            .graph_type(match ui_state.line_style {
+                LineStyle::Line | LineStyle::Smooth => GraphType::Line,
+                LineStyle::Markers => GraphType::Scatter,
+            })
+            .data(smooth_stock_data.as_ref().unwrap_or(stock_data));
+        historical_prices_datasets.push(historical_prices_dataset);
+    }
+
+    let (x_steps, x_axis_labels) = x_axis_labels(&x_axis, area, ui_state);
+    let x_axis_bounds = x_axis_bounds(&x_axis, &x_steps, &stock.earnings_dates, ui_state);
+
+    // The volatility cone is projected forward from the last bar, into the room kept for it ahead.
+    let volatility_cone = ui_state
+        .volatility_cone
+        .filter(|_| is_current(ui_state))
+        .and_then(|lookback| VolatilityCone::from_stock(stock, lookback));
+    let volatility_cone_data: Vec<_> = volatility_cone
+        .map(|cone| {
+            volatility_cone_data(&cone, x_axis.to_x(cone.timestamp), x_axis_bounds[1])
+                .into_iter()
+                .map(|bounds_data| {
+                    bounds_data
+                        .iter()
+                        .map(|data| data.iter().map(|&(x, price)| (x, to_y(price))).collect())
+                        .collect::<Vec<Vec<_>>>()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Only the visible window is fitted, leaving out the lookback before it that the indicators
+    // are computed from, but including the indicators within it so that bands are not clipped.
+    let prices: Vec<_> = historical_prices_data
+        .values()
+        .flatten()
+        .chain(volatility_cone_data.iter().flatten().flatten())
+        .filter(|&&(timestamp, _)| x_axis_bounds[0] <= timestamp && timestamp <= x_axis_bounds[1])
+        .map(|&(_, y)| y)
+        .collect();
+    let y_steps = value_steps(prices, area);
+    let y_axis_bounds = [*y_steps.first().unwrap(), *y_steps.last().unwrap()];
+    // The percent axis only relabels the prices, relative to the close of the baseline bar if one
+    // was picked, which may be out of view, or else to the first visible close.
+    let percent_baseline_bar = ui_state.percent_baseline.and_then(|percent_baseline| {
+        stock
+            .bars
+            .iter()
+            .min_by_key(|bar| (bar.timestamp_seconds() as i64 - percent_baseline.timestamp()).abs())
+    });
+    let percent_axis_base = percent_baseline_bar
+        .map(|bar| bar.close)
+        .or_else(|| stock_prices.first().copied())
+        .filter(|&base| ui_state.percent_axis && base != 0f64);
+    let y_axis_labels: Vec<_> = y_steps
+        .iter()
+        .map(|&y| to_y(y))
+        .map(|p| match percent_axis_base {
+            Some(base) => {
+                let percent = (p / base - 1f64) * 100f64;
+                format!(
+                    "{}{}%",
+                    if percent > 0f64 { "+" } else { "" },
+                    ui_state.number_format.format_decimal(percent, 1)
+                )
+            }
+            None => ui_state.number_format.format_price(p),
+        })
+        .collect();
+
+    let chart_viewport = chart_viewport(
+        area,
+        &y_axis_labels,
+        x_axis.clone(),
+        x_axis_bounds,
+        [to_y(y_axis_bounds[0]), to_y(y_axis_bounds[1])],
+    );
+
+    let gridline_data = ui_state
+        .gridlines
+        .map(|divisions| gridline_data(x_axis_bounds, y_axis_bounds, divisions))
+        .unwrap_or_default();
+    // inserted first, so that everything else is drawn over them
+    for gridline_data in gridline_data.iter().rev() {
+        historical_prices_datasets.insert(
+            0,
+            Dataset::default()
+                .marker(ui_state.chart_style.marker())
+                .style(Style::default().fg(ui_state.color_scheme.gridlines))
+                .graph_type(GraphType::Line)
+                .data(gridline_data),
+        );
+    }
+
+    let fibonacci_levels = ui_state
+        .chart_tool_state
+        .complete_anchors(ChartTool::FibonacciRetracement)
+        .map(|anchors| fibonacci_levels(stock, anchors))
+        .unwrap_or_default();
+    let fibonacci_data: Vec<_> = fibonacci_levels
+        .iter()
+        .map(|&(_, price)| {
+            vec![
+                (x_axis_bounds[0], to_y(price)),
+                (x_axis_bounds[1], to_y(price)),
+            ]
+        })
+        .collect();
+    for fibonacci_level_data in &fibonacci_data {
+        historical_prices_datasets.push(
+            Dataset::default()
+                .marker(ui_state.chart_style.marker())
+                .style(Style::default().fg(Color::Magenta))
+                .graph_type(GraphType::Line)
+                .data(fibonacci_level_data),
+        );
+    }
+
+    for (i, bounds_data) in volatility_cone_data.iter().enumerate() {
+        for data in bounds_data {
+            historical_prices_datasets.push(
+                Dataset::default()
+                    .marker(ui_state.chart_style.marker())
+                    .style(Style::default().fg(volatility_cone_color(i)))
+                    .graph_type(GraphType::Line)
+                    .data(data),
+            );
+        }
+    }
+
+    let previous_close = stock
+        .bars
+        .iter()
+        .rev()
+        .nth(1)
+        .map(|bar| bar.close)
+        .filter(|_| ui_state.previous_close);
+    // a dash every few columns, as braille dots
+    let previous_close_data = previous_close
+        .map(|previous_close| {
+            dashed_line_data(
+                x_axis_bounds,
+                to_y(previous_close),
+                cmp::max(area.width / 4, 1) as usize,
+            )
+        })
+        .unwrap_or_default();
+    for previous_close_dash_data in &previous_close_data {
+        historical_prices_datasets.push(
+            Dataset::default()
+                .marker(ui_state.chart_style.marker())
+                .style(Style::default().fg(Color::Gray))
+                .graph_type(GraphType::Line)
+                .data(previous_close_dash_data),
+        );
+    }
+
+    let entry_price_data: Option<Vec<_>> = ui_state.entry_price.map(|entry_price| {
+        vec![
+            (x_axis_bounds[0], to_y(entry_price)),
+            (x_axis_bounds[1], to_y(entry_price)),
+        ]
+    });
+    if let Some(entry_price_data) = &entry_price_data {
+        historical_prices_datasets.push(
+            Dataset::default()
+                .marker(ui_state.chart_style.marker())
+                .style(Style::default().fg(Color::White))
+                .graph_type(GraphType::Line)
+                .data(entry_price_data),
+        );
+    }
+
+    let trendline = ui_state
+        .chart_tool_state
+        .complete_anchors(ChartTool::Trendline)
+        .and_then(|anchors| {
+            let anchors: Vec<_> = anchors.iter().map(|&(t, price)| (t, to_y(price))).collect();
+            let (slope, points) = trendline(&anchors, &x_axis, x_axis_bounds, y_axis_bounds)?;
+            Some((to_y(slope), points))
+        });
+    let trendline_data = trendline.map(|(_, points)| points.to_vec());
+    if let Some(trendline_data) = &trendline_data {
+        historical_prices_datasets.push(
+            Dataset::default()
+                .marker(ui_state.chart_style.marker())
+                .style(Style::default().fg(Color::Cyan))
+                .graph_type(GraphType::Line)
+                .data(trendline_data),
+        );
+    }
+
+    let earnings_timestamps: Vec<_> = if ui_state.earnings {
+        stock
+            .earnings_dates
+            .iter()
+            .map(|earnings_date| earnings_date.timestamp() as f64)
+            .filter(|&timestamp| {
+                let x = x_axis.to_x(timestamp);
+                x_axis_bounds[0] <= x && x <= x_axis_bounds[1]
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+    // a dash every other row, drawn across the whole height of the chart
+    let earnings_data: Vec<Vec<_>> = earnings_timestamps
+        .iter()
+        .flat_map(|&timestamp| {
+            let x = x_axis.to_x(timestamp);
+            dashed_line_data(y_axis_bounds, x, cmp::max(area.height / 2, 1) as usize)
+                .into_iter()
+                .map(|dash_data| {
+                    dash_data
+                        .into_iter()
+                        .map(|(y, x)| (x, y))
+                        .collect::<Vec<_>>()
+                })
+        })
+        .collect();
+    for earnings_dash_data in &earnings_data {
+        historical_prices_datasets.push(
+            Dataset::default()
+                .marker(ui_state.chart_style.marker())
+                .style(Style::default().fg(Color::LightMagenta))
+                .graph_type(GraphType::Line)
+                .data(earnings_dash_data),
+        );
+    }
+
+    let gap_data: Vec<_> = ui_state
+        .gap_threshold
+        .map(|gap_threshold| PriceGap::from_stock(stock, gap_threshold))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|gap| {
+            ui_state.date_range.as_ref().map_or(true, |date_range| {
+                date_range.contains(&Utc.timestamp(gap.timestamp as i64, 0))
+            })
+        })
+        .map(|gap| {
+            let x = x_axis.to_x(gap.timestamp);
+            (
+                gap.direction,
+                vec![(x, to_y(gap.previous_close)), (x, to_y(gap.open))],
+            )
+        })
+        .collect();
+    for (direction, price_gap_data) in &gap_data {
+        historical_prices_datasets.push(
+            Dataset::default()
+                .marker(ui_state.chart_style.marker())
+                .style(Style::default().fg(match direction {
+                    PriceDirection::Down => ui_state.color_scheme.down,
+                    PriceDirection::Up => ui_state.color_scheme.up,
+                }))
+                .graph_type(GraphType::Line)
+                .data(price_gap_data),
+        );
+    }
+
+    let crosshair_bar = ui_state.chart_tool_state.crosshair.and_then(|(x, _)| {
+        stock
+            .bars
+            .iter()
+            .filter(|&bar| {
+                ui_state
+                    .date_range
+                    .as_ref()
+                    .map_or(true, |date_range| date_range.contains(&bar.datetime()))
+            })
+            .min_by_key(|bar| (bar.timestamp_seconds() as i64 - x as i64).abs())
+    });
+    let crosshair_data = crosshair_bar.map(|bar| {
+        let x = x_axis.to_x(bar.timestamp_seconds() as f64);
+        vec![(x, y_axis_bounds[0]), (x, y_axis_bounds[1])]
+    });
+    if let Some(crosshair_data) = &crosshair_data {
+        historical_prices_datasets.push(
+            Dataset::default()
+                .marker(ui_state.chart_style.marker())
+                .style(Style::default().fg(Color::Gray))
+                .graph_type(GraphType::Line)
+                .data(crosshair_data),
+        );
+    }
+
+    let crosshair_price = ui_state
+        .chart_tool_state
+        .crosshair
+        .map(|(_, price)| snap_price(price, ui_state.crosshair_step));
+    let crosshair_price_data = crosshair_price.map(|price| {
+        vec![
+            (x_axis_bounds[0], to_y(price)),
+            (x_axis_bounds[1], to_y(price)),
+        ]
+    });
+    if let Some(crosshair_price_data) = &crosshair_price_data {
+        historical_prices_datasets.push(
+            Dataset::default()
+                .marker(ui_state.chart_style.marker())
+                .style(Style::default().fg(Color::Gray))
+                .graph_type(GraphType::Line)
+                .data(crosshair_price_data),
+        );
+    }
+
+    let title = format!(
+        "Historical Prices{}{}{}{}{}",
+        if ui_state.split_view {
+            format!(" ({})", ui_state.time_frame)
+        } else {
+            String::new()
+        },
+        if ui_state.inverted_axis {
+            " (inverted)"
+        } else {
+            ""
+        },
+        match percent_baseline_bar {
+            Some(bar) if ui_state.percent_axis => {
+                format!(
+                    " (% since {})",
+                    ui_state.date_format.format(&bar.datetime())
+                )
+            }
+            _ => String::new(),
+        },
+        if ui_state.is_adjusted_close() {
+            " (adjusted)"
+        } else {
+            ""
+        },
+        match ui_state.chart_tool_state.active {
+            Some(ChartTool::FibonacciRetracement) => {
+                " - Fibonacci retracement: click two points"
+            }
+            Some(ChartTool::Measure) => " - Measure: click two bars",
+            Some(ChartTool::Trendline) => " - Trendline: click two points",
+            None => "",
+        }
+    );
+    let historical_prices_chart = Chart::default()
+        .block(
+            Block::default()
+                .title(&title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Gray)),
+        )
+        .x_axis(Axis::default().bounds(x_axis_bounds).labels(&x_axis_labels))
+        .y_axis(Axis::default().bounds(y_axis_bounds).labels(&y_axis_labels))
+        .datasets(&historical_prices_datasets);
+    f.render_widget(historical_prices_chart, area);
+
+    if let Some(main_line) =
+        indicator_main_line.filter(|_| ui_state.ma_shading && is_shown(ChartSeries::Indicator))
+    {
+        let shading_columns: Vec<_> = fill_between(
+            chart_viewport.area,
+            x_axis_bounds,
+            y_axis_bounds,
+            stock_data,
+            historical_prices_data.get(&main_line).unwrap(),
+        )
+        .into_iter()
+        .map(|(x, top, bottom, is_price_above)| {
+            // compared as plotted, which is upside down on an inverted axis
+            let color = if is_price_above != ui_state.inverted_axis {
+                ui_state.color_scheme.ma_shading_above
+            } else {
+                ui_state.color_scheme.ma_shading_below
+            };
+            (x, top, bottom, color)
+        })
+        .collect();
+        f.render_widget(Shading::new(&shading_columns), chart_viewport.area);
+    }
+
+    for &(ratio, price) in &fibonacci_levels {
+        let t = x_axis.to_timestamp(x_axis_bounds[1]);
+        if let Some((_, y)) = chart_viewport.value_to_point((t, price)) {
+            let label = format!(
+                "{}% {}",
+                ui_state.number_format.format_decimal(ratio * 100f64, 1),
+                ui_state.number_format.format_price(price)
+            );
+            let width = cmp::min(label.chars().count() as u16, chart_viewport.area.width);
+            let label_area = Rect::new(chart_viewport.area.right() - width, y, width, 1);
+            let label_texts = vec![Text::styled(label, Style::default().fg(Color::Magenta))];
+            f.render_widget(Paragraph::new(label_texts.iter()), label_area);
+        }
+    }
+
+    if let Some(cone) = &volatility_cone {
+        let t = x_axis.to_timestamp(x_axis_bounds[1]);
+        let bars_ahead = x_axis_bounds[1] - x_axis.to_x(cone.timestamp);
+        for (i, &(deviations, chance)) in VOLATILITY_CONE_DEVIATIONS.iter().enumerate() {
+            let (_, upper) = cone.bounds(deviations, bars_ahead);
+            if let Some((_, y)) = chart_viewport.value_to_point((t, upper)) {
+                let label = format!("{}σ {}", deviations, chance);
+                let width = cmp::min(label.chars().count() as u16, chart_viewport.area.width);
+                let label_area = Rect::new(chart_viewport.area.right() - width, y, width, 1);
+                let label_texts = vec![Text::styled(
+                    label,
+                    Style::default().fg(volatility_cone_color(i)),
+                )];
+                f.render_widget(Paragraph::new(label_texts.iter()), label_area);
+            }
+        }
+    }
+
+    // the earnings are labeled at the top of their lines
+    let middle_price = to_y((y_axis_bounds[0] + y_axis_bounds[1]) / 2f64);
+    for &timestamp in &earnings_timestamps {
+        if let Some((x, _)) = chart_viewport.value_to_point((timestamp, middle_price)) {
+            let marker_texts = vec![Text::styled(
+                "E",
+                Style::default()
+                    .fg(Color::LightMagenta)
+                    .modifier(Modifier::BOLD),
+            )];
+            let marker_area = Rect::new(x, chart_viewport.area.y, 1, 1);
+            f.render_widget(Paragraph::new(marker_texts.iter()), marker_area);
+        }
+    }
+
+    if let Some(price) = crosshair_price {
+        let t = x_axis.to_timestamp(x_axis_bounds[1]);
+        if let Some((_, y)) = chart_viewport.value_to_point((t, price)) {
+            // a snapped price is shown to the decimal places of the step
+            let label = match ui_state.crosshair_step {
+                Some(step) => ui_state
+                    .number_format
+                    .format_decimal(price, step_precision(step)),
+                None => ui_state.number_format.format_price(price),
+            };
+            let width = cmp::min(label.chars().count() as u16, chart_viewport.area.width);
+            let label_area = Rect::new(chart_viewport.area.right() - width, y, width, 1);
+            let label_texts = vec![Text::styled(
+                label,
+                Style::default().fg(Color::Black).bg(Color::Gray),
+            )];
+            f.render_widget(Paragraph::new(label_texts.iter()), label_area);
+        }
+    }
+
+    if let Some(previous_close) = previous_close {
+        let t = x_axis.to_timestamp(x_axis_bounds[1]);
+        if let Some((_, y)) = chart_viewport.value_to_point((t, previous_close)) {
+            let label = format!(
+                "Prev close {}",
+                ui_state.number_format.format_price(previous_close)
+            );
+            let width = cmp::min(label.chars().count() as u16, chart_viewport.area.width);
+            let label_area = Rect::new(chart_viewport.area.right() - width, y, width, 1);
+            let label_texts = vec![Text::styled(label, Style::default().fg(Color::Gray))];
+            f.render_widget(Paragraph::new(label_texts.iter()), label_area);
+        }
+    }
+
+    if let Some((slope, points)) = trendline {
+        // level with the right end of the line
+        let (x, y) = points[1];
+        let point = (x_axis.to_timestamp(x), to_y(y));
+        if let Some((_, y)) = chart_viewport.value_to_point(point) {
+            let label = format!(
+                "{}{}/day",
+                if slope > 0f64 { "+" } else { "" },
+                ui_state.number_format.format_price(slope)
+            );
+            let width = cmp::min(label.chars().count() as u16, chart_viewport.area.width);
+            let label_area = Rect::new(chart_viewport.area.right() - width, y, width, 1);
+            let label_texts = vec![Text::styled(label, Style::default().fg(Color::Cyan))];
+            f.render_widget(Paragraph::new(label_texts.iter()), label_area);
+        }
+    }
+
+    if let Some((crossover_periods, signals)) = crossovers
+        .as_ref()
+        .filter(|_| is_shown(ChartSeries::Crossovers))
+    {
+        for signal in signals {
+            if let Some((x, y)) = chart_viewport.value_to_point((signal.timestamp, signal.price)) {
+                let marker_texts = vec![crossover_marker_text(signal.kind, &ui_state.color_scheme)];
+                f.render_widget(Paragraph::new(marker_texts.iter()), Rect::new(x, y, 1, 1));
+            }
+        }
+
+        let legend_texts = vec![
+            Text::styled(
+                format!("SMA({}) ", crossover_periods.fast),
+                Style::default().fg(Color::Yellow),
+            ),
+            Text::styled(
+                format!("SMA({}) ", crossover_periods.slow),
+                Style::default().fg(Color::Blue),
+            ),
+            crossover_marker_text(CrossoverKind::Golden, &ui_state.color_scheme),
+            Text::raw(" Golden cross "),
+            crossover_marker_text(CrossoverKind::Death, &ui_state.color_scheme),
+            Text::raw(" Death cross"),
+        ];
+        let legend_area = Rect::new(
+            chart_viewport.area.x,
+            chart_viewport.area.bottom() - 1,
+            chart_viewport.area.width,
+            1,
+        );
+        f.render_widget(Paragraph::new(legend_texts.iter()), legend_area);
+    }
+
+    for event in &stock.events {
+        let bar = match event_bar(stock, event) {
+            Some(bar) => bar,
+            None => continue,
+        };
+        let in_date_range = ui_state
+            .date_range
+            .as_ref()
+            .map_or(true, |date_range| date_range.contains(&bar.datetime()));
+        if !in_date_range {
+            continue;
+        }
+
+        let t = bar.timestamp_seconds() as f64;
+        if let Some((x, y)) = chart_viewport.value_to_point((t, bar.close)) {
+            // beneath the bar, unless it is already at the bottom of the chart
+            let y = cmp::min(y + 1, chart_viewport.area.bottom() - 1);
+            let color = match event.kind {
+                CorporateEventKind::Dividend { .. } => Color::Yellow,
+                CorporateEventKind::Split { .. } => Color::LightBlue,
+            };
+            let marker_texts = vec![Text::styled(
+                event.marker().to_string(),
+                Style::default().fg(color),
+            )];
+            f.render_widget(Paragraph::new(marker_texts.iter()), Rect::new(x, y, 1, 1));
+        }
+    }
+
+    if let Some(latest_bar) = stock.bars.get_max() {
+        draw_latest_bar_marker(f, latest_bar, ui_state, &chart_viewport);
+    }
+
+    // a single series needs no legend
+    if legend_entries.len() > 1 {
+        let legend_texts: Vec<_> = legend_entries
+            .iter()
+            .map(|(series, label, color)| {
+                let color = if is_shown(*series) {
+                    *color
+                } else {
+                    Color::DarkGray
+                };
+                Text::styled(
+                    format!("{} {}  ", series.number(), label),
+                    Style::default().fg(color),
+                )
+            })
+            .collect();
+        let legend_area = Rect::new(
+            chart_viewport.area.x,
+            chart_viewport.area.y,
+            chart_viewport.area.width,
+            1,
+        );
+        f.render_widget(Paragraph::new(legend_texts.iter()), legend_area);
+    }
+
+    if let Some(bar) = crosshair_bar {
+        let number_format = &ui_state.number_format;
+        let x = x_axis.to_x(bar.timestamp_seconds() as f64);
+
+        let mut tooltip_lines = vec![
+            ui_state.date_format.format(&bar.datetime()),
+            format!("O: {}", number_format.format_price(bar.open)),
+            format!("H: {}", number_format.format_price(bar.high)),
+            format!("L: {}", number_format.format_price(bar.low)),
+            format!("C: {}", number_format.format_price(bar.close)),
+        ];
+        if let Some(volume) = bar.volume {
+            tooltip_lines.push(format!("Vol: {}", number_format.format_si(volume as f64)));
+        }
+        for event in &stock.events {
+            if event_bar(stock, event) != Some(bar) {
+                continue;
+            }
+            tooltip_lines.push(match event.kind {
+                CorporateEventKind::Dividend { amount } => {
+                    format!("Dividend: {}", number_format.format_price(amount))
+                }
+                CorporateEventKind::Split {
+                    denominator,
+                    numerator,
+                } => format!("Split: {}:{}", numerator, denominator),
+            });
+        }
+        let mut indicator_values: Vec<_> = historical_prices_data
+            .iter()
+            .filter(|(name, _)| **name != stock.symbol)
+            .filter_map(|(name, data)| {
+                data.iter()
+                    .find(|&&(data_x, _)| data_x == x)
+                    .map(|&(_, y)| (name.clone(), to_y(y)))
+            })
+            .collect();
+        indicator_values.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (name, value) in indicator_values {
+            let value = match ui_state.indicator_precision {
+                Some(precision) => number_format.format_decimal(value, precision),
+                None => number_format.format_price(value),
+            };
+            tooltip_lines.push(format!("{}: {}", name, value));
+        }
+
+        let tooltip_width = tooltip_lines
+            .iter()
+            .map(|line| line.chars().count() as u16 + 2)
+            .max()
+            .unwrap_or(0);
+        let tooltip_area = Rect::new(
+            chart_viewport.area.x,
+            chart_viewport.area.y,
+            cmp::min(tooltip_width, chart_viewport.area.width),
+            cmp::min(tooltip_lines.len() as u16 + 2, chart_viewport.area.height),
+        );
+        let tooltip_texts: Vec<_> = tooltip_lines
+            .into_iter()
+            .map(|line| Text::raw(format!("{}\n", line)))
+            .collect();
+        let tooltip_paragraph = Paragraph::new(tooltip_texts.iter()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Gray)),
+        );
+        f.render_widget(Clear, tooltip_area);
+        f.render_widget(tooltip_paragraph, tooltip_area);
+    }
+
+    ui_state.ui_target_areas.send((UiTarget::Chart, Some(area)));
+    if let Some(ribbon_area) = ribbon_area {
+        draw_up_down_ribbon(
+            f,
+            stock,
+            ui_state.date_range.as_ref(),
+            &ui_state.color_scheme,
+            &chart_viewport,
+            ribbon_area,
+        );
+    }
+
+    ui_state.chart_viewports.send(chart_viewport);
+
+    Ok(())
+}
+
+/// Marks the latest bar so that it can be found after panning, or points an arrow at the right edge
+/// of the chart towards it when panned into history.
+fn draw_latest_bar_marker<B: Backend>(
+    f: &mut Frame<B>,
+    latest_bar: &Bar,
+    ui_state: &UiState,
+    chart_viewport: &ChartViewport,
+) {
+    let area = chart_viewport.area;
+    let style = Style::default()
+        .fg(ui_state.color_scheme.latest_bar)
+        .modifier(Modifier::BOLD);
+    let (marker, point) = match &ui_state.date_range {
+        Some(date_range) if latest_bar.datetime() >= date_range.end => {
+            ("→", Some((area.right() - 1, area.top() + area.height / 2)))
+        }
+        _ => (
+            "●",
+            chart_viewport
+                .value_to_point((latest_bar.timestamp_seconds() as f64, latest_bar.close)),
+        ),
+    };
+
+    if let Some((x, y)) = point {
+        let marker_texts = vec![Text::styled(marker, style)];
+        f.render_widget(Paragraph::new(marker_texts.iter()), Rect::new(x, y, 1, 1));
+    }
+}
+
+/// Draws a block beneath each bar, in the up color if it closed up from the bar before and in the
+/// down color if down.
+fn draw_up_down_ribbon<B: Backend>(
+    f: &mut Frame<B>,
+    stock: &Stock,
+    date_range: Option<&DateRange>,
+    color_scheme: &ColorScheme,
+    chart_viewport: &ChartViewport,
+    area: Rect,
+) {
+    let bottom = chart_viewport.y_bounds[0];
+    let bar_columns: Vec<_> = stock
+        .bars
+        .iter()
+        .tuple_windows()
+        .filter(|(_, bar)| {
+            date_range.map_or(true, |date_range| date_range.contains(&bar.datetime()))
+        })
+        .filter_map(|(previous_bar, bar)| {
+            let (x, _) = chart_viewport.value_to_point((bar.timestamp_seconds() as f64, bottom))?;
+            let color = if bar.close > previous_bar.close {
+                color_scheme.up
+            } else if bar.close < previous_bar.close {
+                color_scheme.down
+            } else {
+                Color::DarkGray
+            };
+            Some((x, color))
+        })
+        .collect();
+
+    // each block reaches up to the next bar, so that sparse bars leave no holes
+    for (i, &(x, color)) in bar_columns.iter().enumerate() {
+        let next_x = bar_columns
+            .get(i + 1)
+            .map_or(chart_viewport.area.right(), |&(next_x, _)| next_x);
+        let width = cmp::max(next_x.saturating_sub(x), 1);
+        let block_texts = vec![Text::styled(
+            "▀".repeat(width as usize),
+            Style::default().fg(color),
+        )];
+        f.render_widget(
+            Paragraph::new(block_texts.iter()),
+            Rect::new(
+                x,
+                area.y,
+                cmp::min(width, area.right().saturating_sub(x)),
+                1,
+            ),
+        );
+    }
+}
+
+/// Draws a bordered box with a message centered in it, in place of a chart.
+fn draw_chart_message<B: Backend>(f: &mut Frame<B>, title: &str, message: &str, area: Rect) {
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Gray));
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+
+    if inner_area.height == 0 {
+        return;
+    }
+    let message_area = Rect::new(
+        inner_area.x,
+        inner_area.y + inner_area.height / 2,
+        inner_area.width,
+        1,
+    );
+    // rate limits clear up by themselves if left alone, unlike the other errors, so they stand out
+    let message_style = if message == stock::RATE_LIMITED_ERROR {
+        Style::default().fg(Color::Yellow).modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::Gray)
+    };
+    let message_texts = vec![Text::styled(message, message_style)];
+    let message_paragraph = Paragraph::new(message_texts.iter()).alignment(Alignment::Center);
+    f.render_widget(message_paragraph, message_area);
+}
+
+/// Returns the first bar on or after the date of the event, as events may fall on a non-trading
+/// day. An event before the first loaded bar has none, rather than being drawn on that bar.
+fn event_bar<'s>(stock: &'s Stock, event: &CorporateEvent) -> Option<&'s Bar> {
+    if event.datetime.date() < stock.bars.get_min()?.datetime().date() {
+        return None;
+    }
+
+    stock
+        .bars
+        .iter()
+        .find(|bar| bar.datetime().date() >= event.datetime.date())
+}
+
+/// Returns the name of a moving average of the crossovers in the chart data, set apart from an SMA
+/// indicator of the same period.
+fn crossover_data_name(period: u16) -> String {
+    format!("Crossover SMA({})", period)
+}
+
+fn crossover_marker_text<'t>(kind: CrossoverKind, color_scheme: &ColorScheme) -> Text<'t> {
+    match kind {
+        CrossoverKind::Death => Text::styled("▼", Style::default().fg(color_scheme.down)),
+        CrossoverKind::Golden => Text::styled("▲", Style::default().fg(color_scheme.up)),
+    }
+}
+
+/// Returns the color of the nth EMA of the ribbon, fading from yellow for the fastest to red for
+/// the slowest.
+fn ema_ribbon_color(n: usize) -> Color {
+    let t = n as f64 / (EMA_RIBBON_PERIODS.len() - 1) as f64;
+    let lerp = |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * t).round() as u8;
+    Color::Rgb(lerp(255, 220), lerp(230, 40), lerp(0, 40))
+}
+
+/// Returns the vertical and horizontal lines dividing the bounds into the number of divisions each
+/// way, leaving out those on the bounds.
+fn gridline_data(x_bounds: [f64; 2], y_bounds: [f64; 2], divisions: u16) -> Vec<Vec<(f64, f64)>> {
+    let inner_steps = |[min, max]: [f64; 2]| {
+        itertools_num::linspace(min, max, divisions as usize + 1)
+            .skip(1)
+            .take((divisions as usize).saturating_sub(1))
+    };
+    let vertical_lines = inner_steps(x_bounds).map(|x| vec![(x, y_bounds[0]), (x, y_bounds[1])]);
+    let horizontal_lines = inner_steps(y_bounds).map(|y| vec![(x_bounds[0], y), (x_bounds[1], y)]);
+
+    vertical_lines.chain(horizontal_lines).collect()
+}
+
+/// Returns the retracement ratios and their price levels between the bars nearest to the anchors.
+fn fibonacci_levels(stock: &Stock, anchors: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    const FIBONACCI_RATIOS: [f64; 6] = [0f64, 0.236, 0.382, 0.5, 0.618, 1f64];
+
+    let nearest_close = |timestamp: f64| {
+        stock
+            .bars
+            .iter()
+            .min_by_key(|bar| (bar.timestamp_seconds() as f64 - timestamp).abs() as i64)
+            .map(|bar| bar.close)
+    };
+
+    let (start_price, end_price) = match (
+        anchors.first().and_then(|&(t, _)| nearest_close(t)),
+        anchors.last().and_then(|&(t, _)| nearest_close(t)),
+    ) {
+        (Some(start_price), Some(end_price)) => (start_price, end_price),
+        _ => return vec![],
+    };
+
+    FIBONACCI_RATIOS
+        .iter()
+        .map(|&ratio| (ratio, end_price - (end_price - start_price) * ratio))
+        .collect()
+}
+
+/// Returns the slope in price per calendar day of the line through the anchors, and the line
+/// extended to the edges of the chart on the trading day axis. The chart doesn't draw lines with an
+/// end out of its bounds, so the line is clipped to them.
+fn trendline(
+    anchors: &[(f64, f64)],
+    x_axis: &TradingDayAxis,
+    x_bounds: [f64; 2],
+    y_bounds: [f64; 2],
+) -> Option<(f64, [(f64, f64); 2])> {
+    const SECONDS_PER_DAY: f64 = 86400f64;
+
+    let (&(t1, p1), &(t2, p2)) = (anchors.first()?, anchors.last()?);
+    if t1 == t2 {
+        return None;
+    }
+    let days_slope = (p2 - p1) / (t2 - t1) * SECONDS_PER_DAY;
+
+    // the line is straight on the chart, so it is drawn between the anchors' positions
+    let (x1, x2) = (x_axis.to_x(t1), x_axis.to_x(t2));
+    let slope = (p2 - p1) / (x2 - x1);
+    let price_at = |x: f64| p1 + slope * (x - x1);
+
+    let (mut start, mut end) = (x_bounds[0], x_bounds[1]);
+    if slope != 0f64 {
+        let (x_min, x_max) = {
+            let x_a = x1 + (y_bounds[0] - p1) / slope;
+            let x_b = x1 + (y_bounds[1] - p1) / slope;
+            (x_a.min(x_b), x_a.max(x_b))
+        };
+        start = start.max(x_min);
+        end = end.min(x_max);
+    } else if p1 < y_bounds[0] || p1 > y_bounds[1] {
+        return None;
+    }
+    if start > end {
+        return None;
+    }
+
+    Some((days_slope, [(start, price_at(start)), (end, price_at(end))]))
+}
+
+/// Returns the points of a Catmull-Rom spline through the data, with the given number of steps
+/// between each pair of points. Each step is kept between the pair of points around it, so that the
+/// curve does not overshoot the highs and lows.
+fn smooth_line(data: &[(f64, f64)], steps: usize) -> Vec<(f64, f64)> {
+    if data.len() < 2 || steps < 2 {
+        return data.to_vec();
+    }
+
+    let mut smooth_data = Vec::with_capacity((data.len() - 1) * steps + 1);
+    for i in 0..(data.len() - 1) {
+        let p0 = data[i.saturating_sub(1)].1;
+        let (x1, p1) = data[i];
+        let (x2, p2) = data[i + 1];
+        let p3 = data[cmp::min(i + 2, data.len() - 1)].1;
+
+        for step in 0..steps {
+            let t = step as f64 / steps as f64;
+            let p = 0.5
+                * (2f64 * p1
+                    + (p2 - p0) * t
+                    + (2f64 * p0 - 5f64 * p1 + 4f64 * p2 - p3) * t.powi(2)
+                    + (3f64 * p1 - p0 - 3f64 * p2 + p3) * t.powi(3));
+            smooth_data.push((x1 + (x2 - x1) * t, p.max(p1.min(p2)).min(p1.max(p2))));
+        }
+    }
+    smooth_data.push(*data.last().unwrap());
+
+    smooth_data
+}
+
+/// Returns the rows between two lines in each column of the area, as
+/// `(x, top, bottom, is_a_above)`, skipping the columns where either line has no value.
+fn fill_between(
+    area: Rect,
+    x_bounds: [f64; 2],
+    y_bounds: [f64; 2],
+    a: &[(f64, f64)],
+    b: &[(f64, f64)],
+) -> Vec<(u16, u16, u16, bool)> {
+    if area.width == 0 || area.height == 0 {
+        return vec![];
+    }
+
+    // lines beyond the bounds are shaded up to the edge
+    let to_row = |y: f64| {
+        let y_ratio = ((y - y_bounds[0]) / (y_bounds[1] - y_bounds[0]))
+            .max(0f64)
+            .min(1f64);
+        area.bottom() - 1 - round::half_to_even(y_ratio * (area.height - 1) as f64, 0) as u16
+    };
+    (0..area.width)
+        .filter_map(|i| {
+            let x_ratio = if area.width > 1 {
+                i as f64 / (area.width - 1) as f64
+            } else {
+                0f64
+            };
+            let x = x_bounds[0] + x_ratio * (x_bounds[1] - x_bounds[0]);
+            let (a_y, b_y) = (interpolate(a, x)?, interpolate(b, x)?);
+            let (a_row, b_row) = (to_row(a_y), to_row(b_y));
+            Some((
+                area.left() + i,
+                cmp::min(a_row, b_row),
+                cmp::max(a_row, b_row),
+                a_y >= b_y,
+            ))
+        })
+        .collect()
+}
+
+/// Returns the value of the line at x, linearly interpolated between its points sorted by x, or
+/// `None` outside of them.
+fn interpolate(data: &[(f64, f64)], x: f64) -> Option<f64> {
+    match data.binary_search_by(|(data_x, _)| data_x.partial_cmp(&x).unwrap_or(Ordering::Less)) {
+        Ok(i) => Some(data[i].1),
+        Err(i) if 0 < i && i < data.len() => {
+            let ((x0, y0), (x1, y1)) = (data[i - 1], data[i]);
+            Some(y0 + (x - x0) / (x1 - x0) * (y1 - y0))
+        }
+        Err(_) => None,
+    }
+}
+
+/// Returns how long ago the latest bar should have been followed by the next one, if longer than
+/// `stale_after` while the market is open, i.e. when the fetches succeed but return stale bars.
+/// A bar is current until its end, so that a daily bar is not flagged stale during its own day.
+fn stale_data_age(
+    stock: &Stock,
+    bar_duration: Duration,
+    stale_after: Duration,
+    now: DateTime<Utc>,
+) -> Option<Duration> {
+    let latest_bar = stock.bars.get_max()?;
+    if stock.exchange().market_status(now) != MarketStatus::Open {
+        return None;
+    }
+
+    let age = now - (latest_bar.datetime() + bar_duration);
+    if age > stale_after {
+        Some(age)
+    } else {
+        None
+    }
+}
+
+/// Estimates the area that the chart widget plots its data in, excluding the borders and axes.
+fn chart_viewport(
+    area: Rect,
+    y_axis_labels: &[String],
+    x_axis: TradingDayAxis,
+    x_bounds: [f64; 2],
+    y_bounds: [f64; 2],
+) -> ChartViewport {
+    let inner_area = area.inner(&Margin {
+        horizontal: 1,
+        vertical: 1,
+    });
+    let y_axis_label_width = y_axis_labels
+        .iter()
+        .map(|label| label.chars().count() as u16)
+        .max()
+        .unwrap_or(0);
+    // one column for the y-axis line, and two rows for the x-axis line and labels
+    let left = cmp::min(
+        inner_area.left() + y_axis_label_width + 1,
+        inner_area.right(),
+    );
+    let plot_area = Rect::new(
+        left,
+        inner_area.top(),
+        inner_area.right() - left,
+        inner_area.height.saturating_sub(2),
+    );
+
+    ChartViewport {
+        area: plot_area,
+        x_axis,
+        x_bounds,
+        y_bounds,
+    }
+}
+
+fn draw_relative_strength_chart<B: Backend>(
+    f: &mut Frame<B>,
+    App {
+        stock,
+        relative_strength_stock,
+        ui_state,
+    }: &App,
+    area: Rect,
+) -> anyhow::Result<()> {
+    let benchmark_stock = match relative_strength_stock {
+        Some(benchmark_stock) => benchmark_stock,
+        None => return Ok(()),
+    };
+
+    // on the axis of the stock, to line up with the price chart above
+    let x_axis = trading_day_axis(stock, ui_state.date_range.as_ref());
+
+    let benchmark_closes: HashMap<_, _> = benchmark_stock
+        .bars
+        .iter()
+        .map(|bar| (bar.datetime(), bar.close))
+        .collect();
+    // Daily bars of different exchanges start at different times of the day, so they are matched
+    // by date instead where the exact time is missing, which would mix up intraday bars.
+    let benchmark_date_closes: Option<HashMap<_, _>> =
+        if ui_state.time_frame.bar_duration() >= Duration::days(1) {
+            Some(
+                benchmark_stock
+                    .bars
+                    .iter()
+                    .map(|bar| (bar.datetime().naive_utc().date(), bar.close))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+    // Only bars present in both series are plotted.
+    let relative_strength_data: Vec<_> = stock
+        .bars
+        .iter()
+        .filter(|&bar| {
+            ui_state
+                .date_range
+                .as_ref()
+                .map_or(true, |date_range| date_range.contains(&bar.datetime()))
+        })
+        .filter_map(|bar| {
+            let benchmark_close = benchmark_closes.get(&bar.datetime()).or_else(|| {
+                benchmark_date_closes
+                    .as_ref()?
+                    .get(&bar.datetime().naive_utc().date())
+            })?;
+            if *benchmark_close == 0f64 {
+                return None;
+            }
+            Some((
+                x_axis.to_x(bar.timestamp_seconds() as f64),
+                bar.close / benchmark_close,
+            ))
+        })
+        .collect();
+
+    let title = format!(
+        "Relative Strength ({} / {})",
+        stock.symbol, benchmark_stock.symbol
+    );
+
+    if relative_strength_data.is_empty() {
+        let message = benchmark_stock
+            .fetch_error
+            .as_deref()
+            .unwrap_or("No data for this range");
+        draw_chart_message(f, &title, message, area);
+
+        return Ok(());
+    }
+
+    let ratios: Vec<_> = relative_strength_data
+        .iter()
+        .map(|&(_, ratio)| ratio)
+        .collect();
+
+    let relative_strength_dataset = Dataset::default()
+        .marker(ui_state.chart_style.marker())
+        .style(Style::default().fg(Color::Yellow))
+        .graph_type(GraphType::Line)
+        .data(&relative_strength_data);
+    let relative_strength_datasets = vec![relative_strength_dataset];
+
+    let (x_steps, x_axis_labels) = x_axis_labels(&x_axis, area, ui_state);
+    let x_axis_bounds = x_axis_bounds(&x_axis, &x_steps, &stock.earnings_dates, ui_state);
+
+    let ratio_steps = value_steps(ratios, area);
+    let y_axis_bounds = [*ratio_steps.first().unwrap(), *ratio_steps.last().unwrap()];
+    let y_axis_labels: Vec<_> = ratio_steps
+        .iter()
+        .map(|&r| ui_state.number_format.format_decimal(r, 4))
+        .collect();
+
+    let relative_strength_chart = Chart::default()
+        .block(
+            Block::default()
+                .title(title.as_str())
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Gray)),
+        )
+        .x_axis(Axis::default().bounds(x_axis_bounds).labels(&x_axis_labels))
+        .y_axis(Axis::default().bounds(y_axis_bounds).labels(&y_axis_labels))
+        .datasets(&relative_strength_datasets);
+    f.render_widget(relative_strength_chart, area);
+
+    Ok(())
+}
+
+fn draw_volume_chart<B: Backend>(
+    f: &mut Frame<B>,
+    App {
+        stock, ui_state, ..
+    }: &App,
+    area: Rect,
+) -> anyhow::Result<()> {
+    let in_date_range = |timestamp: f64| {
+        ui_state.date_range.as_ref().map_or(true, |date_range| {
+            date_range.contains(&Utc.timestamp(timestamp as i64, 0))
+        })
+    };
+
+    let x_axis = trading_day_axis(stock, ui_state.date_range.as_ref());
+
+    let volume_data: Vec<_> = stock
+        .bars
+        .iter()
+        .filter_map(|bar| {
+            Some((
+                bar.timestamp_seconds() as f64,
+                bar.volume? as f64,
+                bar.close >= bar.open,
+            ))
+        })
+        .filter(|&(timestamp, ..)| in_date_range(timestamp))
+        .map(|(timestamp, volume, up)| (x_axis.to_x(timestamp), volume, up))
+        .collect();
+
+    let title = match ui_state.volume_ema_period {
+        Some(period) => format!("Volume (EMA({}))", period),
+        None => "Volume".to_owned(),
+    };
+
+    if volume_data.is_empty() {
+        draw_chart_message(f, &title, "No volume for this range", area);
+
+        return Ok(());
+    }
+
+    // a line down to zero and back at every bar draws the histogram, one for the bars that closed
+    // up and one for those that closed down
+    let volume_histogram_data = |up: bool| -> Vec<_> {
+        volume_data
+            .iter()
+            .filter(|&&(.., bar_up)| bar_up == up)
+            .flat_map(|&(x, volume, _)| vec![(x, 0f64), (x, volume), (x, 0f64)])
+            .collect()
+    };
+    let (up_volume_histogram_data, down_volume_histogram_data) =
+        (volume_histogram_data(true), volume_histogram_data(false));
+    let smoothed_volume_data: Vec<_> = ui_state
+        .volume_ema_period
+        .map(|period| SmoothedVolume::from_stock(stock, period))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|smoothed_volume| in_date_range(smoothed_volume.timestamp))
+        .map(|smoothed_volume| {
+            (
+                x_axis.to_x(smoothed_volume.timestamp),
+                smoothed_volume.volume,
+            )
+        })
+        .collect();
+
+    let volume_datasets = vec![
+        Dataset::default()
+            .marker(ui_state.chart_style.marker())
+            .style(Style::default().fg(ui_state.color_scheme.up))
+            .graph_type(GraphType::Line)
+            .data(&up_volume_histogram_data),
+        Dataset::default()
+            .marker(ui_state.chart_style.marker())
+            .style(Style::default().fg(ui_state.color_scheme.down))
+            .graph_type(GraphType::Line)
+            .data(&down_volume_histogram_data),
+        Dataset::default()
+            .marker(ui_state.chart_style.marker())
+            .style(Style::default().fg(Color::Yellow))
+            .graph_type(GraphType::Line)
+            .data(&smoothed_volume_data),
+    ];
+
+    let volumes: Vec<_> = volume_data.iter().map(|&(_, volume, _)| volume).collect();
+
+    let (x_steps, x_axis_labels) = x_axis_labels(&x_axis, area, ui_state);
+    let x_axis_bounds = x_axis_bounds(&x_axis, &x_steps, &stock.earnings_dates, ui_state);
+
+    // the bars stand on zero
+    let volume_steps = value_steps(volumes.into_iter().chain(vec![0f64]).collect(), area);
+    let y_axis_bounds = [
+        *volume_steps.first().unwrap(),
+        *volume_steps.last().unwrap(),
+    ];
+    let y_axis_labels: Vec<_> = volume_steps
+        .iter()
+        .map(|&v| ui_state.number_format.format_si(v))
+        .collect();
+
+    let volume_chart = Chart::default()
+        .block(
+            Block::default()
+                .title(title.as_str())
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Gray)),
+        )
+        .x_axis(Axis::default().bounds(x_axis_bounds).labels(&x_axis_labels))
+        .y_axis(Axis::default().bounds(y_axis_bounds).labels(&y_axis_labels))
+        .datasets(&volume_datasets);
+    f.render_widget(volume_chart, area);
+
+    Ok(())
+}
+
+/// Draws the closes of all the bars loaded, with a box around those within the date range.
+fn draw_minimap<B: Backend>(
+    f: &mut Frame<B>,
+    App {
+        stock, ui_state, ..
+    }: &App,
+    area: Rect,
+) {
+    let x_axis = trading_day_axis(stock, None);
+    let minimap_data: Vec<_> = stock
+        .bars
+        .iter()
+        .map(|bar| (x_axis.to_x(bar.timestamp_seconds() as f64), bar.close))
+        .collect();
+
+    let (first_bar, last_bar) = match (stock.bars.get_min(), stock.bars.get_max()) {
+        (Some(first_bar), Some(last_bar)) if x_axis.len() > 1 => (first_bar, last_bar),
+        _ => {
+            draw_chart_message(f, "Overview", "", area);
+            ui_state.minimap_viewports.send(None);
+            return;
+        }
+    };
+
+    let x_bounds = [0f64, (x_axis.len() - 1) as f64];
+    let y_bounds = match minimap_data.iter().map(|&(_, close)| close).minmax() {
+        MinMax(min, max) => [min, max],
+        OneElement(close) => [close, close],
+        NoElements => [0f64, 0f64],
+    };
+
+    let minimap_dataset = Dataset::default()
+        .marker(ui_state.chart_style.marker())
+        .style(Style::default().fg(Color::Gray))
+        .graph_type(GraphType::Line)
+        .data(&minimap_data);
+    let minimap_datasets = vec![minimap_dataset];
+    let minimap_chart = Chart::default()
+        .block(
+            Block::default()
+                .title("Overview")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Gray)),
+        )
+        .x_axis(Axis::default().bounds(x_bounds))
+        .y_axis(Axis::default().bounds(y_bounds))
+        .datasets(&minimap_datasets);
+    f.render_widget(minimap_chart, area);
+
+    // without axis labels, the data is plotted right inside the borders
+    let minimap_viewport = ChartViewport {
+        area: area.inner(&Margin {
+            horizontal: 1,
+            vertical: 1,
+        }),
+        x_axis,
+        x_bounds,
+        y_bounds,
+    };
+
+    if let Some(date_range) = &ui_state.date_range {
+        let clamp = |date: DateTime<Utc>| {
+            cmp::min(cmp::max(date, first_bar.datetime()), last_bar.datetime())
+        };
+        let start = minimap_viewport
+            .value_to_point((clamp(date_range.start).timestamp() as f64, y_bounds[0]));
+        let end = minimap_viewport
+            .value_to_point((clamp(date_range.end).timestamp() as f64, y_bounds[0]));
+        if let (Some((left, _)), Some((right, _))) = (start, end) {
+            // the box takes in the borders of the minimap, to leave the closes within uncovered
+            let box_area = Rect::new(
+                left.saturating_sub(1),
+                area.y,
+                right + 2 - left,
+                area.height,
+            )
+            .intersection(area);
+            let viewport_box = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow));
+            f.render_widget(viewport_box, box_area);
+        }
+    }
+
+    ui_state.minimap_viewports.send(Some(minimap_viewport));
 }
 
-fn draw_body<B: Backend>(
-    f: &mut Frame<B>,
-    App { stock, ui_state }: &App,
+/// Returns the trading day axis of the bars within the date range.
+fn trading_day_axis(stock: &Stock, date_range: Option<&DateRange>) -> TradingDayAxis {
+    TradingDayAxis::new(
+        stock
+            .bars
+            .iter()
+            .filter(|&bar| {
+                date_range.map_or(true, |date_range| date_range.contains(&bar.datetime()))
+            })
+            .map(|bar| bar.timestamp_seconds() as f64)
+            .collect(),
+    )
+}
+
+/// Returns evenly spaced bars for the x-axis, from the first to the last, and their dates as
+/// labels, as many as fit side by side without overprinting. The dates are shortened to the month
+/// where too few full dates fit, and left out where not even two fit.
+fn x_axis_labels(
+    x_axis: &TradingDayAxis,
     area: Rect,
-) -> anyhow::Result<()> {
-    const X_AXIS_LABEL_PADDING: u8 = 4;
-    const X_AXIS_LABEL_WIDTH: u8 = 10;
-    const Y_AXIS_LABEL_HEIGHT: u8 = 1;
-    const Y_AXIS_LABEL_PADDING: u8 = 2;
+    ui_state: &UiState,
+) -> (Vec<f64>, Vec<String>) {
+    let full_label_count = x_label_count(area.width, ui_state.date_format.width());
+    let (date_format, label_count) = if full_label_count >= MIN_FULL_X_LABELS {
+        (ui_state.date_format.clone(), full_label_count)
+    } else {
+        let date_format = DateFormat::new(SHORT_X_LABEL_FORMAT);
+        let label_count = x_label_count(area.width, date_format.width());
+        (date_format, label_count)
+    };
+    let label_count = cmp::min(label_count, ui_state.max_x_labels.unwrap_or(usize::MAX));
 
-    let mut historical_prices_data: HashMap<String, Vec<_>> = hashmap! {};
-    let stock_data = stock
-        .bars
+    let x_steps = x_steps(x_axis, label_count);
+    let x_axis_labels = x_steps
         .iter()
-        .filter(|&bar| {
-            ui_state
-                .date_range
-                .as_ref()
-                .map_or(true, |date_range| date_range.contains(&bar.datetime()))
-        })
-        .map(|bar| {
-            (
-                bar.timestamp_seconds() as f64,
-                round::half_to_even(bar.close, 2),
-            )
+        .map(|&x| {
+            if label_count < 2 {
+                return String::new();
+            }
+            let t = x_axis.to_timestamp(x);
+            date_format.format(&Utc.timestamp(t as i64, 0))
         })
         .collect();
-    historical_prices_data.insert(stock.symbol.clone(), stock_data);
-
-    let mut historical_prices_datasets = vec![];
 
-    if let Some(indicator) = ui_state.indicator {
-        let indicator_prices_data = stock.bars.iter().map(|bar| {
-            let data_item = DataItem::builder()
-                .open(bar.open)
-                .high(bar.high)
-                .low(bar.low)
-                .close(bar.close);
-            let data_item = if let Some(volume) = bar.volume {
-                data_item.volume(volume as f64)
-            } else {
-                data_item
-            };
-            let data_item = data_item.build().unwrap();
-            (bar.timestamp_seconds() as f64, data_item)
-        });
+    (x_steps, x_axis_labels)
+}
 
-        match indicator {
-            Indicator::BollingerBands(n, k) => {
-                let indicator_prices_data = indicator_prices_data.filter(|(timestamp, _)| {
-                    ui_state.date_range.as_ref().map_or(true, |date_range| {
-                        let date_range =
-                            (date_range.start - Duration::days(*n as i64 - 1))..date_range.end;
-                        date_range.contains(&Utc.timestamp(*timestamp as i64, 0))
-                    })
-                });
-                let mut bb = indicators::BollingerBands::new(*n as u32, *k as f64).unwrap();
-                let (bb_upper_data, bb_middle_data, bb_lower_data) = indicator_prices_data.fold(
-                    (vec![], vec![], vec![]),
-                    |mut acc_data, (timestamp, data_item)| {
-                        let bb_output = bb.next(&data_item);
-                        acc_data.0.push((timestamp, bb_output.upper));
-                        acc_data.1.push((timestamp, bb_output.average));
-                        acc_data.2.push((timestamp, bb_output.lower));
-                        acc_data
-                    },
-                );
-                historical_prices_data = historical_prices_data
-                    + hashmap! {
-                        "BB Upper".to_owned() => bb_upper_data,
-                        "BB Middle".to_owned() => bb_middle_data,
-                        "BB Lower".to_owned() => bb_lower_data,
-                    };
-                let bb_upper_data = historical_prices_data.get("BB Upper").unwrap();
-                let bb_middle_data = historical_prices_data.get("BB Middle").unwrap();
-                let bb_lower_data = historical_prices_data.get("BB Lower").unwrap();
-
-                historical_prices_datasets.push(
-                    Dataset::default()
-                        .marker(Marker::Braille)
-                        .style(Style::default().fg(Color::DarkGray))
-                        .graph_type(GraphType::Line)
-                        .data(&bb_upper_data),
-                );
-                historical_prices_datasets.push(
-                    Dataset::default()
-                        .marker(Marker::Braille)
-                        .style(Style::default().fg(Color::DarkGray))
-                        .graph_type(GraphType::Line)
-                        .data(&bb_lower_data),
-                );
-                historical_prices_datasets.push(
-                    Dataset::default()
-                        .marker(Marker::Braille)
-                        .style(Style::default().fg(Color::Cyan))
-                        .graph_type(GraphType::Line)
-                        .data(&bb_middle_data),
-                );
-            }
-            Indicator::ExponentialMovingAverage(n) => {
-                let indicator_prices_data = indicator_prices_data.filter(|(timestamp, _)| {
-                    ui_state.date_range.as_ref().map_or(true, |date_range| {
-                        let date_range =
-                            (date_range.start - Duration::days(*n as i64 - 1))..date_range.end;
-                        date_range.contains(&Utc.timestamp(*timestamp as i64, 0))
-                    })
-                });
-                let mut ema = indicators::ExponentialMovingAverage::new(*n as u32).unwrap();
-                let ema_data = indicator_prices_data
-                    .map(|(timestamp, data_item)| (timestamp, ema.next(&data_item)))
-                    .collect();
-                historical_prices_data.insert("EMA".to_owned(), ema_data);
-                let ema_data = historical_prices_data.get("EMA").unwrap();
-
-                historical_prices_datasets.push(
-                    Dataset::default()
-                        .marker(Marker::Braille)
-                        .style(Style::default().fg(Color::Cyan))
-                        .graph_type(GraphType::Line)
-                        .data(&ema_data),
-                );
-            }
-            Indicator::SimpleMovingAverage(n) => {
-                let indicator_prices_data = indicator_prices_data.filter(|(timestamp, _)| {
-                    ui_state.date_range.as_ref().map_or(true, |date_range| {
-                        let date_range =
-                            (date_range.start - Duration::days(*n as i64 - 1))..date_range.end;
-                        date_range.contains(&Utc.timestamp(*timestamp as i64, 0))
-                    })
-                });
-                let mut sma = indicators::SimpleMovingAverage::new(*n as u32).unwrap();
-                let sma_data = indicator_prices_data
-                    .map(|(timestamp, data_item)| (timestamp, sma.next(&data_item)))
-                    .collect();
-                historical_prices_data.insert("SMA".to_owned(), sma_data);
-                let sma_data = historical_prices_data.get("SMA").unwrap();
-
-                historical_prices_datasets.push(
-                    Dataset::default()
-                        .marker(Marker::Braille)
-                        .style(Style::default().fg(Color::Cyan))
-                        .graph_type(GraphType::Line)
-                        .data(&sma_data),
-                );
-            }
-        }
+/// Returns how many labels of the width fit evenly spaced along the x-axis of a chart of the width,
+/// with padding between them. The y-axis labels are taken to be as wide as the x-axis labels, as
+/// the first x-axis label is drawn beneath them.
+fn x_label_count(chart_width: u16, label_width: u16) -> usize {
+    // less the borders, the y-axis labels and the y-axis
+    let graph_width = chart_width.saturating_sub(2 + label_width + 1);
+    if graph_width == 0 {
+        return 0;
     }
 
-    let stock_data = historical_prices_data.get(&stock.symbol).unwrap();
-    let (stock_timestamps, stock_prices): (Vec<_>, Vec<_>) = stock_data.clone().into_iter().unzip();
-
-    let historical_prices_dataset = Dataset::default()
-        .marker(Marker::Braille)
-        .style(Style::default().fg({
-            let first_price = stock_prices.first().unwrap_or(&0f64);
-            let last_price = stock_prices.last().unwrap_or(&0f64);
-            if last_price >= first_price {
-                Color::Green
-            } else {
-                Color::Red
-            }
-        }))
-        .graph_type(GraphType::Line)
-        .data(&stock_data);
-    historical_prices_datasets.push(historical_prices_dataset);
+    (graph_width - 1) as usize / (label_width + X_AXIS_LABEL_PADDING as u16) as usize + 1
+}
 
-    let timestamp_steps: Vec<_> = match stock_timestamps.clone().into_iter().minmax() {
-        MinMax(min, max) => {
-            let n = cmp::min(
-                round::floor(
-                    (area.width - 2) as f64 / (X_AXIS_LABEL_WIDTH + X_AXIS_LABEL_PADDING) as f64,
-                    0,
-                ) as usize,
-                stock_timestamps.len(),
-            );
+/// Returns `n` evenly spaced bars from the first to the last, and at least both of those.
+fn x_steps(x_axis: &TradingDayAxis, n: usize) -> Vec<f64> {
+    let n = cmp::min(n, x_axis.len());
+    let last = x_axis.len().saturating_sub(1) as f64;
 
-            itertools_num::linspace(min, max, n).collect()
-        }
-        OneElement(t) => vec![t, t],
-        NoElements => {
-            if let Some(Range {
-                start: start_date,
-                end: end_date,
-            }) = &ui_state.date_range
-            {
-                vec![
-                    start_date.timestamp() as f64,
-                    (*end_date - Duration::seconds(1)).timestamp() as f64,
-                ]
-            } else {
-                vec![
-                    Utc.ymd(1, 1, 1).and_hms(0, 0, 0).timestamp() as f64,
-                    Utc::now().timestamp() as f64,
-                ]
-            }
+    // rounded to whole bars, so that the labels are dates of bars
+    itertools_num::linspace(0f64, last, cmp::max(n, 2))
+        .map(f64::round)
+        .collect()
+}
+
+/// Returns the bounds of the x-axis from the first to the last step, extended past the last bar up
+/// to now when anchored to now and the date range is current, by at most a bar so that a closed
+/// market doesn't leave a wide gap.
+fn x_axis_bounds(
+    x_axis: &TradingDayAxis,
+    x_steps: &[f64],
+    earnings_dates: &[DateTime<Utc>],
+    ui_state: &UiState,
+) -> [f64; 2] {
+    let (first, last) = (*x_steps.first().unwrap(), *x_steps.last().unwrap());
+    let is_current = is_current(ui_state);
+
+    let right = match ui_state.chart_anchor {
+        ChartAnchor::Now if is_current && !x_axis.is_empty() => {
+            let now_x = x_axis.to_x(Utc::now().timestamp() as f64);
+            now_x.max(last).min(last + 1f64)
         }
+        _ => last,
     };
-    let x_axis_bounds = [
-        *timestamp_steps.first().unwrap(),
-        *timestamp_steps.last().unwrap(),
-    ];
-    let x_axis_labels: Vec<_> = timestamp_steps
+    // the volatility cone is kept clear of the right edge of the chart
+    let volatility_cone_bars = if ui_state.volatility_cone.is_some() && is_current {
+        (x_axis.len() as f64 * VOLATILITY_CONE_SHARE).ceil()
+    } else {
+        0f64
+    };
+    // so is the next earnings date, unless it is too far ahead
+    let earnings_x = if ui_state.earnings && is_current && !x_axis.is_empty() {
+        let now = Utc::now();
+        earnings_dates
+            .iter()
+            .find(|&&earnings_date| earnings_date > now)
+            .map(|earnings_date| x_axis.to_x(earnings_date.timestamp() as f64).ceil() + 1f64)
+            .filter(|&x| x - last <= (x_axis.len() as f64 * EARNINGS_AHEAD_SHARE).ceil())
+    } else {
+        None
+    };
+
+    [
+        first,
+        right
+            .max(last + volatility_cone_bars)
+            .max(earnings_x.unwrap_or(last)),
+    ]
+}
+
+/// Returns the dashes of a horizontal line across the x bounds, each as long as the gaps between.
+fn dashed_line_data(x_bounds: [f64; 2], y: f64, dashes: usize) -> Vec<Vec<(f64, f64)>> {
+    let step = (x_bounds[1] - x_bounds[0]) / (dashes * 2 - 1) as f64;
+    (0..dashes)
+        .map(|i| {
+            let x = x_bounds[0] + step * (i * 2) as f64;
+            vec![(x, y), (x + step, y)]
+        })
+        .collect()
+}
+
+/// Rounds the price to the nearest multiple of the step, if there is one.
+fn snap_price(price: f64, step: Option<f64>) -> f64 {
+    match step {
+        Some(step) => (price / step).round() * step,
+        None => price,
+    }
+}
+
+/// Returns the fewest decimal places that show the multiples of the step exactly.
+fn step_precision(step: f64) -> usize {
+    (0..format::MAX_PRICE_PRECISION)
+        .find(|&precision| {
+            let scaled = step * 10f64.powi(precision as i32);
+            (scaled - scaled.round()).abs() < 1e-6
+        })
+        .unwrap_or(format::MAX_PRICE_PRECISION)
+}
+
+/// Returns whether the date range reaches up to now.
+fn is_current(ui_state: &UiState) -> bool {
+    ui_state
+        .date_range
+        .as_ref()
+        .map_or(true, |date_range| date_range.contains(&Utc::now()))
+}
+
+/// Returns the lower and upper bounds of the volatility cone at each of the deviations, from the
+/// last bar at `from_x` out to `to_x`, in steps of a quarter bar.
+fn volatility_cone_data(
+    cone: &VolatilityCone,
+    from_x: f64,
+    to_x: f64,
+) -> Vec<[Vec<(f64, f64)>; 2]> {
+    let steps = cmp::max(((to_x - from_x) * 4f64).ceil() as usize, 1);
+    VOLATILITY_CONE_DEVIATIONS
         .iter()
-        .map(|&t| Utc.timestamp(t as i64, 0).format("%Y-%m-%d").to_string())
-        .collect();
+        .map(|&(deviations, _)| {
+            let (lower_data, upper_data) = (0..=steps)
+                .map(|i| {
+                    let x = from_x + (to_x - from_x) * i as f64 / steps as f64;
+                    let (lower, upper) = cone.bounds(deviations, x - from_x);
+                    ((x, lower), (x, upper))
+                })
+                .unzip();
+            [lower_data, upper_data]
+        })
+        .collect()
+}
+
+fn volatility_cone_color(i: usize) -> Color {
+    match i {
+        0 => Color::LightBlue,
+        _ => Color::Blue,
+    }
+}
 
-    let (_, prices): (Vec<_>, Vec<_>) = historical_prices_data.values().flatten().copied().unzip();
-    let price_steps: Vec<_> = match prices.into_iter().minmax() {
+/// Returns evenly spaced values for the y-axis labels, fitting as many as the area allows.
+fn value_steps(values: Vec<f64>, area: Rect) -> Vec<f64> {
+    match values.into_iter().minmax() {
         MinMax(min, max) => {
             let n = round::floor(
-                (area.height - 2) as f64 / (Y_AXIS_LABEL_HEIGHT + Y_AXIS_LABEL_PADDING) as f64,
+                area.height.saturating_sub(2) as f64
+                    / (Y_AXIS_LABEL_HEIGHT + Y_AXIS_LABEL_PADDING) as f64,
                 0,
             ) as usize;
 
             itertools_num::linspace(min, max, n).collect()
         }
-        OneElement(p) => vec![p, p],
+        OneElement(v) => vec![v, v],
         NoElements => vec![0_f64, f64::INFINITY],
-    };
-    let y_axis_bounds = [*price_steps.first().unwrap(), *price_steps.last().unwrap()];
-    let y_axis_labels: Vec<_> = price_steps.iter().map(|&p| format!("{:.2}", p)).collect();
-
-    let historical_prices_chart = Chart::default()
-        .block(
-            Block::default()
-                .title("Historical Prices")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Gray)),
-        )
-        .x_axis(Axis::default().bounds(x_axis_bounds).labels(&x_axis_labels))
-        .y_axis(Axis::default().bounds(y_axis_bounds).labels(&y_axis_labels))
-        .datasets(&historical_prices_datasets);
-    f.render_widget(historical_prices_chart, area);
-
-    Ok(())
+    }
 }
 
 fn draw_footer<B: Backend>(
     f: &mut Frame<B>,
-    App { ui_state, .. }: &App,
+    App {
+        stock, ui_state, ..
+    }: &App,
     area: Rect,
 ) -> anyhow::Result<()> {
-    let (indicator_box_area, time_frame_box_area) = {
+    let (message_area, indicator_box_area, time_frame_box_area) = {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints(vec![
@@ -345,9 +2646,86 @@ fn draw_footer<B: Backend>(
                 Constraint::Length(20),
             ])
             .split(area);
-        (chunks[1], chunks[2])
+        (chunks[0], chunks[1], chunks[2])
     };
 
+    let message_area = {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .horizontal_margin(1)
+            .constraints(vec![Constraint::Min(0)])
+            .split(message_area);
+        chunks[0]
+    };
+    // checked on every draw, as the latest bar ages between fetches
+    let stale_data_age = ui_state.stale_after.and_then(|stale_after| {
+        stale_data_age(
+            stock,
+            ui_state.time_frame.bar_duration(),
+            stale_after,
+            Utc::now(),
+        )
+    });
+    let message_area = if let Some(stale_data_age) = stale_data_age {
+        let badge = format!(" STALE {} ", format::format_duration(stale_data_age));
+        let badge_width = cmp::min(badge.chars().count() as u16, message_area.width);
+        let badge_texts = vec![Text::styled(
+            badge,
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .modifier(Modifier::BOLD),
+        )];
+        let badge_area = Rect::new(
+            message_area.right() - badge_width,
+            message_area.y,
+            badge_width,
+            1,
+        );
+        f.render_widget(Paragraph::new(badge_texts.iter()), badge_area);
+        Rect {
+            width: message_area.width - badge_width,
+            ..message_area
+        }
+    } else {
+        message_area
+    };
+    // the measurement stays until the tool is toggled off, with any message shown over it
+    let measurement = ui_state
+        .chart_tool_state
+        .complete_anchors(ChartTool::Measure)
+        .and_then(|anchors| {
+            let timestamps = (anchors[0].0, anchors[1].0);
+            if ui_state.is_adjusted_close() {
+                PriceMeasurement::between(&stock.adjusted(), timestamps)
+            } else {
+                PriceMeasurement::between(stock, timestamps)
+            }
+        });
+    if let Some(footer_message) = &ui_state.footer_message {
+        let message_texts = vec![Text::styled(
+            &footer_message.text,
+            Style::default().fg(Color::Gray),
+        )];
+        f.render_widget(Paragraph::new(message_texts.iter()), message_area);
+    } else if let Some(measurement) = measurement {
+        let number_format = &ui_state.number_format;
+        let sign = if measurement.change > 0f64 { "+" } else { "" };
+        let measurement_texts = vec![Text::styled(
+            format!(
+                "Measured: {}{} ({}{}%) over {} bars, {} days",
+                sign,
+                number_format.format_price(measurement.change),
+                sign,
+                number_format.format_decimal(measurement.change_percent, 2),
+                measurement.bars,
+                measurement.days
+            ),
+            Style::default().fg(ui_state.color_scheme.up_down(measurement.change >= 0f64)),
+        )];
+        f.render_widget(Paragraph::new(measurement_texts.iter()), message_area);
+    }
+
     let menu_active_base_style = Style::default().fg(Color::White).bg(Color::DarkGray);
 
     let indicator_menu_state = ui_state.indicator_menu_state.borrow();
@@ -373,11 +2751,15 @@ fn draw_footer<B: Backend>(
         Text::styled(
             if let Some(indicator) = ui_state.indicator {
                 indicator.to_string()
+            } else if let Some(hidden_indicator) = ui_state.hidden_indicator {
+                format!("{} (hidden)", hidden_indicator)
             } else {
                 "None".to_owned()
             },
             if indicator_menu_state.active {
                 menu_active_base_style
+            } else if ui_state.hidden_indicator.is_some() {
+                Style::default().fg(Color::DarkGray)
             } else {
                 Style::default()
             },
@@ -483,6 +2865,177 @@ fn draw_overlay<B: Backend>(f: &mut Frame<B>, App { ui_state, .. }: &App) -> any
             .send((UiTarget::StockSymbolField, None));
     }
 
+    let indicator_params_field_state = ui_state.indicator_params_field_state.borrow();
+
+    if indicator_params_field_state.active {
+        // above the indicator box in the footer
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Min(0), Constraint::Length(40)])
+            .split(f.size());
+        let indicator_params_field_area = chunks[1];
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Min(0),
+                Constraint::Length(3),
+                Constraint::Length(2),
+            ])
+            .split(indicator_params_field_area);
+        let indicator_params_field_area = chunks[1];
+
+        let indicator_params_field_title = match ui_state.indicator.or(ui_state.hidden_indicator) {
+            Some(indicator) => format!("{}: {}", indicator, indicator.param_names().join(", ")),
+            None => "No indicator".to_owned(),
+        };
+        let indicator_params_field_value = indicator_params_field_state.value.clone();
+        let indicator_params_field_texts = vec![Text::raw(indicator_params_field_value.as_str())];
+        let indicator_params_field = TextField::new(indicator_params_field_texts.iter())
+            .title(&indicator_params_field_title)
+            .style(active_base_style)
+            .border_style(Style::default().fg(Color::Gray));
+        drop(indicator_params_field_state);
+        let mut indicator_params_field_state = ui_state.indicator_params_field_state.borrow_mut();
+        f.render_stateful_widget(
+            indicator_params_field,
+            indicator_params_field_area,
+            &mut indicator_params_field_state,
+        );
+
+        ui_state.ui_target_areas.send((
+            UiTarget::IndicatorParamsField,
+            Some(indicator_params_field_area),
+        ));
+    } else {
+        ui_state
+            .ui_target_areas
+            .send((UiTarget::IndicatorParamsField, None));
+    }
+
+    let entry_price_field_state = ui_state.entry_price_field_state.borrow();
+
+    if entry_price_field_state.active {
+        // above the footer, away from the indicator params field
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Length(30), Constraint::Min(0)])
+            .split(f.size());
+        let entry_price_field_area = chunks[0];
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Min(0),
+                Constraint::Length(3),
+                Constraint::Length(2),
+            ])
+            .split(entry_price_field_area);
+        let entry_price_field_area = chunks[1];
+
+        let entry_price_field_value = entry_price_field_state.value.clone();
+        let entry_price_field_texts = vec![Text::raw(entry_price_field_value.as_str())];
+        let entry_price_field = TextField::new(entry_price_field_texts.iter())
+            .title("Entry price")
+            .style(active_base_style)
+            .border_style(Style::default().fg(Color::Gray));
+        drop(entry_price_field_state);
+        let mut entry_price_field_state = ui_state.entry_price_field_state.borrow_mut();
+        f.render_stateful_widget(
+            entry_price_field,
+            entry_price_field_area,
+            &mut entry_price_field_state,
+        );
+
+        ui_state
+            .ui_target_areas
+            .send((UiTarget::EntryPriceField, Some(entry_price_field_area)));
+    } else {
+        ui_state
+            .ui_target_areas
+            .send((UiTarget::EntryPriceField, None));
+    }
+
+    if ui_state.news_panel_state.active {
+        let news_panel_area = {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![
+                    Constraint::Min(0),
+                    Constraint::Length(NEWS_PANEL_WIDTH),
+                ])
+                .split(f.size());
+            let news_panel_area = chunks[1];
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(vec![
+                    Constraint::Length(2),
+                    Constraint::Min(0),
+                    Constraint::Length(2),
+                ])
+                .split(news_panel_area);
+            chunks[1]
+        };
+
+        let news_texts: Vec<_> = if ui_state.news_headlines.is_empty() {
+            vec![Text::styled(
+                "No headlines",
+                Style::default().fg(Color::Gray),
+            )]
+        } else {
+            ui_state
+                .news_headlines
+                .iter()
+                .skip(ui_state.news_panel_state.scroll)
+                .enumerate()
+                .flat_map(|(i, news_headline)| {
+                    vec![
+                        // the headline at the top is the one opened with Enter
+                        Text::styled(
+                            format!("{}\n", news_headline.title),
+                            if i == 0 {
+                                Style::default().modifier(Modifier::BOLD)
+                            } else {
+                                Style::default()
+                            },
+                        ),
+                        Text::styled(
+                            format!(
+                                "{} - {} {}\n\n",
+                                news_headline.publisher,
+                                ui_state.date_format.format(&news_headline.published),
+                                news_headline.published.format("%H:%M")
+                            ),
+                            Style::default().fg(Color::Gray),
+                        ),
+                    ]
+                })
+                .collect()
+        };
+        let news_title = format!(
+            "News ({}/{})",
+            cmp::min(
+                ui_state.news_panel_state.scroll + 1,
+                ui_state.news_headlines.len()
+            ),
+            ui_state.news_headlines.len()
+        );
+        let news_paragraph = Paragraph::new(news_texts.iter())
+            .block(
+                Block::default()
+                    .title(&news_title)
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Gray)),
+            )
+            .wrap(true);
+        f.render_widget(Clear, news_panel_area);
+        f.render_widget(news_paragraph, news_panel_area);
+
+        ui_state
+            .ui_target_areas
+            .send((UiTarget::NewsPanel, Some(news_panel_area)));
+    } else {
+        ui_state.ui_target_areas.send((UiTarget::NewsPanel, None));
+    }
+
     let indicator_menu_state = ui_state.indicator_menu_state.borrow();
 
     if indicator_menu_state.active {
@@ -501,7 +3054,7 @@ fn draw_overlay<B: Backend>(f: &mut Frame<B>, App { ui_state, .. }: &App) -> any
                 .constraints(vec![
                     Constraint::Min(0),
                     Constraint::Length(cmp::min(
-                        Indicator::iter().count() as u16 + 1 + 2,
+                        INDICATOR_REGISTRY.len() as u16 + 1 + 2,
                         indicator_list_area.height - 2,
                     )),
                     Constraint::Length(2),
@@ -510,8 +3063,10 @@ fn draw_overlay<B: Backend>(f: &mut Frame<B>, App { ui_state, .. }: &App) -> any
             chunks[1]
         };
 
-        let indicator_menu_items = iter::once("None".to_owned())
-            .chain(Indicator::iter().map(|t| t.to_string()))
+        let indicator_menu_items = indicator_menu_state
+            .visible_items()
+            .into_iter()
+            .map(|t| t.map_or_else(|| "None".to_owned(), |t| t.to_string()))
             .map(Text::raw);
         let indicator_list = SelectMenuList::new(indicator_menu_items)
             .border_style(Style::default().fg(Color::Gray))
@@ -556,7 +3111,12 @@ fn draw_overlay<B: Backend>(f: &mut Frame<B>, App { ui_state, .. }: &App) -> any
             chunks[1]
         };
 
-        let time_frame_menu_items = TimeFrame::iter().map(|t| Text::raw(t.to_string()));
+        let time_frame_menu_items = time_frame_menu_state
+            .visible_items()
+            .into_iter()
+            .flatten()
+            // with the hotkeys selecting them directly, at the right edge of the menu
+            .map(|t| Text::raw(format!("{:<17}{}", t.to_string(), t.hotkey())));
         let time_frame_list = SelectMenuList::new(time_frame_menu_items)
             .border_style(Style::default().fg(Color::Gray))
             .highlight_style(highlight_base_style);
@@ -591,11 +3151,8 @@ fn draw_debug<B: Backend>(
 ) -> anyhow::Result<()> {
     let frame_time = {
         let mut frame_rate_counter = frame_rate_counter.borrow_mut();
-        if let Some(frame_time) = frame_rate_counter.incr() {
-            Some(frame_time)
-        } else {
-            frame_rate_counter.frame_time()
-        }
+        frame_rate_counter.incr();
+        frame_rate_counter.smoothed_frame_time()
     };
     let frame_time_text = if let Some(frame_time) = frame_time {
         format!("{} ms", frame_time.num_milliseconds())
@@ -638,3 +3195,275 @@ fn draw_debug<B: Backend>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{assert_snapshot, buffer_lines, fixture_date_range, stock_fixture};
+    use tui::{backend::TestBackend, Terminal};
+
+    fn draw_lines(app: &App, width: u16, height: u16) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|mut f| {
+                draw(&mut f, app).expect("draw failed");
+            })
+            .unwrap();
+        buffer_lines(terminal.backend().buffer())
+    }
+
+    fn app_fixture(ui_state: UiState) -> App {
+        App {
+            relative_strength_stock: None,
+            split_stock: None,
+            stock: stock_fixture(),
+            ui_state: UiState {
+                date_range: Some(fixture_date_range()),
+                time_frame: TimeFrame::OneMonth,
+                ..ui_state
+            },
+        }
+    }
+
+    #[test]
+    fn draws_too_small() {
+        let app = app_fixture(UiState::default());
+
+        assert_snapshot("too_small", &draw_lines(&app, 40, 6));
+    }
+
+    #[test]
+    fn draws_chart_with_indicator() {
+        let app = app_fixture(UiState {
+            indicator: Some("SMA(5)".parse().unwrap()),
+            ..UiState::default()
+        });
+        let lines = draw_lines(&app, 100, 30);
+
+        assert!(lines.iter().any(|line| line.contains("BTC-USD")));
+        assert_snapshot("chart_with_indicator", &lines);
+    }
+
+    #[test]
+    fn draws_compact_header_with_volume_pane() {
+        let app = app_fixture(UiState {
+            volume_pane: true,
+            ..UiState::default()
+        });
+        let lines = draw_lines(&app, 70, 20);
+
+        assert!(lines.iter().any(|line| line.contains("BTC-USD")));
+        assert_snapshot("compact_header_with_volume_pane", &lines);
+    }
+
+    #[test]
+    fn leaves_out_the_sub_panes_that_do_not_fit() {
+        let app = app_fixture(UiState {
+            volume_pane: true,
+            ..UiState::default()
+        });
+        let has_volume_pane = |lines: Vec<String>| lines.iter().any(|line| line.contains("Volume"));
+
+        assert!(has_volume_pane(draw_lines(&app, 70, 40)));
+        assert!(!has_volume_pane(draw_lines(
+            &app,
+            MIN_TERMINAL_WIDTH,
+            MIN_TERMINAL_HEIGHT
+        )));
+    }
+
+    #[test]
+    fn draws_adjusted_prices_when_toggled() {
+        let app = app_fixture(UiState {
+            adjusted_close: Some(true),
+            ..UiState::default()
+        });
+        let lines = draw_lines(&app, 100, 30);
+
+        assert!(lines.iter().any(|line| line.contains("(adjusted)")));
+    }
+
+    #[test]
+    fn anchors_the_x_axis_to_now_only_when_current() {
+        let stock = stock_fixture();
+        let x_axis = trading_day_axis(&stock, None);
+        let x_steps = [0f64, 29f64];
+        let ui_state = |chart_anchor, date_range| UiState {
+            chart_anchor,
+            date_range,
+            ..UiState::default()
+        };
+
+        assert_eq!(
+            x_axis_bounds(
+                &x_axis,
+                &x_steps,
+                &[],
+                &ui_state(ChartAnchor::LastBar, None)
+            ),
+            [0f64, 29f64]
+        );
+        // now is long after the last bar, so the gap is capped at a bar
+        assert_eq!(
+            x_axis_bounds(&x_axis, &x_steps, &[], &ui_state(ChartAnchor::Now, None)),
+            [0f64, 30f64]
+        );
+        assert_eq!(
+            x_axis_bounds(
+                &x_axis,
+                &x_steps,
+                &[],
+                &ui_state(ChartAnchor::Now, Some(fixture_date_range()))
+            ),
+            [0f64, 29f64]
+        );
+    }
+
+    #[test]
+    fn divides_the_chart_by_gridlines_inside_the_bounds() {
+        assert_eq!(
+            gridline_data([0f64, 10f64], [100f64, 140f64], 2),
+            vec![
+                vec![(5f64, 100f64), (5f64, 140f64)],
+                vec![(0f64, 120f64), (10f64, 120f64)],
+            ]
+        );
+        assert!(gridline_data([0f64, 10f64], [100f64, 140f64], 1).is_empty());
+    }
+
+    #[test]
+    fn flags_the_latest_bar_as_stale_past_the_threshold() {
+        let stock = stock_fixture();
+        let latest_bar_end = stock.bars.get_max().unwrap().datetime() + Duration::days(1);
+
+        assert_eq!(
+            stale_data_age(
+                &stock,
+                Duration::days(1),
+                Duration::hours(1),
+                latest_bar_end + Duration::minutes(30)
+            ),
+            None
+        );
+        assert_eq!(
+            stale_data_age(
+                &stock,
+                Duration::days(1),
+                Duration::hours(1),
+                latest_bar_end + Duration::hours(2)
+            ),
+            Some(Duration::hours(2))
+        );
+    }
+
+    #[test]
+    fn matches_events_to_the_loaded_bars_only() {
+        let stock = stock_fixture();
+        let first_bar = stock.bars.get_min().unwrap();
+        let event_at = |datetime| CorporateEvent {
+            datetime,
+            kind: CorporateEventKind::Dividend { amount: 1f64 },
+        };
+
+        assert_eq!(
+            event_bar(&stock, &event_at(first_bar.datetime() + Duration::hours(6))),
+            Some(first_bar)
+        );
+        assert_eq!(
+            event_bar(&stock, &event_at(first_bar.datetime() - Duration::days(3))),
+            None
+        );
+    }
+
+    #[test]
+    fn keeps_the_daily_bar_current_during_its_day() {
+        let stock = stock_fixture();
+        let latest_bar_date = stock.bars.get_max().unwrap().datetime();
+
+        // the crypto market of the fixture is open around the clock
+        assert_eq!(
+            stale_data_age(
+                &stock,
+                Duration::days(1),
+                Duration::hours(1),
+                latest_bar_date + Duration::hours(6)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn fills_between_the_lines_where_both_have_values() {
+        let price_data = [(0f64, 4f64), (2f64, 0f64)];
+        let ma_data = [(1f64, 2f64), (2f64, 2f64)];
+
+        assert_eq!(interpolate(&price_data, 1.5), Some(1f64));
+        assert_eq!(interpolate(&ma_data, 0.5), None);
+        assert_eq!(
+            fill_between(
+                Rect::new(0, 0, 3, 5),
+                [0f64, 2f64],
+                [0f64, 4f64],
+                &price_data,
+                &ma_data
+            ),
+            vec![(1, 2, 2, true), (2, 2, 4, false)]
+        );
+    }
+
+    #[test]
+    fn measures_the_unrealized_pl_from_the_entry_price() {
+        assert_eq!(entry_price_pl_percent(100f64, 125f64), 25f64);
+        assert_eq!(entry_price_pl_percent(200f64, 150f64), -25f64);
+    }
+
+    #[test]
+    fn fits_the_x_axis_labels_without_overprinting() {
+        // 80 columns leave 67 for the graph, with a 10 column label every 14
+        assert_eq!(x_label_count(80, 10), 5);
+        assert_eq!(x_label_count(30, 10), 2);
+        assert_eq!(x_label_count(14, 10), 1);
+        assert_eq!(x_label_count(10, 10), 0);
+    }
+
+    #[test]
+    fn dashes_the_line_across_the_bounds() {
+        assert_eq!(
+            dashed_line_data([0f64, 10f64], 5f64, 3),
+            vec![
+                vec![(0f64, 5f64), (2f64, 5f64)],
+                vec![(4f64, 5f64), (6f64, 5f64)],
+                vec![(8f64, 5f64), (10f64, 5f64)],
+            ]
+        );
+    }
+
+    #[test]
+    fn snaps_the_crosshair_price_to_the_step() {
+        assert_eq!(snap_price(123.37, None), 123.37);
+        assert_eq!(snap_price(123.37, Some(0.25)), 123.25);
+        assert_eq!(snap_price(123.37, Some(5f64)), 125f64);
+        assert_eq!(step_precision(0.25), 2);
+        assert_eq!(step_precision(5f64), 0);
+        assert_eq!(step_precision(0.001), 3);
+    }
+
+    #[test]
+    fn widens_the_volatility_cone_from_the_last_close() {
+        let cone = VolatilityCone {
+            last_close: 100f64,
+            timestamp: 0f64,
+            volatility: 0.01,
+        };
+        let data = volatility_cone_data(&cone, 10f64, 11f64);
+
+        assert_eq!(data.len(), VOLATILITY_CONE_DEVIATIONS.len());
+        for [lower_data, upper_data] in &data {
+            assert_eq!(lower_data.len(), 5);
+            assert_eq!(lower_data[0], (10f64, 100f64));
+            assert_eq!(upper_data[0], (10f64, 100f64));
+            assert!(lower_data[4].1 < 100f64 && upper_data[4].1 > 100f64);
+        }
+        assert!(data[1][1][4].1 > data[0][1][4].1);
+    }
+}
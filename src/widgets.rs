@@ -1,5 +1,7 @@
 pub use select_menu::*;
+pub use shading::*;
 pub use text_field::*;
 
 mod select_menu;
+mod shading;
 mod text_field;
@@ -82,7 +82,10 @@ where
     L: Iterator<Item = Text<'a>>,
     S: Clone + PartialEq + ToString,
 {
-    list: List<'a, L>,
+    border_style: Style,
+    filter_style: Style,
+    highlight_style: Style,
+    items: L,
     phantom_s: PhantomData<&'a S>,
 }
 
@@ -93,28 +96,26 @@ where
 {
     pub fn new(items: L) -> Self {
         Self {
-            list: List::new(items)
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::Gray)),
-                )
-                .highlight_style(Style::default().fg(Color::Black).bg(Color::White)),
+            border_style: Style::default().fg(Color::Gray),
+            filter_style: Style::default().fg(Color::White),
+            highlight_style: Style::default().fg(Color::Black).bg(Color::White),
+            items,
             phantom_s: PhantomData,
         }
     }
 
     pub fn border_style(mut self, border_style: Style) -> Self {
-        self.list = self.list.block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(border_style),
-        );
+        self.border_style = border_style;
+        self
+    }
+
+    pub fn filter_style(mut self, filter_style: Style) -> Self {
+        self.filter_style = filter_style;
         self
     }
 
     pub fn highlight_style(mut self, highlight_style: Style) -> Self {
-        self.list = self.list.highlight_style(highlight_style);
+        self.highlight_style = highlight_style;
         self
     }
 }
@@ -127,8 +128,42 @@ where
     type State = SelectMenuState<S>;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let list_height = area.height.saturating_sub(2) as usize;
+        let item_count = state.visible_items().len();
+        state.scroll_to_selected(list_height);
+
+        // only the rows in view are passed to the list, so that it never scrolls by itself
+        let list = List::new(self.items.skip(state.scroll_offset).take(list_height))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(self.border_style),
+            )
+            .highlight_style(self.highlight_style);
+        let mut list_state = ListState::default();
+        list_state.select(state.list_state.selected().map(|n| n - state.scroll_offset));
+
         widgets::Widget::render(Clear, area, buf);
-        widgets::StatefulWidget::render(self.list, area, buf, &mut state.list_state);
+        widgets::StatefulWidget::render(list, area, buf, &mut list_state);
+
+        if area.width > 2 {
+            if state.scroll_offset > 0 {
+                buf.set_string(area.right() - 2, area.top(), "▲", self.border_style);
+            }
+            if state.scroll_offset + list_height < item_count {
+                buf.set_string(area.right() - 2, area.bottom() - 1, "▼", self.border_style);
+            }
+        }
+
+        if !state.filter.is_empty() && area.width > 2 {
+            buf.set_stringn(
+                area.left() + 1,
+                area.top(),
+                format!("/{}", state.filter),
+                (area.width - 2) as usize,
+                self.filter_style,
+            );
+        }
     }
 }
 
@@ -139,8 +174,11 @@ where
 {
     pub active: bool,
     pub allow_empty_selection: bool,
+    filter: String,
     pub items: Vec<T>,
     list_state: ListState,
+    /// Index of the first row in view.
+    scroll_offset: usize,
 }
 
 impl<T> SelectMenuState<T>
@@ -154,26 +192,65 @@ where
         Self {
             active: false,
             allow_empty_selection: false,
+            filter: String::new(),
             items: items.into_iter().collect(),
             list_state: ListState::default(),
+            scroll_offset: 0,
         }
     }
 
+    pub fn filter(&self) -> &str {
+        self.filter.as_str()
+    }
+
+    /// Sets the filter string, moving the highlight to the first matching item.
+    ///
+    /// When the filter is cleared, the highlight stays on the previously highlighted item.
+    pub fn set_filter(&mut self, filter: String) -> anyhow::Result<()> {
+        let selected = self.selected_row();
+
+        self.filter = filter;
+
+        if self.filter.is_empty() {
+            match selected {
+                Some(item) => self.select(item)?,
+                None => self.list_state.select(None),
+            }
+        } else if self.visible_items().is_empty() {
+            self.list_state.select(None);
+        } else {
+            self.select_index(0)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the rows currently displayed in the menu, after filtering.
+    ///
+    /// The empty selection row (if allowed) is represented by `None`, and is only displayed when
+    /// there is no filter.
+    pub fn visible_items(&self) -> Vec<Option<T>> {
+        let empty_row = if self.allow_empty_selection && self.filter.is_empty() {
+            Some(None)
+        } else {
+            None
+        };
+        let filter = self.filter.to_lowercase();
+
+        empty_row
+            .into_iter()
+            .chain(
+                self.items
+                    .iter()
+                    .filter(|item| item.to_string().to_lowercase().contains(&filter))
+                    .cloned()
+                    .map(Some),
+            )
+            .collect()
+    }
+
     pub fn selected(&self) -> Option<T> {
-        self.list_state
-            .selected()
-            .map(|n| {
-                if self.allow_empty_selection {
-                    if n == 0 {
-                        None
-                    } else {
-                        Some(n - 1)
-                    }
-                } else {
-                    Some(n)
-                }
-            })?
-            .map(|n| self.items[n].clone())
+        self.selected_row()?
     }
 
     pub fn selected_index(&self) -> Option<usize> {
@@ -181,20 +258,15 @@ where
     }
 
     pub fn select(&mut self, item: Option<T>) -> anyhow::Result<()> {
-        let n = item.map_or_else(
-            || {
-                ensure!(self.allow_empty_selection, "empty selection not allowed");
-                Ok(0)
-            },
-            |item| {
-                self.items
-                    .iter()
-                    .cloned()
-                    .position(|t| t == item)
-                    .map(|n| if self.allow_empty_selection { n + 1 } else { n })
-                    .with_context(|| "item not found")
-            },
-        )?;
+        if item.is_none() {
+            ensure!(self.allow_empty_selection, "empty selection not allowed");
+        }
+
+        let n = self
+            .visible_items()
+            .iter()
+            .position(|t| *t == item)
+            .with_context(|| "item not found")?;
 
         self.select_index(n)?;
 
@@ -202,17 +274,15 @@ where
     }
 
     pub fn select_prev(&mut self) -> anyhow::Result<()> {
-        let n = self.list_state.selected().map_or_else(
-            || {
-                let l = self.items.len();
-                if self.allow_empty_selection {
-                    l
-                } else {
-                    l - 1
-                }
-            },
-            |n| if n > 0 { n - 1 } else { 0 },
-        );
+        let l = self.visible_items().len();
+        if l == 0 {
+            return Ok(());
+        }
+
+        let n = self
+            .list_state
+            .selected()
+            .map_or(l - 1, |n| if n > 0 { n - 1 } else { 0 });
 
         self.select_index(n)?;
 
@@ -220,15 +290,15 @@ where
     }
 
     pub fn select_next(&mut self) -> anyhow::Result<()> {
-        let n = self.list_state.selected().map_or(0, |n| {
-            let l = self.items.len();
-            let l = if self.allow_empty_selection { l } else { l - 1 };
-            if n < l {
-                n + 1
-            } else {
-                n
-            }
-        });
+        let l = self.visible_items().len();
+        if l == 0 {
+            return Ok(());
+        }
+
+        let n = self
+            .list_state
+            .selected()
+            .map_or(0, |n| if n < l - 1 { n + 1 } else { n });
 
         self.select_index(n)?;
 
@@ -249,16 +319,12 @@ where
         let inner_area = menu_area.inner(&border_margin);
 
         if inner_area.left() <= x
-            && inner_area.right() >= x
+            && inner_area.right() > x
             && inner_area.top() <= y
-            && inner_area.bottom() >= y
+            && inner_area.bottom() > y
         {
-            if (inner_area.height as usize) < self.items.len() {
-                todo!("not sure how to select an item from scrollable list");
-            }
-            let n: usize = (y - inner_area.top()) as usize;
-            let l = self.items.len();
-            let l = if self.allow_empty_selection { l + 1 } else { l };
+            let l = self.visible_items().len();
+            let n = self.scroll_offset + (y - inner_area.top()) as usize;
 
             if n < l {
                 return Some(n);
@@ -267,4 +333,23 @@ where
 
         None
     }
+
+    /// Scrolls the least needed to bring the highlighted row into view.
+    pub fn scroll_to_selected(&mut self, list_height: usize) {
+        self.scroll_offset = match self.list_state.selected() {
+            Some(n) if n < self.scroll_offset => n,
+            Some(n) if list_height > 0 && n >= self.scroll_offset + list_height => {
+                n + 1 - list_height
+            }
+            Some(_) => self.scroll_offset,
+            None => 0,
+        };
+    }
+
+    /// Returns the highlighted row, where `Some(None)` is the empty selection row.
+    fn selected_row(&self) -> Option<Option<T>> {
+        self.list_state
+            .selected()
+            .and_then(|n| self.visible_items().into_iter().nth(n))
+    }
 }
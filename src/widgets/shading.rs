@@ -0,0 +1,28 @@
+use tui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+
+/// Colors the background of cells, keeping what is drawn in them, e.g. to shade the area between
+/// two lines of a chart.
+pub struct Shading<'a> {
+    /// Columns of the cells, with the range of rows to shade in each.
+    columns: &'a [(u16, u16, u16, Color)],
+}
+
+impl<'a> Shading<'a> {
+    /// Takes the columns as `(x, top, bottom, color)`, with the rows from top to bottom inclusive.
+    pub fn new(columns: &'a [(u16, u16, u16, Color)]) -> Self {
+        Self { columns }
+    }
+}
+
+impl<'a> Widget for Shading<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for &(x, top, bottom, color) in self.columns {
+            if x < area.left() || area.right() <= x {
+                continue;
+            }
+            for y in top.max(area.top())..=bottom.min(area.bottom().saturating_sub(1)) {
+                buf.get_mut(x, y).set_bg(color);
+            }
+        }
+    }
+}
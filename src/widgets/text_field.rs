@@ -1,3 +1,4 @@
+use std::cmp;
 use tui::{
     buffer::Buffer,
     layout::{Margin, Rect},
@@ -5,6 +6,11 @@ use tui::{
     widgets::{self, Block, Borders, Clear, Paragraph, Text},
 };
 
+const BORDER_MARGIN: Margin = Margin {
+    horizontal: 1,
+    vertical: 1,
+};
+
 pub struct TextField<'a, 't, T>
 where
     T: Iterator<Item = &'t Text<'t>>,
@@ -35,6 +41,12 @@ where
         self
     }
 
+    pub fn title(mut self, title: &'a str) -> Self {
+        self.block = self.block.title(title);
+        self.paragraph = self.paragraph.block(self.block);
+        self
+    }
+
     pub fn style(mut self, style: Style) -> Self {
         self.block = self.block.style(style);
         self.paragraph = self.paragraph.block(self.block);
@@ -59,24 +71,90 @@ where
 #[derive(Clone, Debug, Default)]
 pub struct TextFieldState {
     pub active: bool,
+    /// The cursor position, as a char index into the value.
+    pub cursor: usize,
     pub value: String,
 }
 
 impl TextFieldState {
+    /// Activates the text field, placing the cursor at the end of the value.
+    pub fn activate(&mut self) {
+        self.active = true;
+        self.cursor = self.value.chars().count();
+    }
+
+    pub fn set_value(&mut self, value: String) {
+        self.value = value;
+        self.move_cursor(self.cursor);
+    }
+
+    pub fn move_cursor(&mut self, cursor: usize) {
+        self.cursor = cmp::min(cursor, self.value.chars().count());
+    }
+
+    /// Inserts a char at the cursor, moving the cursor past it.
+    pub fn insert(&mut self, c: char) {
+        let i = self.byte_index(self.cursor);
+        self.value.insert(i, c);
+        self.cursor += 1;
+    }
+
+    /// Deletes the char before the cursor.
+    pub fn delete_backward(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+
+        let i = self.byte_index(self.cursor - 1);
+        self.value.remove(i);
+        self.cursor -= 1;
+    }
+
+    /// Deletes the char at the cursor.
+    pub fn delete_forward(&mut self) {
+        if self.cursor >= self.value.chars().count() {
+            return;
+        }
+
+        let i = self.byte_index(self.cursor);
+        self.value.remove(i);
+    }
+
     pub fn cursor_point(&self, text_field_area: Rect) -> Option<(u16, u16)> {
         if !self.active {
             return None;
         }
 
-        let border_margin = Margin {
-            horizontal: 1,
-            vertical: 1,
-        };
-        let inner_area = text_field_area.inner(&border_margin);
+        let inner_area = text_field_area.inner(&BORDER_MARGIN);
 
-        let cx = inner_area.left() + self.value.chars().count() as u16;
+        let cx = cmp::min(
+            inner_area.left() + self.cursor as u16,
+            inner_area.right().saturating_sub(1),
+        );
         let cy = inner_area.top();
 
         Some((cx, cy))
     }
+
+    /// Returns the cursor position for a point in the text field, if the point is on the value's
+    /// line.
+    pub fn point_to_cursor(&self, text_field_area: Rect, (x, y): (u16, u16)) -> Option<usize> {
+        let inner_area = text_field_area.inner(&BORDER_MARGIN);
+
+        if y != inner_area.top() || x < inner_area.left() || x >= inner_area.right() {
+            return None;
+        }
+
+        Some(cmp::min(
+            (x - inner_area.left()) as usize,
+            self.value.chars().count(),
+        ))
+    }
+
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(char_index)
+            .map_or(self.value.len(), |(i, _)| i)
+    }
 }